@@ -0,0 +1,220 @@
+//! # sync-format-wasm
+//!
+//! Browser bindings to the `sync-format` parser, so the web viewer applies
+//! the same archive rules as the native tools: stored (uncompressed)
+//! payloads, the required entries of each variant's profile, manifest
+//! migration and signature checks.
+//!
+//! Archives are parsed from memory in hardened mode with the default
+//! [`ArchiveLimits`], since the viewer opens files from anywhere.
+//!
+//! ## Building
+//!
+//! ```sh
+//! wasm-pack build crates/sync-format-wasm --target web
+//! ```
+//!
+//! ## Example
+//!
+//! ```js
+//! import init, { SyncArchive } from "sync-format-wasm";
+//!
+//! await init();
+//! const archive = SyncArchive.open(new Uint8Array(await file.arrayBuffer()));
+//! const manifest = JSON.parse(archive.manifestJson());
+//! const payload = archive.payload();
+//! const signatures = JSON.parse(archive.verifySignatures());
+//! ```
+
+use serde_json::json;
+use sync_format::{
+    verify_manifest_signature_from_reader, verify_provenance_from_reader, ArchiveLimits,
+    ArchiveSignatureStatus, OpenOptions,
+};
+use wasm_bindgen::prelude::*;
+
+/// A `.sync` archive parsed from bytes.
+#[wasm_bindgen]
+pub struct SyncArchive {
+    inner: sync_format::SyncArchive<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl SyncArchive {
+    /// Parse an archive, upgrading an older manifest to the latest version.
+    ///
+    /// Throws if the archive is malformed, exceeds the default limits or
+    /// breaks a format rule.
+    pub fn open(bytes: Vec<u8>) -> Result<SyncArchive, JsError> {
+        let inner = open_options().open_bytes(bytes)?;
+        Ok(Self { inner })
+    }
+
+    /// The manifest as a JSON string.
+    #[wasm_bindgen(js_name = manifestJson)]
+    pub fn manifest_json(&self) -> Result<String, JsError> {
+        Ok(serde_json::to_string(self.inner.manifest())?)
+    }
+
+    /// Names of all entries in the archive.
+    #[wasm_bindgen(js_name = entryNames)]
+    pub fn entry_names(&self) -> Vec<String> {
+        self.inner
+            .entries()
+            .iter()
+            .map(|entry| entry.name.clone())
+            .collect()
+    }
+
+    /// Bytes of the payload entry.
+    pub fn payload(&self) -> Result<Vec<u8>, JsError> {
+        Ok(self.inner.read_payload()?)
+    }
+
+    /// Bytes of a stored entry, e.g. `sync.wasm` or `context.json`.
+    pub fn entry(&self, name: &str) -> Result<Vec<u8>, JsError> {
+        Ok(self.inner.entry_reader(name)?.read_all()?)
+    }
+
+    /// Check the embedded `sync.sig` signature, the manifest `[signature]`
+    /// section and the `[[provenance]]` log.
+    ///
+    /// Returns a JSON string:
+    ///
+    /// ```json
+    /// {
+    ///   "archive": { "status": "unsigned" | "valid" | "invalid", "publicKey": ..., "error": ... },
+    ///   "manifest": { "valid": ..., "manifestHash": ..., "payloadHash": ..., "error": ... },
+    ///   "provenance": { "valid": ..., "anchored": ..., "fullySigned": ..., "error": ... }
+    /// }
+    /// ```
+    #[wasm_bindgen(js_name = verifySignatures)]
+    pub fn verify_signatures(&self) -> Result<String, JsError> {
+        let archive = match self.inner.signature_status() {
+            ArchiveSignatureStatus::Unsigned => json!({ "status": "unsigned" }),
+            ArchiveSignatureStatus::Valid { public_key } => {
+                json!({ "status": "valid", "publicKey": public_key })
+            }
+            ArchiveSignatureStatus::Invalid(error) => {
+                json!({ "status": "invalid", "error": error })
+            }
+        };
+
+        let manifest = verify_manifest_signature_from_reader(self.inner.archive_reader()?)?;
+        let provenance = verify_provenance_from_reader(self.inner.archive_reader()?)?;
+
+        Ok(json!({
+            "archive": archive,
+            "manifest": {
+                "valid": manifest.valid,
+                "manifestHash": manifest.manifest_hash,
+                "payloadHash": manifest.payload_hash,
+                "error": manifest.error,
+            },
+            "provenance": {
+                "valid": provenance.valid,
+                "anchored": provenance.anchored,
+                "fullySigned": provenance.is_fully_signed(),
+                "error": provenance.error,
+            },
+        })
+        .to_string())
+    }
+}
+
+/// The options every archive is opened with: migrated and hardened.
+fn open_options() -> OpenOptions {
+    OpenOptions::new()
+        .migrate(true)
+        .hardened(ArchiveLimits::default())
+}
+
+// `JsError` needs a JavaScript host, so native tests only exercise the
+// success paths of the bindings and check failures on the underlying calls.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use sync_format::{SyncBuilder, SyncManifest, SyncSigner};
+    use tempfile::tempdir;
+
+    fn manifest() -> SyncManifest {
+        SyncManifest::from_toml(
+            br#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "viewer-test"
+hash_algo = "blake3"
+
+[sync]
+version = "1.2"
+content_type = "text/csv"
+display_ext = "csv"
+
+[policy]
+ttl = 60
+timeout = 5
+"#,
+        )
+        .unwrap()
+    }
+
+    fn build(builder: SyncBuilder) -> Vec<u8> {
+        let dir = tempdir().unwrap();
+        let path = builder
+            .with_payload_bytes(b"id,value\n1,viewer\n".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .write_to(dir.path().join("viewer.sync"))
+            .unwrap();
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_open_reads_manifest_and_payload() {
+        let archive =
+            SyncArchive::open(build(SyncBuilder::new().with_manifest(manifest()))).unwrap();
+
+        let manifest: Value = serde_json::from_str(&archive.manifest_json().unwrap()).unwrap();
+        assert_eq!(manifest["sync"]["version"], "1.3");
+        assert_eq!(manifest["sync"]["content_type"], "text/csv");
+        assert_eq!(manifest["sync"]["variant"], "plain");
+
+        assert_eq!(archive.payload().unwrap(), b"id,value\n1,viewer\n");
+        assert_eq!(archive.entry("sync.wasm").unwrap(), b"\0asm\x01\0\0\0");
+        assert!(archive.entry_names().contains(&"manifest.toml".to_string()));
+    }
+
+    #[test]
+    fn test_verify_signatures_reports_each_check() {
+        let unsigned =
+            SyncArchive::open(build(SyncBuilder::new().with_manifest(manifest()))).unwrap();
+        let report: Value = serde_json::from_str(&unsigned.verify_signatures().unwrap()).unwrap();
+        assert_eq!(report["archive"]["status"], "unsigned");
+        assert_eq!(report["manifest"]["valid"], false);
+
+        let signer = SyncSigner::from_bytes(&[7u8; 32]);
+        let signed = SyncArchive::open(build(
+            SyncBuilder::new()
+                .with_manifest(manifest())
+                .with_signer(signer),
+        ))
+        .unwrap();
+        let report: Value = serde_json::from_str(&signed.verify_signatures().unwrap()).unwrap();
+        assert_eq!(report["manifest"]["valid"], true, "{report}");
+        assert_eq!(report["provenance"]["valid"], true, "{report}");
+        assert_eq!(report["provenance"]["anchored"], true, "{report}");
+    }
+
+    #[test]
+    fn test_open_enforces_hardened_limits() {
+        let mut oversized = manifest();
+        oversized
+            .meta
+            .extra
+            .insert("padding".to_string(), "x".repeat(2 * 1024 * 1024).into());
+        let bytes = build(SyncBuilder::new().with_manifest(oversized));
+
+        let err = open_options().open_bytes(bytes).unwrap_err();
+        assert!(matches!(err, sync_format::Error::LimitExceeded(_)), "{err}");
+    }
+}
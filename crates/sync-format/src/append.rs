@@ -0,0 +1,494 @@
+//! In-place payload updates.
+//!
+//! Rewriting a whole archive costs O(archive size) per payload refresh. For
+//! large, frequently refreshed archives the payload (and the manifest that
+//! records the update) is instead written at the end of the archive,
+//! followed by a fresh central directory that points at the new entries and
+//! at the unchanged local entries.
+//!
+//! When the replaced entries are already the last local entries, the archive
+//! is truncated where they start and they are written again in place, so a
+//! trailing payload does not leave a dead copy behind. Otherwise the new
+//! entries are appended past the current end of file. Either way the payload
+//! data start is padded to [`PAYLOAD_ALIGNMENT`], like the builder does.
+//!
+//! A journal file next to the archive holds the original length and the
+//! bytes about to be overwritten while an update is in flight; [`recover`]
+//! puts them back if the process died before the update was committed.
+//! Updates and recovery hold an exclusive lock on the archive file, so
+//! concurrent writers wait for each other instead of undoing each other's
+//! work.
+//!
+//! Superseded entries and central directories that are not at the end are
+//! left behind as dead space. Once dead space would outgrow the live
+//! archive, the caller falls back to a full rewrite, which compacts the
+//! archive again.
+
+use crate::builder::PAYLOAD_ALIGNMENT;
+use crate::error::Result;
+use crate::records::{
+    name_len, read_central_record, u16_at, u32_at, CENTRAL_HEADER_LEN, CENTRAL_HEADER_SIGNATURE,
+    END_OF_CENTRAL_DIRECTORY_LEN, END_OF_CENTRAL_DIRECTORY_SIGNATURE, LOCAL_HEADER_LEN,
+    LOCAL_HEADER_SIGNATURE,
+};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// General purpose flag: sizes and CRC follow the data in a data descriptor.
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+/// Largest value a zip32 size or offset field can hold without zip64.
+const ZIP32_MAX: u64 = 0xFFFF_FFFE;
+/// Extra field id of alignment padding (APPNOTE 4.6.11), as the zip crate
+/// writes it.
+const ALIGNMENT_EXTRA_ID: u16 = 0xa11e;
+/// Journal header: original length, write start and CRC-32 of the saved bytes.
+const JOURNAL_HEADER_LEN: u64 = 20;
+
+/// Path of the journal that guards an in-flight append.
+pub(crate) fn journal_path(archive_path: &Path) -> PathBuf {
+    archive_path.with_extension("sync.journal")
+}
+
+/// Roll back an update that was interrupted before it committed.
+///
+/// Only write paths call this: it needs write access, and it must not run
+/// while another process holds the lock mid-update.
+pub(crate) fn recover(archive_path: &Path) -> Result<()> {
+    if !journal_path(archive_path).exists() {
+        return Ok(());
+    }
+    let file = lock_archive(archive_path)?;
+    recover_locked(archive_path, &file)
+}
+
+/// Roll back an interrupted update of `file`, which the caller has locked.
+///
+/// The journal stores the archive length from before the update and the
+/// bytes from where the update started writing. Putting them back and
+/// truncating restores the previous archive exactly. A journal that was not
+/// fully written means the update never touched the archive.
+fn recover_locked(archive_path: &Path, file: &File) -> Result<()> {
+    let journal_path = journal_path(archive_path);
+    let mut journal = match File::open(&journal_path) {
+        Ok(journal) => journal,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut header = [0u8; JOURNAL_HEADER_LEN as usize];
+    let complete = journal.read_exact(&mut header).is_ok();
+    let original_len = u64::from_le_bytes(header[0..8].try_into().expect("8 bytes"));
+    let write_start = u64::from_le_bytes(header[8..16].try_into().expect("8 bytes"));
+    let crc = u32_at(&header, 16);
+    let saved_len = original_len.checked_sub(write_start);
+
+    let complete = complete
+        && saved_len.is_some_and(|len| {
+            journal.metadata().ok().map(|m| m.len()) == Some(JOURNAL_HEADER_LEN + len)
+        })
+        && copy_with_crc(&mut journal, &mut io::sink())? == crc;
+    if complete {
+        journal.seek(SeekFrom::Start(JOURNAL_HEADER_LEN))?;
+        let mut out = file;
+        out.seek(SeekFrom::Start(write_start))?;
+        copy_with_crc(&mut journal, &mut out)?;
+        file.set_len(original_len)?;
+        file.sync_all()?;
+    }
+
+    drop(journal);
+    fs::remove_file(&journal_path)?;
+    sync_parent_dir(&journal_path);
+    Ok(())
+}
+
+/// Open the archive for writing and take an exclusive lock on it, waiting
+/// for any other update to finish. The lock is released when the file is
+/// closed.
+fn lock_archive(archive_path: &Path) -> Result<File> {
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(archive_path)?;
+    file.lock()?;
+    Ok(file)
+}
+
+/// Write new contents for existing entries to the archive at `archive_path`
+/// and commit a new central directory.
+///
+/// `entries` maps entry names to their new contents; each must already be in
+/// the archive, stored uncompressed. The data is written in the given order.
+///
+/// Returns `Ok(false)` without touching the file when the archive does not
+/// qualify for the fast path; the caller should rewrite the archive instead.
+pub(crate) fn append_entries(archive_path: &Path, entries: &[(&str, &[u8])]) -> Result<bool> {
+    let file = lock_archive(archive_path)?;
+    recover_locked(archive_path, &file)?;
+
+    let Some(plan) = AppendPlan::prepare(&file, entries)? else {
+        return Ok(false);
+    };
+
+    let journal = journal_path(archive_path);
+    write_journal(&journal, &file, plan.original_len, plan.write_start)?;
+
+    match plan.commit(&file, entries) {
+        Ok(()) => {
+            fs::remove_file(&journal)?;
+            sync_parent_dir(archive_path);
+            Ok(true)
+        }
+        Err(e) => {
+            recover_locked(archive_path, &file)?;
+            Err(e)
+        }
+    }
+}
+
+/// Everything needed to append entries, gathered before any write.
+struct AppendPlan {
+    /// Archive length before the update.
+    original_len: u64,
+    /// Where the new data starts: the first of the replaced entries at the
+    /// end of the archive, or `original_len` if there are none.
+    write_start: u64,
+    /// Central directory records, in their original order.
+    central_records: Vec<CentralRecord>,
+    /// Archive comment, carried over into the new end record.
+    comment: Vec<u8>,
+}
+
+enum CentralRecord {
+    /// An unchanged entry, kept as its raw record.
+    Kept(Vec<u8>),
+    /// A replaced entry, regenerated during commit from the template.
+    Replaced {
+        /// Index into the appended entries.
+        entry: usize,
+        template: EntryTemplate,
+    },
+}
+
+/// Fields copied from the record of a replaced entry.
+struct EntryTemplate {
+    version_made_by: u16,
+    version_needed: u16,
+    flags: u16,
+    modified_time: u16,
+    modified_date: u16,
+    internal_attributes: u16,
+    external_attributes: u32,
+}
+
+impl AppendPlan {
+    fn prepare(file: &File, entries: &[(&str, &[u8])]) -> Result<Option<Self>> {
+        let mut file = file;
+        let original_len = file.metadata()?.len();
+        let mut archive = zip::ZipArchive::new(&mut file)?;
+
+        // Prepended data shifts every offset; leave that to the rewrite.
+        if archive.offset() != 0 || archive.len() > usize::from(u16::MAX) {
+            return Ok(None);
+        }
+
+        let central_start = archive.central_directory_start();
+        let comment = archive.comment().to_vec();
+        let count = archive.len();
+
+        let position = |name: &[u8]| entries.iter().position(|(n, _)| n.as_bytes() == name);
+
+        let mut live_len = 0u64;
+        let mut local_entries = Vec::with_capacity(count);
+        for i in 0..count {
+            let entry = archive.by_index_raw(i)?;
+            let replaced = position(entry.name_raw()).is_some();
+            if !replaced {
+                live_len += entry.data_start() - entry.header_start() + entry.compressed_size();
+            }
+            local_entries.push((entry.header_start(), replaced));
+        }
+        drop(archive);
+
+        // Replaced entries at the end of the local data, and the dead space
+        // and central directory after them, are overwritten in place.
+        local_entries.sort_unstable();
+        let write_start = local_entries
+            .iter()
+            .rev()
+            .take_while(|(_, replaced)| *replaced)
+            .last()
+            .map_or(original_len, |(header_start, _)| *header_start);
+
+        file.seek(SeekFrom::Start(central_start))?;
+        let mut reader = io::BufReader::new(&mut file);
+        let mut central_records = Vec::with_capacity(count);
+        let mut found = vec![false; entries.len()];
+        let mut central_len = 0u64;
+
+        for _ in 0..count {
+            let record = read_central_record(&mut reader)?;
+            let flags = u16_at(&record, 8);
+            if flags & FLAG_DATA_DESCRIPTOR != 0 {
+                // Entry lengths above do not account for data descriptors.
+                return Ok(None);
+            }
+
+            let name = &record[CENTRAL_HEADER_LEN..CENTRAL_HEADER_LEN + name_len(&record)];
+            match position(name) {
+                Some(entry) => {
+                    if u16_at(&record, 10) != 0 || found[entry] {
+                        return Ok(None);
+                    }
+                    found[entry] = true;
+                    central_len += (CENTRAL_HEADER_LEN + name.len()) as u64;
+                    central_records.push(CentralRecord::Replaced {
+                        entry,
+                        template: EntryTemplate {
+                            version_made_by: u16_at(&record, 4),
+                            version_needed: u16_at(&record, 6),
+                            flags,
+                            modified_time: u16_at(&record, 12),
+                            modified_date: u16_at(&record, 14),
+                            internal_attributes: u16_at(&record, 36),
+                            external_attributes: u32_at(&record, 38),
+                        },
+                    });
+                }
+                None => {
+                    central_len += record.len() as u64;
+                    central_records.push(CentralRecord::Kept(record));
+                }
+            }
+        }
+
+        if found.contains(&false) {
+            return Ok(None);
+        }
+
+        let mut local_len = 0u64;
+        for (name, data) in entries {
+            // The new records must fit in zip32 fields.
+            if data.len() as u64 > ZIP32_MAX {
+                return Ok(None);
+            }
+            let extra_len = alignment_padding(name, write_start + local_len);
+            local_len += LOCAL_HEADER_LEN + name.len() as u64 + extra_len + data.len() as u64;
+        }
+        let central_offset = write_start + local_len;
+        let new_len =
+            central_offset + central_len + END_OF_CENTRAL_DIRECTORY_LEN + comment.len() as u64;
+
+        if central_offset > ZIP32_MAX || central_len > ZIP32_MAX {
+            return Ok(None);
+        }
+
+        // Compact once superseded bytes would outweigh the live archive. The
+        // old payload's alignment padding is dead too, so allow one alignment
+        // unit of slack for small archives.
+        let live_len = live_len
+            + local_len
+            + central_len
+            + END_OF_CENTRAL_DIRECTORY_LEN
+            + comment.len() as u64;
+        if new_len - live_len > live_len + u64::from(PAYLOAD_ALIGNMENT) {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            original_len,
+            write_start,
+            central_records,
+            comment,
+        }))
+    }
+
+    fn commit(&self, file: &File, entries: &[(&str, &[u8])]) -> Result<()> {
+        // Nothing past the write start stays valid, including the old end
+        // record, so readers never pick up a stale central directory.
+        file.set_len(self.write_start)?;
+        let mut file = file;
+        file.seek(SeekFrom::Start(self.write_start))?;
+
+        let mut out = io::BufWriter::new(file);
+
+        // Local headers and data, in the order given.
+        let mut written = Vec::with_capacity(entries.len());
+        let mut offset = self.write_start;
+        for (i, (name, data)) in entries.iter().enumerate() {
+            let template = self
+                .central_records
+                .iter()
+                .find_map(|record| match record {
+                    CentralRecord::Replaced { entry, template } if *entry == i => Some(template),
+                    _ => None,
+                })
+                .expect("every appended entry has a central record");
+            let crc = crc32fast::hash(data);
+            let size = data.len() as u32;
+            let flags = template.flags & !FLAG_DATA_DESCRIPTOR;
+            let extra = alignment_extra(alignment_padding(name, offset));
+
+            out.write_all(&LOCAL_HEADER_SIGNATURE.to_le_bytes())?;
+            out.write_all(&template.version_needed.to_le_bytes())?;
+            out.write_all(&flags.to_le_bytes())?;
+            out.write_all(&0u16.to_le_bytes())?; // stored
+            out.write_all(&template.modified_time.to_le_bytes())?;
+            out.write_all(&template.modified_date.to_le_bytes())?;
+            out.write_all(&crc.to_le_bytes())?;
+            out.write_all(&size.to_le_bytes())?;
+            out.write_all(&size.to_le_bytes())?;
+            out.write_all(&(name.len() as u16).to_le_bytes())?;
+            out.write_all(&(extra.len() as u16).to_le_bytes())?;
+            out.write_all(name.as_bytes())?;
+            out.write_all(&extra)?;
+            out.write_all(data)?;
+
+            written.push((offset as u32, crc, size));
+            offset += LOCAL_HEADER_LEN + name.len() as u64 + extra.len() as u64 + data.len() as u64;
+        }
+
+        // Central directory, keeping the original entry order.
+        let central_offset = offset;
+        let mut central_len = 0u64;
+        for record in &self.central_records {
+            match record {
+                CentralRecord::Kept(record) => {
+                    out.write_all(record)?;
+                    central_len += record.len() as u64;
+                }
+                CentralRecord::Replaced { entry, template } => {
+                    let name = entries[*entry].0;
+                    let (local_offset, crc, size) = written[*entry];
+                    let flags = template.flags & !FLAG_DATA_DESCRIPTOR;
+                    out.write_all(&CENTRAL_HEADER_SIGNATURE.to_le_bytes())?;
+                    out.write_all(&template.version_made_by.to_le_bytes())?;
+                    out.write_all(&template.version_needed.to_le_bytes())?;
+                    out.write_all(&flags.to_le_bytes())?;
+                    out.write_all(&0u16.to_le_bytes())?; // stored
+                    out.write_all(&template.modified_time.to_le_bytes())?;
+                    out.write_all(&template.modified_date.to_le_bytes())?;
+                    out.write_all(&crc.to_le_bytes())?;
+                    out.write_all(&size.to_le_bytes())?;
+                    out.write_all(&size.to_le_bytes())?;
+                    out.write_all(&(name.len() as u16).to_le_bytes())?;
+                    out.write_all(&0u16.to_le_bytes())?; // extra field
+                    out.write_all(&0u16.to_le_bytes())?; // comment
+                    out.write_all(&0u16.to_le_bytes())?; // disk number
+                    out.write_all(&template.internal_attributes.to_le_bytes())?;
+                    out.write_all(&template.external_attributes.to_le_bytes())?;
+                    out.write_all(&local_offset.to_le_bytes())?;
+                    out.write_all(name.as_bytes())?;
+                    central_len += (CENTRAL_HEADER_LEN + name.len()) as u64;
+                }
+            }
+        }
+
+        // End of central directory. Writing it is the commit point.
+        let count = self.central_records.len() as u16;
+        out.write_all(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes())?;
+        out.write_all(&0u16.to_le_bytes())?; // this disk
+        out.write_all(&0u16.to_le_bytes())?; // central directory disk
+        out.write_all(&count.to_le_bytes())?;
+        out.write_all(&count.to_le_bytes())?;
+        out.write_all(&(central_len as u32).to_le_bytes())?;
+        out.write_all(&(central_offset as u32).to_le_bytes())?;
+        out.write_all(&(self.comment.len() as u16).to_le_bytes())?;
+        out.write_all(&self.comment)?;
+
+        out.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Length of the extra field that aligns the data of `name`, whose local
+/// header starts at `header_start`.
+///
+/// Only the payload is aligned, to [`PAYLOAD_ALIGNMENT`]. The padding is at
+/// least an extra field header plus the alignment value, as in the zip crate.
+fn alignment_padding(name: &str, header_start: u64) -> u64 {
+    if name != "payload" {
+        return 0;
+    }
+    let align = u64::from(PAYLOAD_ALIGNMENT);
+    let unaligned = (header_start + LOCAL_HEADER_LEN + name.len() as u64) % align;
+    if unaligned == 0 {
+        return 0;
+    }
+    let mut padding = align - unaligned;
+    while padding < 6 {
+        padding += align;
+    }
+    padding
+}
+
+/// Build an alignment padding extra field of `len` bytes.
+fn alignment_extra(len: u64) -> Vec<u8> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let mut extra = vec![0u8; len as usize];
+    extra[0..2].copy_from_slice(&ALIGNMENT_EXTRA_ID.to_le_bytes());
+    extra[2..4].copy_from_slice(&((len - 4) as u16).to_le_bytes());
+    extra[4..6].copy_from_slice(&PAYLOAD_ALIGNMENT.to_le_bytes());
+    extra
+}
+
+/// Save the archive bytes from `write_start` on, with the original length,
+/// so [`recover_locked`] can undo the update.
+pub(crate) fn write_journal(
+    path: &Path,
+    archive: &File,
+    original_len: u64,
+    write_start: u64,
+) -> Result<()> {
+    let mut journal = File::create(path)?;
+    journal.write_all(&[0u8; JOURNAL_HEADER_LEN as usize])?;
+    let mut archive = archive;
+    archive.seek(SeekFrom::Start(write_start))?;
+    let crc = copy_with_crc(&mut archive.take(original_len - write_start), &mut journal)?;
+
+    journal.seek(SeekFrom::Start(0))?;
+    journal.write_all(&original_len.to_le_bytes())?;
+    journal.write_all(&write_start.to_le_bytes())?;
+    journal.write_all(&crc.to_le_bytes())?;
+    journal.sync_all()?;
+    sync_parent_dir(path);
+    Ok(())
+}
+
+/// Copy everything from `reader` to `writer`, returning its CRC-32.
+fn copy_with_crc<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> io::Result<u32> {
+    let mut hasher = crc32fast::Hasher::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = match reader.read(&mut buf) {
+            Ok(0) => return Ok(hasher.finalize()),
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        hasher.update(&buf[..n]);
+        writer.write_all(&buf[..n])?;
+    }
+}
+
+/// Make a journal creation or removal durable. Best effort: not every
+/// platform can open a directory for syncing.
+fn sync_parent_dir(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
@@ -1,17 +1,174 @@
-use crate::{Error, Manifest, Result};
-use std::fs::File;
-use std::io::Write;
+use crate::{Error, Manifest, Result, PAYLOAD_DIR};
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use zip::{write::FileOptions, ZipWriter};
 
+/// Alignment of the payload data start in archives written by [`SyncBuilder`].
+///
+/// The padding is stored in the payload's local extra field, so the archive
+/// stays a standard ZIP. 16 KiB covers both 4 KiB and 16 KiB page sizes.
+pub const PAYLOAD_ALIGNMENT: u16 = 16384;
+
+/// Unix permissions of every entry in a reproducible archive.
+pub(crate) const REPRODUCIBLE_PERMISSIONS: u32 = 0o644;
+
+/// Payload entries to write: archive name and source.
+type PayloadEntries<'a> = Vec<(String, Cow<'a, EntrySource>)>;
+
+/// Entries written after the payload: archive name and source.
+type AuxEntries<'a> = Vec<(&'static str, Cow<'a, EntrySource>)>;
+
+/// A one-shot reader shared between builder clones.
+type SharedReader = Arc<Mutex<Option<Box<dyn Read + Send>>>>;
+
+/// Source of an entry's bytes.
+#[derive(Clone)]
+enum EntrySource {
+    /// Bytes held in memory.
+    Bytes(Vec<u8>),
+    /// A file streamed from disk on each write.
+    File(PathBuf),
+    /// A reader streamed once, on the first write.
+    Reader(SharedReader),
+    /// Another source, encrypted for a vault as it is streamed.
+    #[cfg(feature = "encryption")]
+    Encrypted(Box<EntrySource>, crate::vault::Protection),
+}
+
+impl std::fmt::Debug for EntrySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EntrySource::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+            EntrySource::File(path) => write!(f, "File({})", path.display()),
+            EntrySource::Reader(_) => write!(f, "Reader"),
+            #[cfg(feature = "encryption")]
+            EntrySource::Encrypted(inner, _) => write!(f, "Encrypted({:?})", inner),
+        }
+    }
+}
+
+impl EntrySource {
+    fn reader(reader: impl Read + Send + 'static) -> Self {
+        EntrySource::Reader(Arc::new(Mutex::new(Some(Box::new(reader)))))
+    }
+
+    /// Stream the entry bytes into `writer`.
+    fn copy_to<W: Write + ?Sized>(&self, writer: &mut W) -> Result<u64> {
+        match self {
+            EntrySource::Bytes(bytes) => {
+                writer.write_all(bytes)?;
+                Ok(bytes.len() as u64)
+            }
+            EntrySource::File(path) => {
+                let mut file = File::open(path)?;
+                Ok(io::copy(&mut file, writer)?)
+            }
+            EntrySource::Reader(shared) => {
+                let mut reader = shared
+                    .lock()
+                    .map_err(|_| io::Error::other("entry reader lock poisoned"))?
+                    .take()
+                    .ok_or_else(|| io::Error::other("entry reader was already consumed"))?;
+                Ok(io::copy(&mut reader, writer)?)
+            }
+            #[cfg(feature = "encryption")]
+            EntrySource::Encrypted(inner, protection) => {
+                let mut encrypting = protection.encrypt_writer(writer)?;
+                // Through `dyn Write`, so nested sources do not nest the type.
+                let copied = inner.copy_to::<dyn Write>(&mut encrypting)?;
+                encrypting.finish()?;
+                Ok(copied)
+            }
+        }
+    }
+
+    /// Compute the "blake3:<hex>" hash of the entry bytes.
+    ///
+    /// A reader can only be consumed once, and encryption is randomized, so
+    /// both are spooled to a temporary file in `spool_dir` while hashing and
+    /// replaced with that file.
+    #[cfg(feature = "signatures")]
+    fn hash(&self, spool_dir: &Path) -> Result<(String, Option<tempfile::TempPath>)> {
+        let mut hasher = blake3::Hasher::new();
+        let spooled = match self {
+            EntrySource::Bytes(_) | EntrySource::File(_) => {
+                self.copy_to(&mut hasher)?;
+                None
+            }
+            _ => {
+                let mut spool = tempfile::Builder::new()
+                    .prefix(".tmp.payload-")
+                    .tempfile_in(spool_dir)?;
+                let mut tee = TeeWriter {
+                    inner: spool.as_file_mut(),
+                    hasher: &mut hasher,
+                };
+                self.copy_to(&mut tee)?;
+                Some(spool.into_temp_path())
+            }
+        };
+        Ok((
+            format!("blake3:{}", hex::encode(hasher.finalize().as_bytes())),
+            spooled,
+        ))
+    }
+}
+
+/// Writer that hashes everything written through it.
+#[cfg(feature = "signatures")]
+struct TeeWriter<'a, W: Write> {
+    inner: W,
+    hasher: &'a mut blake3::Hasher,
+}
+
+#[cfg(feature = "signatures")]
+impl<W: Write> Write for TeeWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 /// Builder for creating `.sync` archives.
+///
+/// Each entry can come from bytes in memory, a file path, or any `Read`
+/// source. File and reader sources are streamed into the archive, so the
+/// payload never has to fit in memory. Readers are consumed by the first
+/// [`write_to`](Self::write_to).
+///
+/// The payload is either a single entry (`with_payload_*`) or a directory
+/// tree of files under [`PAYLOAD_DIR`] (`add_payload_*`).
+///
+/// With the `encryption` feature, [`with_vault`](Self::with_vault) writes a
+/// vault whose payload, and any entries selected with
+/// [`encrypt_entry`](Self::encrypt_entry), are encrypted on the way in.
+///
+/// With [`reproducible`](Self::reproducible), the same inputs always give
+/// the same archive bytes.
 #[derive(Debug, Default, Clone)]
 pub struct SyncBuilder {
     manifest: Option<Manifest>,
-    payload: Option<Vec<u8>>,
-    context: Option<Vec<u8>>,
-    wasm: Option<Vec<u8>>,
-    proof: Option<Vec<u8>>,
+    payload: Option<EntrySource>,
+    payload_files: Vec<(String, EntrySource)>,
+    context: Option<EntrySource>,
+    wasm: Option<EntrySource>,
+    proof: Option<EntrySource>,
+    reproducible: bool,
+    #[cfg(feature = "signatures")]
+    signer: Option<crate::SyncSigner>,
+    #[cfg(feature = "encryption")]
+    vault: Option<crate::vault::Protection>,
+    #[cfg(feature = "encryption")]
+    encrypted_entries: Vec<String>,
 }
 
 impl SyncBuilder {
@@ -28,84 +185,452 @@ impl SyncBuilder {
 
     /// Set the payload as raw bytes.
     pub fn with_payload_bytes(mut self, payload: impl Into<Vec<u8>>) -> Self {
-        self.payload = Some(payload.into());
+        self.payload = Some(EntrySource::Bytes(payload.into()));
+        self
+    }
+
+    /// Stream the payload from a reader.
+    pub fn with_payload_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.payload = Some(EntrySource::reader(reader));
+        self
+    }
+
+    /// Stream the payload from a file.
+    pub fn with_payload_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.payload = Some(EntrySource::File(path.into()));
+        self
+    }
+
+    /// Add a file to a multi-file payload, streamed from `source` on disk.
+    ///
+    /// `path_in_archive` is relative to [`PAYLOAD_DIR`] and uses `/` as the
+    /// separator, e.g. `"tables/users.csv"`.
+    pub fn add_payload_file<S: Into<String>, P: Into<PathBuf>>(
+        mut self,
+        path_in_archive: S,
+        source: P,
+    ) -> Self {
+        self.payload_files
+            .push((path_in_archive.into(), EntrySource::File(source.into())));
+        self
+    }
+
+    /// Add a file to a multi-file payload from raw bytes.
+    pub fn add_payload_bytes<S: Into<String>>(
+        mut self,
+        path_in_archive: S,
+        bytes: impl Into<Vec<u8>>,
+    ) -> Self {
+        self.payload_files
+            .push((path_in_archive.into(), EntrySource::Bytes(bytes.into())));
+        self
+    }
+
+    /// Add a file to a multi-file payload, streamed from a reader.
+    pub fn add_payload_reader<S: Into<String>>(
+        mut self,
+        path_in_archive: S,
+        reader: impl Read + Send + 'static,
+    ) -> Self {
+        self.payload_files
+            .push((path_in_archive.into(), EntrySource::reader(reader)));
         self
     }
 
     /// Set the context as raw bytes.
     pub fn with_context_bytes(mut self, context: impl Into<Vec<u8>>) -> Self {
-        self.context = Some(context.into());
+        self.context = Some(EntrySource::Bytes(context.into()));
         self
     }
 
     /// Set the context from a JSON value.
     pub fn with_context_json(mut self, context: &serde_json::Value) -> Result<Self> {
         let data = serde_json::to_vec(context).map_err(Error::JsonError)?;
-        self.context = Some(data);
+        self.context = Some(EntrySource::Bytes(data));
         Ok(self)
     }
 
+    /// Stream the context from a reader.
+    pub fn with_context_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.context = Some(EntrySource::reader(reader));
+        self
+    }
+
+    /// Stream the context from a file.
+    pub fn with_context_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.context = Some(EntrySource::File(path.into()));
+        self
+    }
+
     /// Set the WASM module as raw bytes.
     pub fn with_wasm_bytes(mut self, wasm: impl Into<Vec<u8>>) -> Self {
-        self.wasm = Some(wasm.into());
+        self.wasm = Some(EntrySource::Bytes(wasm.into()));
+        self
+    }
+
+    /// Stream the WASM module from a reader.
+    pub fn with_wasm_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.wasm = Some(EntrySource::reader(reader));
+        self
+    }
+
+    /// Stream the WASM module from a file.
+    pub fn with_wasm_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.wasm = Some(EntrySource::File(path.into()));
         self
     }
 
     /// Set the proof as raw bytes.
     pub fn with_proof_bytes(mut self, proof: impl Into<Vec<u8>>) -> Self {
-        self.proof = Some(proof.into());
+        self.proof = Some(EntrySource::Bytes(proof.into()));
+        self
+    }
+
+    /// Stream the proof from a reader.
+    pub fn with_proof_reader(mut self, reader: impl Read + Send + 'static) -> Self {
+        self.proof = Some(EntrySource::reader(reader));
+        self
+    }
+
+    /// Stream the proof from a file.
+    pub fn with_proof_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.proof = Some(EntrySource::File(path.into()));
+        self
+    }
+
+    /// Write the same bytes for the same inputs.
+    ///
+    /// Entries get a fixed timestamp (1980-01-01, the ZIP epoch) and fixed
+    /// permissions, payload directory files are written sorted by path, and
+    /// the manifest is [canonicalized](Manifest::canonicalize) before it is
+    /// signed. Signature timestamps come from the signer's clock, so sign
+    /// with a [`FixedClock`](crate::FixedClock) to reproduce signed archives.
+    ///
+    /// Vault encryption is randomized, so vaults cannot be written in this
+    /// mode.
+    pub fn reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Sign the manifest with the given signer when writing.
+    ///
+    /// Requires the `signatures` feature.
+    #[cfg(feature = "signatures")]
+    pub fn with_signer(mut self, signer: crate::SyncSigner) -> Self {
+        self.signer = Some(signer);
+        self
+    }
+
+    /// Write a vault archive, encrypting the payload with `protection`.
+    ///
+    /// The manifest is marked as a vault and its `[encryption]` section is
+    /// filled in. Only a single payload can be encrypted, not a payload
+    /// directory.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn with_vault(mut self, protection: crate::vault::Protection) -> Self {
+        self.vault = Some(protection);
+        self
+    }
+
+    /// Also encrypt `name`, one of
+    /// [`ENCRYPTABLE_ENTRIES`](crate::vault::ENCRYPTABLE_ENTRIES), in a vault.
+    ///
+    /// Requires the `encryption` feature and [`with_vault`](Self::with_vault).
+    #[cfg(feature = "encryption")]
+    pub fn encrypt_entry(mut self, name: &str) -> Self {
+        if !self.encrypted_entries.iter().any(|entry| entry == name) {
+            self.encrypted_entries.push(name.to_string());
+        }
         self
     }
 
     /// Write the archive to the specified path.
+    ///
+    /// The archive is written to a temporary file in the same directory and
+    /// renamed into place, so readers never observe a partial archive.
     pub fn write_to<P: AsRef<Path>>(&self, path: P) -> Result<PathBuf> {
         let manifest = self
             .manifest
             .as_ref()
             .ok_or_else(|| Error::MissingEntry("manifest.toml".to_string()))?;
-        let payload = self
-            .payload
-            .as_ref()
-            .ok_or_else(|| Error::MissingEntry("payload".to_string()))?;
-        let wasm = self
-            .wasm
-            .as_ref()
-            .ok_or_else(|| Error::MissingEntry("sync.wasm".to_string()))?;
+        let mut payload = self.payload_entries()?;
+        #[cfg_attr(
+            not(any(feature = "encryption", feature = "signatures")),
+            allow(unused_mut)
+        )]
+        let mut aux = self.aux_entries();
 
-        let manifest_text =
-            toml::to_string_pretty(manifest).map_err(|e| Error::ManifestError(e.to_string()))?;
+        let canonical;
+        let manifest = if self.reproducible {
+            #[cfg(feature = "encryption")]
+            if self.vault.is_some() {
+                return Err(Error::InvalidFormat(
+                    "vault archives cannot be written reproducibly".to_string(),
+                ));
+            }
+            payload.sort_by(|(a, _), (b, _)| a.cmp(b));
+            let mut manifest = manifest.clone();
+            manifest.canonicalize();
+            canonical = manifest;
+            &canonical
+        } else {
+            manifest
+        };
 
         let path = path.as_ref();
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::create_dir_all(parent)?;
+
+        #[cfg(feature = "encryption")]
+        let vault_manifest = self.encrypt_for_write(manifest, &mut payload, &mut aux)?;
+        #[cfg(feature = "encryption")]
+        let manifest = vault_manifest.as_ref();
+
+        let names = std::iter::once("manifest.toml")
+            .chain(payload.iter().map(|(name, _)| name.as_str()))
+            .chain(aux.iter().map(|(name, _)| *name));
+        manifest.sync.variant.profile().check(names)?;
+
+        #[cfg(feature = "signatures")]
+        let (manifest, _spooled_payload) =
+            self.sign_for_write(manifest, &mut payload, &mut aux, parent)?;
+
+        let manifest_text = manifest.to_toml()?;
+
+        let mut temp = tempfile::Builder::new()
+            .prefix(".tmp.sync-")
+            .suffix(".sync")
+            .tempfile_in(parent)?;
+        self.write_entries(temp.as_file_mut(), &manifest_text, &payload, &aux)?;
+        temp.persist(path).map_err(|e| Error::IoError(e.error))?;
+
+        Ok(path.to_path_buf())
+    }
+
+    /// Resolve the payload into archive entries.
+    ///
+    /// Payload directory paths are validated here: they must be relative,
+    /// unique, free of `.`/`..` components, and no file may also be used as a
+    /// directory.
+    fn payload_entries(&self) -> Result<PayloadEntries<'_>> {
+        match (&self.payload, self.payload_files.is_empty()) {
+            (Some(payload), true) => Ok(vec![("payload".to_string(), Cow::Borrowed(payload))]),
+            (Some(_), false) => Err(Error::InvalidFormat(
+                "archive cannot have both a payload and payload files".to_string(),
+            )),
+            (None, true) => Err(Error::MissingEntry("payload".to_string())),
+            (None, false) => {
+                let mut files = HashSet::new();
+                let mut dirs = HashSet::new();
+                for (path, _) in &self.payload_files {
+                    validate_payload_path(path)?;
+                    if !files.insert(path.as_str()) {
+                        return Err(Error::InvalidFormat(format!(
+                            "duplicate payload path: {}",
+                            path
+                        )));
+                    }
+                    dirs.extend(path.match_indices('/').map(|(i, _)| &path[..i]));
+                }
+                if let Some(path) = files.iter().find(|path| dirs.contains(*path)) {
+                    return Err(Error::InvalidFormat(format!(
+                        "payload path is both a file and a directory: {}",
+                        path
+                    )));
+                }
+
+                Ok(self
+                    .payload_files
+                    .iter()
+                    .map(|(path, source)| {
+                        (format!("{}{}", PAYLOAD_DIR, path), Cow::Borrowed(source))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Resolve the entries written after the payload, in archive order.
+    fn aux_entries(&self) -> AuxEntries<'_> {
+        let mut entries = Vec::new();
+        if let Some(wasm) = &self.wasm {
+            entries.push(("sync.wasm", Cow::Borrowed(wasm)));
+        }
+        if let Some(context) = &self.context {
+            entries.push(("context.json", Cow::Borrowed(context)));
+        }
+        if let Some(proof) = &self.proof {
+            entries.push(("sync.proof", Cow::Borrowed(proof)));
+        }
+        entries
+    }
+
+    /// Encrypt the payload and selected entries when writing a vault.
+    ///
+    /// Entries are encrypted as they stream into the archive. A payload that
+    /// is also signed is spooled as ciphertext first, so the signature
+    /// covers the bytes that are actually stored.
+    #[cfg(feature = "encryption")]
+    fn encrypt_for_write<'a>(
+        &self,
+        manifest: &'a Manifest,
+        payload: &mut PayloadEntries<'_>,
+        aux: &mut AuxEntries<'_>,
+    ) -> Result<Cow<'a, Manifest>> {
+        let Some(protection) = &self.vault else {
+            if self.encrypted_entries.is_empty() {
+                return Ok(Cow::Borrowed(manifest));
+            }
+            return Err(Error::InvalidFormat(
+                "encrypted entries require a vault".to_string(),
+            ));
+        };
+        if self.payload.is_none() {
+            return Err(Error::InvalidFormat(
+                "vault payload directories are not supported".to_string(),
+            ));
+        }
+        for name in &self.encrypted_entries {
+            if !crate::vault::ENCRYPTABLE_ENTRIES.contains(&name.as_str()) {
+                return Err(Error::InvalidFormat(format!(
+                    "entry cannot be encrypted: {}",
+                    name
+                )));
+            }
+        }
+
+        let encrypt = |source: &mut Cow<'_, EntrySource>| {
+            let inner = Box::new(source.clone().into_owned());
+            *source = Cow::Owned(EntrySource::Encrypted(inner, protection.clone()));
+        };
+        for (_, source) in payload.iter_mut() {
+            encrypt(source);
+        }
+        for (name, source) in aux.iter_mut() {
+            if self.encrypted_entries.iter().any(|entry| entry == name) {
+                encrypt(source);
+            }
+        }
+
+        let mut manifest = manifest.clone();
+        manifest.sync.variant = crate::SyncVariant::Vault;
+        protection.apply_to(&mut manifest.encryption);
+        manifest.encryption.entries = aux
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .filter(|name| self.encrypted_entries.contains(name))
+            .collect();
+        Ok(Cow::Owned(manifest))
+    }
+
+    /// Sign a copy of the manifest when a signer is configured.
+    ///
+    /// Reader sources in `payload` and a reader `sync.wasm` are replaced with
+    /// spooled temporary files, returned as paths that must outlive the write.
+    #[cfg(feature = "signatures")]
+    fn sign_for_write<'a>(
+        &self,
+        manifest: &'a Manifest,
+        payload: &mut PayloadEntries<'_>,
+        aux: &mut AuxEntries<'_>,
+        spool_dir: &Path,
+    ) -> Result<(Cow<'a, Manifest>, Vec<tempfile::TempPath>)> {
+        let Some(signer) = &self.signer else {
+            return Ok((Cow::Borrowed(manifest), Vec::new()));
+        };
+
+        let mut spooled = Vec::new();
+        let mut hashes = std::collections::BTreeMap::new();
+        for (name, source) in payload.iter_mut() {
+            let (hash, spool) = source.hash(spool_dir)?;
+            if let Some(temp_path) = spool {
+                *source = Cow::Owned(EntrySource::File(temp_path.to_path_buf()));
+                spooled.push(temp_path);
+            }
+            let path = name.strip_prefix(PAYLOAD_DIR).unwrap_or(name);
+            hashes.insert(path.to_string(), hash);
         }
 
-        let file = File::create(path)?;
-        let mut zip = ZipWriter::new(file);
-        let options: FileOptions<()> =
+        let payload_hash = if self.payload.is_some() {
+            hashes.into_values().next()
+        } else {
+            Some(crate::verification::compute_payload_tree_hash(&hashes))
+        };
+
+        let mut module_hash = None;
+        if let Some((_, source)) = aux.iter_mut().find(|(name, _)| *name == "sync.wasm") {
+            let (hash, spool) = source.hash(spool_dir)?;
+            if let Some(temp_path) = spool {
+                *source = Cow::Owned(EntrySource::File(temp_path.to_path_buf()));
+                spooled.push(temp_path);
+            }
+            module_hash = Some(hash);
+        }
+
+        let mut signed = manifest.clone();
+        signer.sign_manifest_with_hashes(&mut signed, payload_hash, module_hash)?;
+        Ok((Cow::Owned(signed), spooled))
+    }
+
+    fn write_entries<W: Write + Seek>(
+        &self,
+        writer: W,
+        manifest_text: &str,
+        payload: &PayloadEntries<'_>,
+        aux: &AuxEntries<'_>,
+    ) -> Result<()> {
+        let mut zip = ZipWriter::new(writer);
+        let mut options: FileOptions<()> =
             FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        if self.reproducible {
+            options = options
+                .last_modified_time(zip::DateTime::default())
+                .unix_permissions(REPRODUCIBLE_PERMISSIONS);
+        }
 
         zip.start_file("manifest.toml", options)?;
         zip.write_all(manifest_text.as_bytes())?;
 
-        zip.start_file("payload", options)?;
-        zip.write_all(payload)?;
-
-        zip.start_file("sync.wasm", options)?;
-        zip.write_all(wasm)?;
-
-        if let Some(context) = &self.context {
-            zip.start_file("context.json", options)?;
-            zip.write_all(context)?;
+        // Only a single payload is padded; aligning every file of a payload
+        // directory would waste up to 16 KiB per file.
+        for (name, source) in payload {
+            if name == "payload" {
+                zip.start_file(name, options.with_alignment(PAYLOAD_ALIGNMENT))?;
+            } else {
+                zip.start_file(name, options)?;
+            }
+            source.copy_to(&mut zip)?;
         }
 
-        if let Some(proof) = &self.proof {
-            zip.start_file("sync.proof", options)?;
-            zip.write_all(proof)?;
+        for (name, source) in aux {
+            zip.start_file(*name, options)?;
+            source.copy_to(&mut zip)?;
         }
 
         zip.finish()?;
 
-        Ok(path.to_path_buf())
+        Ok(())
+    }
+}
+
+fn validate_payload_path(path: &str) -> Result<()> {
+    let valid = !path.is_empty()
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|component| !component.is_empty() && component != "." && component != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidFormat(format!(
+            "invalid payload path: {:?}",
+            path
+        )))
     }
 }
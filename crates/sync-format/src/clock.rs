@@ -0,0 +1,72 @@
+//! Injectable time source for reproducible output.
+//!
+//! Signing stamps timestamps into the manifest and `sync.sig`, so two builds
+//! of the same inputs differ unless time is pinned. A [`SyncSigner`] reads
+//! the time from a [`Clock`]; give it a [`FixedClock`] to sign the same
+//! bytes on every rebuild.
+//!
+//! [`SyncSigner`]: crate::SyncSigner
+
+use chrono::{DateTime, Utc};
+
+/// Source of the current time.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// The current time.
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that always returns the same time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedClock(DateTime<Utc>);
+
+impl FixedClock {
+    /// A clock stopped at `at`.
+    pub fn new(at: DateTime<Utc>) -> Self {
+        Self(at)
+    }
+
+    /// A clock stopped at `secs` seconds after the Unix epoch.
+    ///
+    /// Returns `None` if `secs` is out of range.
+    pub fn from_unix_timestamp(secs: i64) -> Option<Self> {
+        DateTime::from_timestamp(secs, 0).map(Self)
+    }
+
+    /// A clock stopped at the `SOURCE_DATE_EPOCH` environment variable, the
+    /// convention reproducible build systems use to pin timestamps.
+    ///
+    /// Returns `None` if the variable is unset or not a Unix timestamp.
+    pub fn from_source_date_epoch() -> Option<Self> {
+        let secs = std::env::var("SOURCE_DATE_EPOCH").ok()?;
+        Self::from_unix_timestamp(secs.trim().parse().ok()?)
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock() {
+        let clock = FixedClock::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(clock.now(), clock.now());
+        assert_eq!(clock.now().to_rfc3339(), "2023-11-14T22:13:20+00:00");
+        assert!(FixedClock::from_unix_timestamp(i64::MAX).is_none());
+    }
+}
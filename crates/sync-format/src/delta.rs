@@ -0,0 +1,629 @@
+//! Content-defined chunking and delta payload updates.
+//!
+//! A payload is split into chunks with FastCDC, so cut points follow the
+//! content rather than fixed offsets and an edit only changes the chunks
+//! around it. Each chunk is identified by its BLAKE3 hash.
+//!
+//! To replicate a new payload version, the receiver sends the
+//! [`ChunkedPayload`] of its current payload, the sender computes a
+//! [`PayloadPatch`] against it with [`PayloadPatch::diff`], and the receiver
+//! applies the patch with [`SyncArchive::apply_payload_patch`]. Only the
+//! changed chunks travel; unchanged ones are copied from the local payload.
+//!
+//! # Patch format
+//!
+//! [`PayloadPatch::to_bytes`] writes integers as unsigned LEB128 varints:
+//!
+//! ```text
+//! "SYNCDLT1"
+//! base_len    base_hash[32]
+//! target_len  target_hash[32]
+//! op_count
+//! op*         0x00 offset length     copy from the base payload
+//!             0x01 length bytes      insert literal bytes
+//! ```
+//!
+//! [`SyncArchive::apply_payload_patch`]: crate::SyncArchive::apply_payload_patch
+
+use crate::{Error, Result};
+use fastcdc::v2020::{self, FastCDC, StreamCDC};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Magic bytes at the start of a serialized [`PayloadPatch`].
+const PATCH_MAGIC: &[u8; 8] = b"SYNCDLT1";
+
+const OP_COPY: u8 = 0x00;
+const OP_INSERT: u8 = 0x01;
+
+/// Chunk size bounds for content-defined chunking.
+///
+/// Both sides of a delta must chunk with the same configuration, so the
+/// configuration travels with every [`ChunkedPayload`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkerConfig {
+    /// Smallest chunk, except for the last one.
+    pub min_size: u32,
+    /// Target average chunk size.
+    pub avg_size: u32,
+    /// Largest chunk.
+    pub max_size: u32,
+}
+
+impl Default for ChunkerConfig {
+    fn default() -> Self {
+        Self {
+            min_size: 4 * 1024,
+            avg_size: 16 * 1024,
+            max_size: 64 * 1024,
+        }
+    }
+}
+
+impl ChunkerConfig {
+    /// Check the sizes against the bounds FastCDC supports.
+    pub fn validate(&self) -> Result<()> {
+        let in_range = |value: u32, min: u32, max: u32| (min..=max).contains(&value);
+        if !in_range(self.min_size, v2020::MINIMUM_MIN, v2020::MINIMUM_MAX)
+            || !in_range(self.avg_size, v2020::AVERAGE_MIN, v2020::AVERAGE_MAX)
+            || !in_range(self.max_size, v2020::MAXIMUM_MIN, v2020::MAXIMUM_MAX)
+            || self.min_size > self.avg_size
+            || self.avg_size > self.max_size
+        {
+            return Err(Error::DeltaError(format!(
+                "unsupported chunk sizes {}/{}/{}",
+                self.min_size, self.avg_size, self.max_size
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// BLAKE3 hash of a chunk or a whole payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ChunkHash(pub [u8; 32]);
+
+impl ChunkHash {
+    /// Hash `data` with BLAKE3.
+    pub fn of(data: &[u8]) -> Self {
+        Self(*blake3::hash(data).as_bytes())
+    }
+}
+
+impl std::fmt::Display for ChunkHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "blake3:{}", hex::encode(self.0))
+    }
+}
+
+/// One content-defined chunk of a payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Chunk {
+    /// BLAKE3 hash of the chunk bytes.
+    pub hash: ChunkHash,
+    /// Offset of the chunk within the payload.
+    pub offset: u64,
+    /// Length of the chunk in bytes.
+    pub length: u64,
+}
+
+/// A payload described as a list of content-defined chunks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkedPayload {
+    /// Configuration the chunks were cut with.
+    pub config: ChunkerConfig,
+    /// Total payload length.
+    pub len: u64,
+    /// BLAKE3 hash of the whole payload.
+    pub hash: ChunkHash,
+    /// Chunks in payload order.
+    pub chunks: Vec<Chunk>,
+}
+
+impl ChunkedPayload {
+    /// Chunk an in-memory payload.
+    pub fn from_bytes(data: &[u8], config: ChunkerConfig) -> Result<Self> {
+        config.validate()?;
+        let chunks = FastCDC::new(data, config.min_size, config.avg_size, config.max_size)
+            .map(|chunk| {
+                let bytes = &data[chunk.offset..chunk.offset + chunk.length];
+                Chunk {
+                    hash: ChunkHash::of(bytes),
+                    offset: chunk.offset as u64,
+                    length: chunk.length as u64,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            config,
+            len: data.len() as u64,
+            hash: ChunkHash::of(data),
+            chunks,
+        })
+    }
+
+    /// Chunk a payload from a reader without holding it in memory.
+    pub fn from_reader<R: Read>(reader: R, config: ChunkerConfig) -> Result<Self> {
+        config.validate()?;
+        let mut hasher = blake3::Hasher::new();
+        let mut chunks = Vec::new();
+        let mut len = 0;
+        for chunk in StreamCDC::new(reader, config.min_size, config.avg_size, config.max_size) {
+            let chunk = chunk.map_err(|e| Error::DeltaError(e.to_string()))?;
+            hasher.update(&chunk.data);
+            chunks.push(Chunk {
+                hash: ChunkHash::of(&chunk.data),
+                offset: chunk.offset,
+                length: chunk.length as u64,
+            });
+            len += chunk.length as u64;
+        }
+
+        Ok(Self {
+            config,
+            len,
+            hash: ChunkHash(*hasher.finalize().as_bytes()),
+            chunks,
+        })
+    }
+
+    /// Find a chunk with the given hash.
+    pub fn find(&self, hash: &ChunkHash) -> Option<&Chunk> {
+        self.chunks.iter().find(|chunk| chunk.hash == *hash)
+    }
+}
+
+/// One step in rebuilding a target payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchOp {
+    /// Copy a byte range of the base payload.
+    Copy {
+        /// Offset within the base payload.
+        offset: u64,
+        /// Number of bytes to copy.
+        length: u64,
+    },
+    /// Insert bytes carried by the patch.
+    Insert(Vec<u8>),
+}
+
+/// Instructions for turning one payload version into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadPatch {
+    /// Length of the payload the patch applies to.
+    pub base_len: u64,
+    /// BLAKE3 hash of the payload the patch applies to.
+    pub base_hash: ChunkHash,
+    /// Length of the patched payload.
+    pub target_len: u64,
+    /// BLAKE3 hash of the patched payload.
+    pub target_hash: ChunkHash,
+    /// Operations in output order.
+    pub ops: Vec<PatchOp>,
+}
+
+impl PayloadPatch {
+    /// Compute a patch from the chunks of a base payload to `target`.
+    ///
+    /// Only the base chunk list is needed, not the base bytes. `target` is
+    /// chunked with the same configuration, and every chunk already in the
+    /// base becomes a copy.
+    pub fn diff(base: &ChunkedPayload, target: &[u8]) -> Result<Self> {
+        let mut known: HashMap<ChunkHash, &Chunk> = HashMap::new();
+        for chunk in &base.chunks {
+            known.entry(chunk.hash).or_insert(chunk);
+        }
+
+        let target_chunks = ChunkedPayload::from_bytes(target, base.config)?;
+        let mut ops: Vec<PatchOp> = Vec::new();
+        for chunk in &target_chunks.chunks {
+            match known.get(&chunk.hash) {
+                Some(base_chunk) => push_copy(&mut ops, base_chunk.offset, base_chunk.length),
+                None => {
+                    let start = chunk.offset as usize;
+                    push_insert(&mut ops, &target[start..start + chunk.length as usize]);
+                }
+            }
+        }
+
+        Ok(Self {
+            base_len: base.len,
+            base_hash: base.hash,
+            target_len: target_chunks.len,
+            target_hash: target_chunks.hash,
+            ops,
+        })
+    }
+
+    /// Compute a patch between two in-memory payloads.
+    pub fn between(base: &[u8], target: &[u8], config: ChunkerConfig) -> Result<Self> {
+        Self::diff(&ChunkedPayload::from_bytes(base, config)?, target)
+    }
+
+    /// Number of bytes the patch copies from the base payload.
+    pub fn copied_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                PatchOp::Copy { length, .. } => *length,
+                PatchOp::Insert(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Number of bytes the patch carries itself.
+    pub fn inserted_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                PatchOp::Copy { .. } => 0,
+                PatchOp::Insert(data) => data.len() as u64,
+            })
+            .sum()
+    }
+
+    /// Serialize the patch in its compact binary form.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = PATCH_MAGIC.to_vec();
+        write_varint(&mut out, self.base_len);
+        out.extend_from_slice(&self.base_hash.0);
+        write_varint(&mut out, self.target_len);
+        out.extend_from_slice(&self.target_hash.0);
+        write_varint(&mut out, self.ops.len() as u64);
+        for op in &self.ops {
+            match op {
+                PatchOp::Copy { offset, length } => {
+                    out.push(OP_COPY);
+                    write_varint(&mut out, *offset);
+                    write_varint(&mut out, *length);
+                }
+                PatchOp::Insert(data) => {
+                    out.push(OP_INSERT);
+                    write_varint(&mut out, data.len() as u64);
+                    out.extend_from_slice(data);
+                }
+            }
+        }
+        out
+    }
+
+    /// Parse a patch written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        let mut input = PatchInput { data, position: 0 };
+        if input.bytes(PATCH_MAGIC.len())? != PATCH_MAGIC {
+            return Err(Error::DeltaError("not a payload patch".to_string()));
+        }
+
+        let base_len = input.varint()?;
+        let base_hash = input.hash()?;
+        let target_len = input.varint()?;
+        let target_hash = input.hash()?;
+        let op_count = input.varint()?;
+
+        let mut ops = Vec::new();
+        for _ in 0..op_count {
+            let op = match input.byte()? {
+                OP_COPY => PatchOp::Copy {
+                    offset: input.varint()?,
+                    length: input.varint()?,
+                },
+                OP_INSERT => {
+                    let length = input.varint()?;
+                    let length = usize::try_from(length)
+                        .map_err(|_| Error::DeltaError("insert too large".to_string()))?;
+                    PatchOp::Insert(input.bytes(length)?.to_vec())
+                }
+                tag => return Err(Error::DeltaError(format!("unknown patch op {:#04x}", tag))),
+            };
+            ops.push(op);
+        }
+        if input.position != data.len() {
+            return Err(Error::DeltaError("trailing bytes after patch".to_string()));
+        }
+
+        Ok(Self {
+            base_len,
+            base_hash,
+            target_len,
+            target_hash,
+            ops,
+        })
+    }
+
+    /// Apply the patch to an in-memory base payload.
+    pub fn apply(&self, base: &[u8]) -> Result<Vec<u8>> {
+        let mut target = Vec::new();
+        self.apply_to(io::Cursor::new(base), &mut target)?;
+        Ok(target)
+    }
+
+    /// Apply the patch to a seekable base payload, writing the result to `out`.
+    ///
+    /// Fails if `base` is not the payload the patch was computed against, or
+    /// if the output does not match the target hash. On failure `out` may
+    /// hold partial output.
+    pub fn apply_to<R: Read + Seek, W: Write>(&self, mut base: R, out: W) -> Result<()> {
+        let mut base_hasher = blake3::Hasher::new();
+        base.seek(SeekFrom::Start(0))?;
+        let base_len = io::copy(&mut base, &mut base_hasher)?;
+        if base_len != self.base_len
+            || ChunkHash(*base_hasher.finalize().as_bytes()) != self.base_hash
+        {
+            return Err(Error::DeltaError(
+                "patch does not apply to this payload".to_string(),
+            ));
+        }
+
+        let mut out = HashingWriter {
+            inner: out,
+            hasher: blake3::Hasher::new(),
+            written: 0,
+        };
+        for op in &self.ops {
+            let length = match op {
+                PatchOp::Copy { length, .. } => *length,
+                PatchOp::Insert(data) => data.len() as u64,
+            };
+            if out
+                .written
+                .checked_add(length)
+                .is_none_or(|end| end > self.target_len)
+            {
+                return Err(Error::DeltaError(
+                    "patch output exceeds the target length".to_string(),
+                ));
+            }
+            match op {
+                PatchOp::Copy { offset, length } => {
+                    if offset.checked_add(*length).is_none_or(|end| end > base_len) {
+                        return Err(Error::DeltaError(
+                            "copy range outside the base payload".to_string(),
+                        ));
+                    }
+                    base.seek(SeekFrom::Start(*offset))?;
+                    io::copy(&mut (&mut base).take(*length), &mut out)?;
+                }
+                PatchOp::Insert(data) => out.write_all(data)?,
+            }
+        }
+        out.flush()?;
+
+        if out.written != self.target_len
+            || ChunkHash(*out.hasher.finalize().as_bytes()) != self.target_hash
+        {
+            return Err(Error::DeltaError(
+                "patched payload does not match the target hash".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Append a copy, merging it with a preceding adjacent copy.
+fn push_copy(ops: &mut Vec<PatchOp>, offset: u64, length: u64) {
+    if let Some(PatchOp::Copy {
+        offset: last_offset,
+        length: last_length,
+    }) = ops.last_mut()
+    {
+        if *last_offset + *last_length == offset {
+            *last_length += length;
+            return;
+        }
+    }
+    ops.push(PatchOp::Copy { offset, length });
+}
+
+/// Append an insert, merging it with a preceding insert.
+fn push_insert(ops: &mut Vec<PatchOp>, data: &[u8]) {
+    if let Some(PatchOp::Insert(last)) = ops.last_mut() {
+        last.extend_from_slice(data);
+        return;
+    }
+    ops.push(PatchOp::Insert(data.to_vec()));
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Cursor over serialized patch bytes.
+struct PatchInput<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> PatchInput<'a> {
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| Error::DeltaError("truncated patch".to_string()))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn hash(&mut self) -> Result<ChunkHash> {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(self.bytes(32)?);
+        Ok(ChunkHash(hash))
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::DeltaError("varint too long".to_string()))
+    }
+}
+
+/// Writer that hashes and counts everything written through it.
+struct HashingWriter<W> {
+    inner: W,
+    hasher: blake3::Hasher,
+    written: u64,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use crate::{SyncArchive, SyncBuilder, SyncManifest};
+    use tempfile::tempdir;
+
+    const CONFIG: ChunkerConfig = ChunkerConfig {
+        min_size: 256,
+        avg_size: 1024,
+        max_size: 4096,
+    };
+
+    /// Deterministic pseudo-random bytes.
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    fn edited(base: &[u8]) -> Vec<u8> {
+        let mut target = base[..50_000].to_vec();
+        target.extend_from_slice(b"a few changed rows");
+        target.extend_from_slice(&base[50_100..]);
+        target
+    }
+
+    #[test]
+    fn test_small_edit_reuses_most_chunks() {
+        let base = noise(200_000, 1);
+        let target = edited(&base);
+
+        let patch = PayloadPatch::between(&base, &target, CONFIG).unwrap();
+        assert!(
+            patch.inserted_bytes() < 10_000,
+            "{}",
+            patch.inserted_bytes()
+        );
+        assert_eq!(
+            patch.copied_bytes() + patch.inserted_bytes(),
+            target.len() as u64
+        );
+        assert_eq!(patch.apply(&base).unwrap(), target);
+
+        let bytes = patch.to_bytes();
+        assert!(bytes.len() < 10_200);
+        assert_eq!(PayloadPatch::from_bytes(&bytes).unwrap(), patch);
+    }
+
+    #[test]
+    fn test_streamed_chunks_match_in_memory() {
+        let data = noise(100_000, 2);
+        let streamed = ChunkedPayload::from_reader(data.as_slice(), CONFIG).unwrap();
+        assert_eq!(streamed, ChunkedPayload::from_bytes(&data, CONFIG).unwrap());
+    }
+
+    #[test]
+    fn test_patch_rejects_wrong_base_and_bad_input() {
+        let base = noise(20_000, 3);
+        let patch = PayloadPatch::between(&base, &edited(&noise(60_000, 3)), CONFIG).unwrap();
+        let err = patch.apply(&noise(20_000, 4)).unwrap_err();
+        assert!(err.to_string().contains("does not apply"));
+
+        // A patch that writes past its declared length stops at the first
+        // op that would overrun it.
+        let mut short = patch.clone();
+        short.target_len = 10;
+        let err = short.apply(&base).unwrap_err();
+        assert!(err.to_string().contains("exceeds the target length"));
+
+        let bytes = patch.to_bytes();
+        assert!(PayloadPatch::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(PayloadPatch::from_bytes(b"SYNCDLT0").is_err());
+        assert!(ChunkerConfig {
+            min_size: 1,
+            ..CONFIG
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_to_archive() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("delta.sync");
+        let base = noise(150_000, 5);
+        let manifest = SyncManifest::from_toml(
+            br#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "delta-test"
+hash_algo = "blake3"
+
+[sync]
+version = "1.3"
+content_type = "application/octet-stream"
+display_ext = "bin"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#,
+        )
+        .unwrap();
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(base.clone())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .write_to(&path)
+            .unwrap();
+
+        // The receiver describes its payload; the sender only ships changes.
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let chunks = archive.payload_chunks(CONFIG).unwrap();
+        let target = edited(&base);
+        let patch =
+            PayloadPatch::from_bytes(&PayloadPatch::diff(&chunks, &target).unwrap().to_bytes())
+                .unwrap();
+
+        archive.apply_payload_patch(&patch).unwrap();
+        assert_eq!(archive.read_payload().unwrap(), target);
+        assert!(archive.apply_payload_patch(&patch).is_err());
+    }
+}
@@ -15,6 +15,34 @@ pub enum Error {
     #[error("manifest error: {0}")]
     ManifestError(String),
 
+    /// The manifest failed strict validation.
+    #[error("manifest validation failed: {}", join_diagnostics(.0))]
+    ValidationError(Vec<crate::Diagnostic>),
+
+    /// Two entries share a name.
+    #[error("duplicate entry: {0}")]
+    DuplicateEntry(String),
+
+    /// An entry name is absolute, contains `..` or is otherwise unsafe.
+    #[error("unsafe entry name: {0:?}")]
+    UnsafeEntryName(String),
+
+    /// The data of two entries overlaps.
+    #[error("entries {0} and {1} overlap")]
+    OverlappingEntries(String, String),
+
+    /// An entry extends beyond the end of the archive.
+    #[error("entry extends beyond the end of the archive: {0}")]
+    EntryOutOfBounds(String),
+
+    /// An offset or size declared for an entry overflows.
+    #[error("size overflow in {0}")]
+    SizeOverflow(String),
+
+    /// The archive exceeds a configured [`ArchiveLimits`](crate::ArchiveLimits) limit.
+    #[error("archive limit exceeded: {0}")]
+    LimitExceeded(String),
+
     /// Payload hash verification failed.
     #[error("payload hash mismatch")]
     HashMismatch,
@@ -31,6 +59,26 @@ pub enum Error {
     #[error("encryption error: {0}")]
     EncryptError(String),
 
+    /// Signing error (requires `signatures` feature).
+    #[error("signature error: {0}")]
+    SignatureError(String),
+
+    /// Keystore error (requires `signatures` feature).
+    #[error("keystore error: {0}")]
+    KeystoreError(String),
+
+    /// Trust store error (requires `signatures` feature).
+    #[error("trust store error: {0}")]
+    TrustError(String),
+
+    /// Invalid chunking configuration or payload patch.
+    #[error("delta error: {0}")]
+    DeltaError(String),
+
+    /// Missing or unusable payload version history.
+    #[error("history error: {0}")]
+    HistoryError(String),
+
     /// Error from the zip library.
     #[error("zip error: {0}")]
     ZipError(#[from] zip::result::ZipError),
@@ -54,3 +102,11 @@ pub enum Error {
 
 /// Result type for sync-format operations.
 pub type Result<T> = std::result::Result<T, Error>;
+
+fn join_diagnostics(diagnostics: &[crate::Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -1,9 +1,51 @@
-use crate::manifest::SyncVariant;
+#[cfg(feature = "fs")]
+use crate::append;
+use crate::clock::Clock;
+#[cfg(feature = "fs")]
+use crate::delta::PayloadPatch;
+use crate::delta::{ChunkedPayload, ChunkerConfig};
+use crate::limits::{self, ArchiveLimits};
+#[cfg(feature = "fs")]
+use crate::manifest::ProvenanceRecord;
+use crate::manifest::{PayloadVersion, SyncVariant};
+use crate::migration::MigrationNote;
+#[cfg(feature = "mmap")]
+use crate::reader::EntryMap;
+use crate::reader::{EntryReader, ReadAt};
+#[cfg(feature = "signatures")]
+use crate::verification::{check_archive_signature, ArchiveSignatureStatus};
 use crate::{error::Result, manifest::Manifest};
-use std::collections::HashSet;
-use std::fs::{self, File};
-use std::io::{Read, Write};
+#[cfg(feature = "fs")]
+use chrono::{DateTime, SecondsFormat, Utc};
+#[cfg(feature = "fs")]
+use std::fs;
+use std::fs::File;
+#[cfg(any(feature = "fs", feature = "encryption"))]
+use std::io::Write;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Archive prefix for the files of a multi-file payload.
+///
+/// An archive carries either a single `payload` entry or a directory tree of
+/// entries named `payload/<path>`.
+pub const PAYLOAD_DIR: &str = "payload/";
+
+/// Archive prefix for replaced payload versions.
+///
+/// Version `n` is stored as `history/<n>` and listed in the manifest's
+/// `[[history]]`; see [`SyncArchive::history`].
+pub const HISTORY_DIR: &str = "history/";
+
+/// Provenance actor recorded for updates to an archive without a
+/// `sync.wasm` module, unless another actor is named.
+pub const HOST_ACTOR: &str = "host";
+
+/// Signs the bytes of a provenance record, returning a base64 signature.
+#[cfg(feature = "fs")]
+pub(crate) type SignRecord<'a> = dyn Fn(&[u8]) -> String + 'a;
 
 /// Represents an entry within a `.sync` archive.
 #[derive(Debug, Clone)]
@@ -18,28 +60,668 @@ pub struct SyncEntry {
     pub compression: zip::CompressionMethod,
 }
 
+impl SyncEntry {
+    /// Path of this entry relative to [`PAYLOAD_DIR`], if it is a file of a
+    /// multi-file payload.
+    pub fn payload_path(&self) -> Option<&str> {
+        self.name
+            .strip_prefix(PAYLOAD_DIR)
+            .filter(|path| !path.is_empty() && !path.ends_with('/'))
+    }
+}
+
+/// Options for opening a [`SyncArchive`].
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+    strict: bool,
+    migrate: bool,
+    limits: Option<ArchiveLimits>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl OpenOptions {
+    /// Create options with the defaults used by [`SyncArchive::open`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject archives whose manifest has validation errors.
+    ///
+    /// Warnings are tolerated. See [`Manifest::validate`].
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Upgrade an older manifest to the latest version in memory.
+    ///
+    /// The archive file is not modified; see
+    /// [`SyncArchive::write_migrated_manifest`] to persist the upgrade.
+    pub fn migrate(mut self, migrate: bool) -> Self {
+        self.migrate = migrate;
+        self
+    }
+
+    /// Check the raw zip structure against `limits` before parsing.
+    ///
+    /// Use this for archives from untrusted sources. Duplicate or unsafe
+    /// entry names, overlapping or out-of-bounds entries and oversized
+    /// entries are rejected with a specific [`Error`](crate::Error) variant.
+    pub fn hardened(mut self, limits: ArchiveLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Read the time stamped into history and provenance records by updates
+    /// from `clock` instead of the system clock.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Open the archive at `path` with these options.
+    #[cfg(feature = "fs")]
+    pub fn open<P: AsRef<Path>>(&self, path: P) -> Result<SyncArchive> {
+        SyncArchive::open_with_options(path, self)
+    }
+
+    /// Parse an archive held in memory with these options.
+    pub fn open_bytes(&self, bytes: impl Into<Vec<u8>>) -> Result<SyncArchive<Vec<u8>>> {
+        SyncArchive::parse(bytes.into(), None, self)
+    }
+
+    /// Parse an archive from a `Read + Seek` source with these options.
+    pub fn open_reader<R: Read + Seek>(&self, reader: R) -> Result<SyncArchive<Mutex<R>>> {
+        SyncArchive::parse(Mutex::new(reader), None, self)
+    }
+}
+
 /// A parsed `.sync` archive.
-#[derive(Debug)]
-pub struct SyncArchive {
-    path: String,
+///
+/// The archive is read through a [`ReadAt`] source: a [`File`] for archives
+/// opened from a path, bytes in memory ([`from_bytes`](SyncArchive::from_bytes))
+/// or any `Read + Seek` reader ([`from_reader`](SyncArchive::from_reader)).
+/// Updates rewrite the archive in place and are only available for archives
+/// opened from a path.
+pub struct SyncArchive<S: ReadAt = File> {
+    source: Arc<S>,
+    path: Option<String>,
     entries: Vec<SyncEntry>,
     manifest: Manifest,
+    migration_notes: Vec<MigrationNote>,
+    #[cfg_attr(not(feature = "fs"), allow(dead_code))]
+    manifest_upgraded: bool,
     payload_offset: Option<u64>,
     payload_size: Option<u64>,
+    #[cfg_attr(not(feature = "fs"), allow(dead_code))]
+    options: OpenOptions,
+    #[cfg(feature = "signatures")]
+    signature_status: std::sync::OnceLock<ArchiveSignatureStatus>,
+}
+
+impl<S: ReadAt> std::fmt::Debug for SyncArchive<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncArchive")
+            .field("path", &self.path)
+            .field("entries", &self.entries)
+            .field("manifest", &self.manifest)
+            .field("payload_offset", &self.payload_offset)
+            .field("payload_size", &self.payload_size)
+            .finish_non_exhaustive()
+    }
 }
 
+#[cfg(feature = "fs")]
 impl SyncArchive {
     /// Open a `.sync` archive from the given path.
+    ///
+    /// Opening never modifies the file. If a previous payload update was
+    /// interrupted, the next update through [`update_payload`](Self::update_payload)
+    /// or a rewrite rolls it back first; see [`recover`](Self::recover) for
+    /// an archive that no longer opens. An embedded `sync.sig` signature is
+    /// not checked until [`signature_status`](Self::signature_status) is
+    /// called.
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::open_with_options(path, &OpenOptions::default())
+    }
+
+    /// Open a `.sync` archive with explicit [`OpenOptions`].
+    pub fn open_with_options<P: AsRef<Path>>(path: P, options: &OpenOptions) -> Result<Self> {
         let path = path.as_ref();
         let file = File::open(path)?;
+        Self::parse(file, Some(path.to_string_lossy().to_string()), options)
+    }
+
+    /// Roll back an interrupted payload update of the archive at `path`.
+    ///
+    /// An update that overwrote a trailing payload in place and then died
+    /// leaves an archive that does not open; this restores it from the
+    /// journal next to it. Waits for an update in progress in another
+    /// process to finish, and does nothing if there is nothing to roll back.
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<()> {
+        append::recover(path.as_ref())
+    }
+
+    /// The current time, from the clock given in [`OpenOptions::clock`].
+    fn now(&self) -> DateTime<Utc> {
+        self.options
+            .clock
+            .as_ref()
+            .map_or_else(Utc::now, |clock| clock.now())
+    }
+
+    /// Reopen the archive after it was rewritten, keeping the open options.
+    fn reload(&mut self) -> Result<()> {
+        append::recover(Path::new(self.path()))?;
+        *self = Self::open_with_options(self.path(), &self.options)?;
+        Ok(())
+    }
+
+    /// Path of a file-backed archive, always set by [`open_with_options`](Self::open_with_options).
+    fn path(&self) -> &str {
+        self.path
+            .as_deref()
+            .expect("file-backed archives are opened from a path")
+    }
+
+    /// Provenance actor for updates made by the archive itself: the hash
+    /// of its `sync.wasm` module in "blake3:<hex>" format, or
+    /// [`HOST_ACTOR`] if it has none.
+    fn module_actor(&self) -> Result<String> {
+        if !self.has_wasm() {
+            return Ok(HOST_ACTOR.to_string());
+        }
+        let mut archive = zip::ZipArchive::new(self.archive_reader()?)?;
+        let module = archive.by_name("sync.wasm")?;
+        hash_reader(module)
+    }
+
+    /// Persist an in-memory manifest upgrade to the archive.
+    ///
+    /// Returns `false` if the manifest was not upgraded on open. Rewriting
+    /// the manifest changes its hash, so a signed archive must be signed again
+    /// afterwards.
+    pub fn write_migrated_manifest(&mut self) -> Result<bool> {
+        if !self.manifest_upgraded {
+            return Ok(false);
+        }
+        let manifest = self.manifest.clone();
+        self.update_manifest(&manifest)?;
+        Ok(true)
+    }
+
+    /// Get the path to the archive file.
+    pub fn archive_path(&self) -> &str {
+        self.path()
+    }
+
+    /// Get the file stem (name without extension) of the archive.
+    pub fn archive_file_stem(&self) -> Option<String> {
+        PathBuf::from(self.path())
+            .file_stem()
+            .map(|v| v.to_string_lossy().to_string())
+    }
+
+    /// Update the payload in the archive with new data.
+    ///
+    /// The new payload is written at the end of the archive together with a
+    /// fresh central directory, so the cost is proportional to the payload
+    /// rather than the whole archive. A payload that is already the last
+    /// entry is overwritten in place; otherwise the superseded payload stays
+    /// in the file as dead space. Once that would outweigh the live entries,
+    /// or the archive needs zip64, the archive is rewritten and compacted
+    /// instead.
+    ///
+    /// When the manifest's `policy.keep_versions` is set, the replaced
+    /// payload is moved to [`HISTORY_DIR`] instead, versions beyond the limit
+    /// are dropped, and the archive is rewritten in one step.
+    ///
+    /// Every update is logged in the manifest's `[[provenance]]`, with the
+    /// hash of the archive's wasm module as the actor, or [`HOST_ACTOR`] for
+    /// archives without one. Use
+    /// [`update_payload_as`](Self::update_payload_as) to name another actor.
+    /// The log grows with every update; see [`Manifest::provenance`] for
+    /// how to compact it.
+    ///
+    /// Archives with a payload directory cannot be updated this way.
+    pub fn update_payload(&mut self, new_payload: &[u8]) -> Result<()> {
+        let actor = self.module_actor()?;
+        self.update_payload_recorded(new_payload, actor, None)
+    }
+
+    /// Update the payload, recording `actor` (e.g., a did:key) in the
+    /// provenance log.
+    ///
+    /// The record is unsigned; see `SyncSigner::update_payload` for signed
+    /// updates. Otherwise this behaves like
+    /// [`update_payload`](Self::update_payload).
+    pub fn update_payload_as(&mut self, new_payload: &[u8], actor: &str) -> Result<()> {
+        self.update_payload_recorded(new_payload, actor.to_string(), None)
+    }
+
+    /// Update the payload and log the update, signing the provenance record
+    /// with `sign` if given.
+    pub(crate) fn update_payload_recorded(
+        &mut self,
+        new_payload: &[u8],
+        actor: String,
+        sign: Option<&SignRecord<'_>>,
+    ) -> Result<()> {
+        if self.payload_entry().is_none() && self.has_payload_dir() {
+            return Err(crate::Error::InvalidFormat(
+                "archive has a payload directory, not a single payload".to_string(),
+            ));
+        }
+
+        let mut manifest = self.manifest.clone();
+        record_update(
+            &mut manifest,
+            hash_reader(self.payload_reader()?)?,
+            hash_bytes(new_payload),
+            actor,
+            self.now(),
+            sign,
+        );
+
+        if manifest.policy.keep_versions > 0 || !manifest.history.is_empty() {
+            return self.replace_payload_keeping_history(manifest, new_payload);
+        }
+
+        let manifest_text = manifest.to_toml()?;
+        let entries = [
+            ("manifest.toml", manifest_text.as_bytes()),
+            ("payload", new_payload),
+        ];
+        if append::append_entries(Path::new(self.path()), &entries)? {
+            self.reload()?;
+            return Ok(());
+        }
+
+        self.rewrite(&entries)
+    }
+
+    /// Replace the manifest in the archive.
+    ///
+    /// All other entries are copied unchanged and keep their order.
+    pub fn update_manifest(&mut self, manifest: &Manifest) -> Result<()> {
+        check_history(manifest)?;
+        let manifest_text = manifest.to_toml()?;
+        self.rewrite(&[("manifest.toml", manifest_text.as_bytes())])
+    }
+
+    /// Move the current payload into the history, store `new_payload` and
+    /// write `manifest`, which already records the update.
+    fn replace_payload_keeping_history(
+        &mut self,
+        mut manifest: Manifest,
+        new_payload: &[u8],
+    ) -> Result<()> {
+        let previous = self.read_payload()?;
+        let keep = manifest.policy.keep_versions as usize;
+
+        let newest = manifest.history.iter().map(|v| v.version).max();
+        let version = newest.unwrap_or(0).max(manifest.last_version) + 1;
+        let entry = history_entry(version);
+        if keep > 0 {
+            manifest.last_version = version;
+            manifest.history.push(PayloadVersion {
+                version,
+                entry: entry.clone(),
+                hash: hash_bytes(&previous),
+                size: previous.len() as u64,
+                replaced_at: self.now().to_rfc3339_opts(SecondsFormat::Secs, true),
+                extra: Default::default(),
+            });
+        }
+        let excess = manifest.history.len().saturating_sub(keep);
+        let dropped: Vec<String> = manifest.history.drain(..excess).map(|v| v.entry).collect();
+
+        let manifest_text = manifest.to_toml()?;
+        let mut replacements = vec![
+            ("manifest.toml", manifest_text.as_bytes()),
+            ("payload", new_payload),
+        ];
+        if keep > 0 {
+            replacements.push((entry.as_str(), previous.as_slice()));
+        }
+        let removals: Vec<&str> = dropped.iter().map(String::as_str).collect();
+        self.rewrite_entries(&replacements, &removals)
+    }
+
+    /// Rewrite the archive through a temporary file and rename it into place.
+    ///
+    /// `replacements` maps entry names to their new contents. Entries keep
+    /// their order, except that a replaced payload moves to the end. Entries
+    /// not yet in the archive are added before the payload.
+    pub(crate) fn rewrite(&mut self, replacements: &[(&str, &[u8])]) -> Result<()> {
+        self.rewrite_entries(replacements, &[])
+    }
+
+    /// Like [`rewrite`](Self::rewrite), additionally leaving out the entries
+    /// named in `removals`.
+    fn rewrite_entries(&mut self, replacements: &[(&str, &[u8])], removals: &[&str]) -> Result<()> {
+        let replacement = |name: &str| {
+            replacements
+                .iter()
+                .find(|(replaced, _)| *replaced == name)
+                .map(|(_, data)| *data)
+        };
+        let archive_path = Path::new(self.path());
+        // A leftover journal would otherwise be replayed onto the new file.
+        append::recover(archive_path)?;
+        let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+
+        let temp_path = archive_path.with_extension("sync.tmp");
+        let temp_file = File::create(&temp_path)?;
+        let mut temp_zip = zip::ZipWriter::new(temp_file);
+        // Fixed times and permissions, so a rewrite of a reproducible archive
+        // stays reproducible.
+        let options: zip::write::FileOptions<()> = zip::write::FileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .last_modified_time(zip::DateTime::default())
+            .unix_permissions(crate::builder::REPRODUCIBLE_PERMISSIONS);
+
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let name = file.name().to_string();
+            if removals.contains(&name.as_str()) {
+                continue;
+            }
+
+            match replacement(&name) {
+                Some(_) if name == "payload" => continue,
+                Some(data) => {
+                    temp_zip.start_file(&name, options)?;
+                    temp_zip.write_all(data)?;
+                }
+                None => {
+                    temp_zip.start_file(&name, options)?;
+                    std::io::copy(&mut file, &mut temp_zip)?;
+                }
+            }
+        }
+
+        for (name, data) in replacements {
+            if *name != "payload" && archive.index_for_name(name).is_none() {
+                temp_zip.start_file(*name, options)?;
+                temp_zip.write_all(data)?;
+            }
+        }
+
+        if let Some(payload) = replacement("payload") {
+            temp_zip.start_file(
+                "payload",
+                options.with_alignment(crate::builder::PAYLOAD_ALIGNMENT),
+            )?;
+            temp_zip.write_all(payload)?;
+        }
+
+        temp_zip.finish()?;
+
+        fs::rename(&temp_path, archive_path)?;
+
+        // Reload the archive
+        self.reload()?;
+
+        Ok(())
+    }
+
+    /// Replace the payload by applying a patch to the current one.
+    ///
+    /// The patch must have been computed against the stored payload; it is
+    /// rejected otherwise, and also if the result does not match the patch's
+    /// target hash. The result is written with [`update_payload`](Self::update_payload).
+    ///
+    /// The patched payload is built in an anonymous temporary file next to
+    /// the archive rather than in memory.
+    pub fn apply_payload_patch(&mut self, patch: &PayloadPatch) -> Result<()> {
+        let dir = Path::new(self.path())
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let target = tempfile::tempfile_in(dir)?;
+        patch.apply_to(self.payload_reader()?, std::io::BufWriter::new(&target))?;
+
+        #[cfg(feature = "mmap")]
+        {
+            // SAFETY: the file is unlinked and only reachable through
+            // `target`, so nothing else can change it while it is mapped.
+            let map = unsafe { memmap2::Mmap::map(&target)? };
+            self.update_payload(&map)
+        }
+        #[cfg(not(feature = "mmap"))]
+        {
+            use std::io::Seek;
+            let mut data = Vec::new();
+            let mut file = &target;
+            file.seek(std::io::SeekFrom::Start(0))?;
+            file.read_to_end(&mut data)?;
+            self.update_payload(&data)
+        }
+    }
+
+    /// Make a previous payload version current again.
+    ///
+    /// This is an ordinary [`update_payload`](Self::update_payload), so the
+    /// payload being replaced is itself kept as the newest version.
+    pub fn checkout_version(&mut self, version: u64) -> Result<()> {
+        let data = self.read_version(version)?;
+        self.update_payload(&data)
+    }
+
+    /// Undo the last payload update.
+    ///
+    /// The newest history version becomes the payload again and leaves the
+    /// history; the current payload is discarded. The rollback is logged in
+    /// the provenance like any other update. Returns the restored version.
+    pub fn rollback(&mut self) -> Result<PayloadVersion> {
+        let restored =
+            self.manifest.history.last().cloned().ok_or_else(|| {
+                crate::Error::HistoryError("no previous payload version".to_string())
+            })?;
+        let data = self.read_version(restored.version)?;
+
+        let mut manifest = self.manifest.clone();
+        manifest.history.pop();
+        record_update(
+            &mut manifest,
+            hash_reader(self.payload_reader()?)?,
+            restored.hash.clone(),
+            self.module_actor()?,
+            self.now(),
+            None,
+        );
+        let manifest_text = manifest.to_toml()?;
+        self.rewrite_entries(
+            &[
+                ("manifest.toml", manifest_text.as_bytes()),
+                ("payload", &data),
+            ],
+            &[&restored.entry],
+        )?;
+        Ok(restored)
+    }
+
+    /// Memory-map the payload and borrow it as a byte slice.
+    ///
+    /// Archives written by [`SyncBuilder`](crate::SyncBuilder) align the
+    /// payload to [`PAYLOAD_ALIGNMENT`](crate::PAYLOAD_ALIGNMENT), and payload
+    /// updates keep it aligned, so the mapping starts on a page boundary.
+    ///
+    /// An update through another handle or process may overwrite a trailing
+    /// payload in place, so do not update the archive elsewhere while the
+    /// map is alive.
+    ///
+    /// Requires the `mmap` feature.
+    #[cfg(feature = "mmap")]
+    pub fn map_payload(&self) -> Result<EntryMap<'_>> {
+        match (self.payload_offset, self.payload_size) {
+            (Some(offset), Some(size)) => Ok(EntryMap::map(&self.source, offset, size)?),
+            _ => Err(crate::Error::PayloadNotFound),
+        }
+    }
+
+    /// Write the payload to the archive (alias for `update_payload`).
+    pub fn write_payload(&mut self, new_payload: &[u8]) -> Result<()> {
+        self.update_payload(new_payload)
+    }
+
+    /// Write the payload, encrypting if this is a vault archive.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn write_payload_with_password(
+        &mut self,
+        data: &[u8],
+        password: &secrecy::SecretString,
+    ) -> Result<()> {
+        let to_write = if self.is_vault() {
+            encrypt_data(data, password)?
+        } else {
+            data.to_vec()
+        };
+        self.update_payload(&to_write)
+    }
+
+    /// Re-encrypt a vault payload under a new passphrase or recipient set.
+    ///
+    /// The payload and any other encrypted entries are decrypted with `old`
+    /// in memory, encrypted with `new`, and written together with the
+    /// updated `[encryption]` section in a single atomic rewrite, so
+    /// plaintext never touches the disk. An existing signature no longer
+    /// matches afterwards and must be renewed.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn rekey(
+        &mut self,
+        old: &crate::vault::Credential,
+        new: &crate::vault::Protection,
+    ) -> Result<()> {
+        if !self.is_vault() {
+            return Err(crate::Error::InvalidFormat(
+                "only vault archives can be rekeyed".to_string(),
+            ));
+        }
+
+        let mut names = vec!["payload".to_string()];
+        names.extend(self.manifest.encryption.entries.iter().cloned());
+        names.extend(self.manifest.history.iter().map(|v| v.entry.clone()));
+        let mut reencrypted = Vec::with_capacity(names.len());
+        for name in names {
+            let plaintext = self.read_entry_with_credential(&name, old)?;
+            reencrypted.push((name, new.encrypt(&plaintext)?));
+        }
+
+        let mut manifest = self.manifest.clone();
+        new.apply_to(&mut manifest.encryption);
+        for version in &mut manifest.history {
+            if let Some((_, data)) = reencrypted.iter().find(|(name, _)| *name == version.entry) {
+                version.hash = hash_bytes(data);
+                version.size = data.len() as u64;
+            }
+        }
+        record_update(
+            &mut manifest,
+            hash_reader(self.payload_reader()?)?,
+            hash_bytes(&reencrypted[0].1),
+            self.module_actor()?,
+            self.now(),
+            None,
+        );
+        let manifest_text = manifest.to_toml()?;
+
+        let mut replacements = vec![("manifest.toml", manifest_text.as_bytes())];
+        replacements.extend(
+            reencrypted
+                .iter()
+                .map(|(name, data)| (name.as_str(), data.as_slice())),
+        );
+        self.rewrite(&replacements)
+    }
+
+    /// Write the payload, encrypting it for the same credential if this is a
+    /// vault archive.
+    ///
+    /// A passphrase re-encrypts with that passphrase; identities re-encrypt
+    /// to the [`manifest_recipients`](Self::manifest_recipients).
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn write_payload_with_credential(
+        &mut self,
+        data: &[u8],
+        credential: &crate::vault::Credential,
+    ) -> Result<()> {
+        match credential {
+            crate::vault::Credential::Passphrase(password) => {
+                self.write_payload_with_password(data, password)
+            }
+            crate::vault::Credential::Identities(_) => {
+                let recipients = if self.is_vault() {
+                    self.manifest_recipients()?
+                } else {
+                    Vec::new()
+                };
+                self.write_payload_for_recipients(data, &recipients)
+            }
+        }
+    }
+
+    /// Write the payload, encrypting it to `recipients` if this is a vault
+    /// archive.
+    ///
+    /// Pass [`manifest_recipients`](Self::manifest_recipients) to share the
+    /// vault with everyone listed in the manifest.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn write_payload_for_recipients(
+        &mut self,
+        data: &[u8],
+        recipients: &[crate::vault::Recipient],
+    ) -> Result<()> {
+        let to_write = if self.is_vault() {
+            crate::vault::encrypt_to_recipients(data, recipients)?
+        } else {
+            data.to_vec()
+        };
+        self.update_payload(&to_write)
+    }
+}
+
+impl SyncArchive<Vec<u8>> {
+    /// Parse an archive held in memory, e.g. an HTTP response body.
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self> {
+        OpenOptions::default().open_bytes(bytes)
+    }
+}
+
+impl<R: Read + Seek> SyncArchive<Mutex<R>> {
+    /// Parse an archive from any `Read + Seek` source, e.g. an entry of
+    /// another container.
+    ///
+    /// Reads seek the shared reader under a lock, so concurrent entry readers
+    /// take turns.
+    pub fn from_reader(reader: R) -> Result<Self> {
+        OpenOptions::default().open_reader(reader)
+    }
+}
+
+impl<S: ReadAt> SyncArchive<S> {
+    /// Parse an archive from `source`. `path` is set for file-backed archives.
+    fn parse(source: S, path: Option<String>, options: &OpenOptions) -> Result<Self> {
+        let source = Arc::new(source);
+        let mut reader = EntryReader::from_source(source.clone(), 0, source.size()?);
+        if let Some(limits) = &options.limits {
+            limits::check_archive(&mut reader, limits)?;
+        }
 
-        let mut archive = zip::ZipArchive::new(file)?;
+        let mut archive = zip::ZipArchive::new(reader)?;
         let mut entries = Vec::new();
         let mut manifest_data = None;
         let mut payload_offset = None;
         let mut payload_size = None;
-        let mut has_wasm = false;
 
         for i in 0..archive.len() {
             let mut file = archive.by_index(i)?;
@@ -55,11 +737,20 @@ impl SyncArchive {
             });
 
             if name == "manifest.toml" {
+                // Declared sizes were checked, but a compressed entry can
+                // still inflate past them.
+                let max = options.limits.map_or(u64::MAX, |l| l.max_manifest_size);
                 let mut data = Vec::new();
-                file.read_to_end(&mut data)?;
+                (&mut file)
+                    .take(max.saturating_add(1))
+                    .read_to_end(&mut data)?;
+                if data.len() as u64 > max {
+                    return Err(crate::Error::LimitExceeded(format!(
+                        "manifest.toml exceeds {} bytes",
+                        max
+                    )));
+                }
                 manifest_data = Some(data);
-            } else if name == "sync.wasm" {
-                has_wasm = true;
             } else if name == "payload" {
                 if compression != zip::CompressionMethod::Stored {
                     return Err(crate::Error::InvalidFormat(
@@ -68,22 +759,59 @@ impl SyncArchive {
                 }
                 payload_offset = Some(file.data_start());
                 payload_size = Some(size);
+            } else if name.starts_with(PAYLOAD_DIR) {
+                // Payload paths are exposed as files by mounts, so they must
+                // stay below the payload directory even without hardening.
+                limits::check_name(&name)?;
+                if compression != zip::CompressionMethod::Stored {
+                    return Err(crate::Error::InvalidFormat(format!(
+                        "{} must be stored (no compression)",
+                        name
+                    )));
+                }
             }
         }
 
         let manifest_data =
             manifest_data.ok_or_else(|| crate::Error::MissingEntry("manifest.toml".to_string()))?;
-        if !has_wasm {
-            return Err(crate::Error::MissingEntry("sync.wasm".to_string()));
+        let (manifest, migration_notes, manifest_upgraded) = if options.migrate {
+            let migrated = Manifest::from_toml_migrated(&manifest_data)?;
+            let upgraded = migrated.was_upgraded();
+            (migrated.manifest, migrated.notes, upgraded)
+        } else {
+            (Manifest::from_toml(&manifest_data)?, Vec::new(), false)
+        };
+
+        manifest
+            .sync
+            .variant
+            .profile()
+            .check(entries.iter().map(|entry| entry.name.as_str()))?;
+        check_history(&manifest)?;
+
+        if options.strict {
+            let errors: Vec<_> = manifest
+                .validate()
+                .into_iter()
+                .filter(|diagnostic| diagnostic.is_error())
+                .collect();
+            if !errors.is_empty() {
+                return Err(crate::Error::ValidationError(errors));
+            }
         }
-        let manifest = Manifest::from_toml(&manifest_data)?;
 
         Ok(Self {
-            path: path.to_string_lossy().to_string(),
+            source,
+            path,
             entries,
             manifest,
+            migration_notes,
+            manifest_upgraded,
             payload_offset,
             payload_size,
+            options: options.clone(),
+            #[cfg(feature = "signatures")]
+            signature_status: std::sync::OnceLock::new(),
         })
     }
 
@@ -92,6 +820,33 @@ impl SyncArchive {
         &self.manifest
     }
 
+    /// Get the status of the embedded `sync.sig` signature.
+    ///
+    /// The signature covers every entry, so the first call reads and hashes
+    /// the whole archive; the result is cached for the life of this handle
+    /// and refreshed when the archive is rewritten through it. Opening does
+    /// not pay this cost.
+    #[cfg(feature = "signatures")]
+    pub fn signature_status(&self) -> &ArchiveSignatureStatus {
+        self.signature_status.get_or_init(|| {
+            let status = self
+                .archive_reader()
+                .map_err(|e| e.to_string())
+                .and_then(|reader| zip::ZipArchive::new(reader).map_err(|e| e.to_string()))
+                .and_then(|mut archive| {
+                    check_archive_signature(&mut archive).map_err(|e| e.to_string())
+                });
+            status.unwrap_or_else(ArchiveSignatureStatus::Invalid)
+        })
+    }
+
+    /// Get the changes made when the manifest was upgraded on open.
+    ///
+    /// Always empty unless the archive was opened with [`OpenOptions::migrate`].
+    pub fn migration_notes(&self) -> &[MigrationNote] {
+        &self.migration_notes
+    }
+
     /// Get all entries in the archive.
     pub fn entries(&self) -> &[SyncEntry] {
         &self.entries
@@ -107,6 +862,21 @@ impl SyncArchive {
         self.entry("payload")
     }
 
+    /// Get the files of a multi-file payload, in archive order.
+    ///
+    /// Each entry's [`SyncEntry::payload_path`] gives its path below
+    /// [`PAYLOAD_DIR`]. Empty for single-payload archives.
+    pub fn payload_entries(&self) -> impl Iterator<Item = &SyncEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.payload_path().is_some())
+    }
+
+    /// Check if the payload is a directory tree rather than a single entry.
+    pub fn has_payload_dir(&self) -> bool {
+        self.payload_entries().next().is_some()
+    }
+
     /// Get the byte offset of the payload within the archive.
     pub fn payload_offset(&self) -> Option<u64> {
         self.payload_offset
@@ -117,18 +887,6 @@ impl SyncArchive {
         self.payload_size
     }
 
-    /// Get the path to the archive file.
-    pub fn archive_path(&self) -> &str {
-        &self.path
-    }
-
-    /// Get the file stem (name without extension) of the archive.
-    pub fn archive_file_stem(&self) -> Option<String> {
-        PathBuf::from(&self.path)
-            .file_stem()
-            .map(|v| v.to_string_lossy().to_string())
-    }
-
     /// Check if the archive contains a WASM module.
     pub fn has_wasm(&self) -> bool {
         self.entry("sync.wasm").is_some()
@@ -144,45 +902,54 @@ impl SyncArchive {
         self.entry("context.json").is_some()
     }
 
-    /// Update the payload in the archive with new data.
-    pub fn update_payload(&mut self, new_payload: &[u8]) -> Result<()> {
-        let archive_path = Path::new(&self.path);
-
-        let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
-
-        let temp_path = archive_path.with_extension("sync.tmp");
-        let temp_file = File::create(&temp_path)?;
-        let mut temp_zip = zip::ZipWriter::new(temp_file);
-        let options: zip::write::FileOptions<()> =
-            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
-
-        let entries_to_skip = HashSet::from(["payload"]);
+    /// Open a `Read + Seek` reader over the whole archive, e.g. for the
+    /// `_from_reader` verification functions.
+    pub fn archive_reader(&self) -> Result<EntryReader<S>> {
+        Ok(EntryReader::from_source(
+            self.source.clone(),
+            0,
+            self.source.size()?,
+        ))
+    }
 
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
-            let name = file.name().to_string();
+    /// Split the stored payload into content-defined chunks.
+    ///
+    /// The payload is streamed, not loaded into memory. Send the result to
+    /// whoever holds the next payload version so they can compute a
+    /// [`PayloadPatch`] against it.
+    pub fn payload_chunks(&self, config: ChunkerConfig) -> Result<ChunkedPayload> {
+        ChunkedPayload::from_reader(self.payload_reader()?, config)
+    }
 
-            if entries_to_skip.contains(name.as_str()) {
-                continue;
-            }
+    /// Replaced payload versions kept in the archive, oldest first.
+    ///
+    /// Empty unless the manifest's `policy.keep_versions` is set.
+    pub fn history(&self) -> &[PayloadVersion] {
+        &self.manifest.history
+    }
 
-            temp_zip.start_file(&name, options)?;
-            let mut data = Vec::new();
-            file.read_to_end(&mut data)?;
-            temp_zip.write_all(&data)?;
+    /// Read a replaced payload version as stored.
+    ///
+    /// The bytes are checked against the hash recorded in the manifest. In
+    /// a vault they are still encrypted; see
+    /// [`read_entry_with_credential`](Self::read_entry_with_credential).
+    pub fn read_version(&self, version: u64) -> Result<Vec<u8>> {
+        let recorded = self.version(version)?;
+        let data = self.entry_reader(&recorded.entry)?.read_all()?;
+        if hash_bytes(&data) != recorded.hash {
+            return Err(crate::Error::HashMismatch);
         }
+        Ok(data)
+    }
 
-        temp_zip.start_file("payload", options)?;
-        temp_zip.write_all(new_payload)?;
-
-        temp_zip.finish()?;
-
-        fs::rename(&temp_path, archive_path)?;
-
-        // Reload the archive
-        *self = Self::open(archive_path)?;
-
-        Ok(())
+    fn version(&self, version: u64) -> Result<&PayloadVersion> {
+        self.manifest
+            .history
+            .iter()
+            .find(|v| v.version == version)
+            .ok_or_else(|| {
+                crate::Error::HistoryError(format!("payload version {} not found", version))
+            })
     }
 
     /// Check if the archive is a vault (encrypted).
@@ -192,15 +959,45 @@ impl SyncArchive {
 
     /// Read the payload bytes from the archive.
     pub fn read_payload(&self) -> Result<Vec<u8>> {
-        let archive_path = Path::new(&self.path);
-        let file = File::open(archive_path)?;
-        let mut archive = zip::ZipArchive::new(file)?;
+        Ok(self.payload_reader()?.read_all()?)
+    }
 
-        let mut payload_file = archive.by_name("payload")?;
-        let mut buffer = Vec::new();
-        payload_file.read_to_end(&mut buffer)?;
+    /// Open a streaming `Read + Seek` reader over the payload.
+    ///
+    /// The reader uses positional reads over the stored payload range, so
+    /// the payload is never buffered in full.
+    pub fn payload_reader(&self) -> Result<EntryReader<S>> {
+        match (self.payload_offset, self.payload_size) {
+            (Some(offset), Some(size)) => {
+                Ok(EntryReader::from_source(self.source.clone(), offset, size))
+            }
+            _ => Err(crate::Error::PayloadNotFound),
+        }
+    }
+
+    /// Open a streaming reader over a file of a multi-file payload.
+    ///
+    /// `path` is relative to [`PAYLOAD_DIR`], e.g. `"tables/users.csv"`.
+    pub fn payload_file_reader(&self, path: &str) -> Result<EntryReader<S>> {
+        self.entry_reader(&format!("{}{}", PAYLOAD_DIR, path))
+    }
 
-        Ok(buffer)
+    /// Open a streaming reader over any stored entry.
+    pub fn entry_reader(&self, name: &str) -> Result<EntryReader<S>> {
+        let entry = self
+            .entry(name)
+            .ok_or_else(|| crate::Error::MissingEntry(name.to_string()))?;
+        if entry.compression != zip::CompressionMethod::Stored {
+            return Err(crate::Error::InvalidFormat(format!(
+                "{} must be stored (no compression) for direct reads",
+                name
+            )));
+        }
+        Ok(EntryReader::from_source(
+            self.source.clone(),
+            entry.offset,
+            entry.size,
+        ))
     }
 
     /// Read the payload, decrypting if this is a vault archive.
@@ -216,27 +1013,190 @@ impl SyncArchive {
         decrypt_data(encrypted, password)
     }
 
-    /// Write the payload to the archive (alias for `update_payload`).
-    pub fn write_payload(&mut self, new_payload: &[u8]) -> Result<()> {
-        self.update_payload(new_payload)
+    /// Read the payload, decrypting with any matching recipient identity if
+    /// this is a vault archive.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn read_payload_with_identities(
+        &self,
+        identities: &[crate::vault::Identity],
+    ) -> Result<Vec<u8>> {
+        if !self.is_vault() {
+            return self.read_payload();
+        }
+
+        let encrypted = self.read_payload()?;
+        crate::vault::decrypt_with_identities(&encrypted, identities)
     }
 
-    /// Write the payload, encrypting if this is a vault archive.
+    /// Check if an entry is stored encrypted.
+    ///
+    /// In a vault this is the payload, its history versions and every entry
+    /// listed in `encryption.entries`.
+    pub fn is_entry_encrypted(&self, name: &str) -> bool {
+        self.is_vault()
+            && (name == "payload"
+                || name.starts_with(HISTORY_DIR)
+                || self.manifest.encryption.entries.iter().any(|e| e == name))
+    }
+
+    /// Read an entry, decrypting it with `credential` if it is encrypted.
     ///
     /// Requires the `encryption` feature.
     #[cfg(feature = "encryption")]
-    pub fn write_payload_with_password(
-        &mut self,
-        data: &[u8],
-        password: &secrecy::SecretString,
-    ) -> Result<()> {
-        let to_write = if self.is_vault() {
-            encrypt_data(data, password)?
-        } else {
-            data.to_vec()
-        };
-        self.update_payload(&to_write)
+    pub fn read_entry_with_credential(
+        &self,
+        name: &str,
+        credential: &crate::vault::Credential,
+    ) -> Result<Vec<u8>> {
+        if !self.is_entry_encrypted(name) {
+            return Ok(self.entry_reader(name)?.read_all()?);
+        }
+
+        let mut data = Vec::new();
+        self.decrypting_entry_reader(name, credential)?
+            .read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Open a seekable reader that decrypts an encrypted entry as it reads.
+    ///
+    /// Only the chunks actually read are decrypted, so this suits range
+    /// reads from large vault payloads. Fails if the entry is not encrypted;
+    /// see [`is_entry_encrypted`](Self::is_entry_encrypted).
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn decrypting_entry_reader(
+        &self,
+        name: &str,
+        credential: &crate::vault::Credential,
+    ) -> Result<crate::vault::VaultReader<EntryReader<S>>> {
+        if !self.is_entry_encrypted(name) {
+            return Err(crate::Error::InvalidFormat(format!(
+                "{} is not encrypted",
+                name
+            )));
+        }
+        credential.decrypt_reader(self.entry_reader(name)?)
+    }
+
+    /// Open a seekable reader that decrypts the vault payload as it reads.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn decrypting_payload_reader(
+        &self,
+        credential: &crate::vault::Credential,
+    ) -> Result<crate::vault::VaultReader<EntryReader<S>>> {
+        self.decrypting_entry_reader("payload", credential)
+    }
+
+    /// Read the payload, decrypting it with `credential` if this is a vault
+    /// archive.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn read_payload_with_credential(
+        &self,
+        credential: &crate::vault::Credential,
+    ) -> Result<Vec<u8>> {
+        if self.payload_entry().is_none() {
+            return Err(crate::Error::PayloadNotFound);
+        }
+        self.read_entry_with_credential("payload", credential)
     }
+
+    /// Resolve `encryption.meta.recipients` from the manifest.
+    ///
+    /// Entries may be age recipients or did:keys; see
+    /// [`vault::parse_recipient`](crate::vault::parse_recipient).
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn manifest_recipients(&self) -> Result<Vec<crate::vault::Recipient>> {
+        match &self.manifest.encryption.meta {
+            Some(meta) => crate::vault::parse_recipients(&meta.recipients),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Entry name of a payload version in the history.
+fn history_entry(version: u64) -> String {
+    format!("{}{}", HISTORY_DIR, version)
+}
+
+/// Check that every `[[history]]` record points at its own entry under
+/// [`HISTORY_DIR`], so a rollback cannot be aimed at another entry.
+fn check_history(manifest: &Manifest) -> Result<()> {
+    for (i, recorded) in manifest.history.iter().enumerate() {
+        if recorded.entry != history_entry(recorded.version) {
+            return Err(crate::Error::HistoryError(format!(
+                "payload version {} must be stored as {}, not {}",
+                recorded.version,
+                history_entry(recorded.version),
+                recorded.entry
+            )));
+        }
+        if manifest.history[..i]
+            .iter()
+            .any(|earlier| earlier.version == recorded.version)
+        {
+            return Err(crate::Error::HistoryError(format!(
+                "payload version {} is listed twice",
+                recorded.version
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Hash bytes in "blake3:<hex>" format.
+fn hash_bytes(data: &[u8]) -> String {
+    format!("blake3:{}", hex::encode(blake3::hash(data).as_bytes()))
+}
+
+/// Hash everything `reader` yields in "blake3:<hex>" format.
+#[cfg(feature = "fs")]
+pub(crate) fn hash_reader<R: Read>(mut reader: R) -> Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut reader, &mut hasher)?;
+    Ok(format!(
+        "blake3:{}",
+        hex::encode(hasher.finalize().as_bytes())
+    ))
+}
+
+/// Append a provenance record for a payload update to `manifest`, chained to
+/// the last record and signed with `sign` if given.
+#[cfg(feature = "fs")]
+fn record_update(
+    manifest: &mut Manifest,
+    previous_payload_hash: String,
+    payload_hash: String,
+    actor: String,
+    now: DateTime<Utc>,
+    sign: Option<&SignRecord<'_>>,
+) {
+    let mut record = ProvenanceRecord {
+        previous_record: match manifest.provenance.last() {
+            Some(last) => Some(last.record_hash()),
+            None => manifest
+                .provenance_checkpoint
+                .as_ref()
+                .map(|checkpoint| checkpoint.last_record.clone()),
+        },
+        previous_payload_hash,
+        payload_hash,
+        timestamp: now.to_rfc3339_opts(SecondsFormat::Secs, true),
+        actor,
+        signature: None,
+        extra: Default::default(),
+    };
+    record.signature = sign.map(|sign| sign(&record.signing_payload()));
+    manifest.provenance.push(record);
 }
 
 /// Encrypt data using age passphrase encryption.
@@ -244,16 +1204,9 @@ impl SyncArchive {
 /// Requires the `encryption` feature.
 #[cfg(feature = "encryption")]
 pub fn encrypt_data(data: &[u8], password: &secrecy::SecretString) -> Result<Vec<u8>> {
-    use secrecy::ExposeSecret;
-    let passphrase = age::Encryptor::with_user_passphrase(password.expose_secret().clone().into());
-    let mut encrypted = Vec::new();
-    let mut writer = passphrase
-        .wrap_output(&mut encrypted)
-        .map_err(|e| crate::Error::EncryptError(e.to_string()))?;
+    let mut writer = crate::vault::passphrase_writer(Vec::new(), password)?;
     writer.write_all(data)?;
-    writer.finish()?;
-
-    Ok(encrypted)
+    Ok(writer.finish()?)
 }
 
 /// Decrypt data using age passphrase decryption.
@@ -261,27 +1214,12 @@ pub fn encrypt_data(data: &[u8], password: &secrecy::SecretString) -> Result<Vec
 /// Requires the `encryption` feature.
 #[cfg(feature = "encryption")]
 pub fn decrypt_data(encrypted: Vec<u8>, password: &secrecy::SecretString) -> Result<Vec<u8>> {
-    let decryptor = match age::Decryptor::new(&encrypted[..])
-        .map_err(|e| crate::Error::DecryptError(e.to_string()))?
-    {
-        age::Decryptor::Passphrase(d) => d,
-        _ => {
-            return Err(crate::Error::InvalidFormat(
-                "Expected passphrase-encrypted data".to_string(),
-            ));
-        }
-    };
-
     let mut decrypted = Vec::new();
-    let mut reader = decryptor
-        .decrypt(password, None)
-        .map_err(|e| crate::Error::DecryptError(e.to_string()))?;
-    reader.read_to_end(&mut decrypted)?;
-
+    crate::vault::passphrase_reader(&encrypted[..], password)?.read_to_end(&mut decrypted)?;
     Ok(decrypted)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "fs"))]
 mod tests {
     use super::*;
     use crate::Manifest;
@@ -354,8 +1292,9 @@ timeout = 30
         let dir = tempdir().unwrap();
         let path = dir.path().join("no-wasm.zip");
 
-        // Create a zip without sync.wasm
-        let manifest = create_test_manifest();
+        // Create an app archive without sync.wasm
+        let mut manifest = create_test_manifest();
+        manifest.sync.variant = SyncVariant::App;
         let manifest_toml = toml::to_string_pretty(&manifest).unwrap();
 
         let file = File::create(&path).unwrap();
@@ -363,6 +1302,9 @@ timeout = 30
         let options: zip::write::FileOptions<()> = zip::write::FileOptions::default();
         zip.start_file("manifest.toml", options).unwrap();
         zip.write_all(manifest_toml.as_bytes()).unwrap();
+        let stored = options.compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("payload", stored).unwrap();
+        zip.write_all(b"assets").unwrap();
         zip.finish().unwrap();
 
         let result = SyncArchive::open(&path);
@@ -371,6 +1313,35 @@ timeout = 30
         assert!(err.to_string().contains("sync.wasm"));
     }
 
+    #[test]
+    fn test_plain_archive_without_wasm() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("plain.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"no module".to_vec())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        assert!(!archive.has_wasm());
+        assert_eq!(archive.read_payload().unwrap(), b"no module");
+
+        archive.update_payload(b"updated").unwrap();
+        assert_eq!(archive.read_payload().unwrap(), b"updated");
+        assert_eq!(archive.manifest().provenance[0].actor, HOST_ACTOR);
+
+        let mut manifest = create_test_manifest();
+        manifest.sync.variant = SyncVariant::App;
+        let err = SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"assets".to_vec())
+            .write_to(dir.path().join("app.sync"))
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::MissingEntry(ref name) if name == "sync.wasm"));
+    }
+
     #[test]
     fn test_entry_lookup() {
         let dir = tempdir().unwrap();
@@ -436,6 +1407,671 @@ timeout = 30
         assert!(archive2.has_wasm()); // WASM should still exist
     }
 
+    #[test]
+    fn test_update_hashes_the_stored_payload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tampered.sync");
+        let mut manifest = create_test_manifest();
+        manifest.policy.keep_versions = 2;
+
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"v1".to_vec())
+            .write_to(&path)
+            .unwrap();
+        let mut archive = SyncArchive::open(&path).unwrap();
+        archive.update_payload(b"v2").unwrap();
+
+        // A forged log must not be carried over into the next record or the
+        // history.
+        let mut manifest = archive.manifest().clone();
+        manifest.provenance[0].payload_hash = hash_bytes(b"forged");
+        archive.update_manifest(&manifest).unwrap();
+        archive.update_payload(b"v3").unwrap();
+
+        let manifest = archive.manifest();
+        assert_eq!(
+            manifest.provenance[1].previous_payload_hash,
+            hash_bytes(b"v2")
+        );
+        assert_eq!(manifest.history[1].hash, hash_bytes(b"v2"));
+    }
+
+    #[test]
+    fn test_update_payload_appends_in_place() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("append.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(vec![b'a'; 1024])
+            .with_wasm_bytes(create_minimal_wasm())
+            .with_proof_bytes(vec![0u8; 256 * 1024])
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let original_len = fs::metadata(&path).unwrap().len();
+        let proof_offset = archive.entry("sync.proof").unwrap().offset;
+
+        let appended = vec![b'b'; 2048];
+        archive.update_payload(&appended).unwrap();
+
+        // The payload lands after the old end of file; other entries stay put.
+        assert!(archive.payload_offset().unwrap() > original_len);
+        assert_eq!(archive.entry("sync.proof").unwrap().offset, proof_offset);
+        assert!(!append::journal_path(&path).exists());
+
+        let reopened = SyncArchive::open(&path).unwrap();
+        assert_eq!(reopened.read_payload().unwrap(), appended);
+        assert_eq!(
+            reopened
+                .entry_reader("sync.wasm")
+                .unwrap()
+                .read_all()
+                .unwrap(),
+            create_minimal_wasm()
+        );
+
+        // Every entry still passes the zip crate's CRC checks.
+        let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).unwrap();
+            std::io::copy(&mut entry, &mut std::io::sink()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_update_payload_compacts_dead_space() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("compact.sync");
+        let payload = vec![b'x'; 64 * 1024];
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(payload.clone())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        for round in 0..8u8 {
+            let mut next = payload.clone();
+            next[0] = round;
+            archive.update_payload(&next).unwrap();
+
+            // Dead space stays within the live size plus the compaction
+            // slack; both include up to one alignment unit of padding.
+            let len = fs::metadata(&path).unwrap().len();
+            let padding = 3 * crate::PAYLOAD_ALIGNMENT as u64;
+            assert!(
+                len <= 2 * (payload.len() as u64 + 4096) + padding,
+                "archive grew to {len}"
+            );
+            assert_eq!(archive.read_payload().unwrap(), next);
+        }
+    }
+
+    #[test]
+    fn test_payload_history_checkout_and_rollback() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("history.sync");
+
+        let mut manifest = create_test_manifest();
+        manifest.policy.keep_versions = 2;
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"v1".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        for payload in [b"v2", b"v3", b"v4"] {
+            archive.update_payload(payload).unwrap();
+        }
+
+        // Only the two newest replaced versions are kept.
+        let versions: Vec<_> = archive.history().iter().map(|v| v.version).collect();
+        assert_eq!(versions, [2, 3]);
+        assert!(archive.entry("history/1").is_none());
+        assert_eq!(archive.read_version(2).unwrap(), b"v2");
+        assert!(matches!(
+            archive.read_version(1),
+            Err(crate::Error::HistoryError(_))
+        ));
+
+        archive.checkout_version(2).unwrap();
+        assert_eq!(archive.read_payload().unwrap(), b"v2");
+        let versions: Vec<_> = archive.history().iter().map(|v| v.version).collect();
+        assert_eq!(versions, [3, 4]);
+        assert_eq!(archive.read_version(4).unwrap(), b"v4");
+
+        let restored = archive.rollback().unwrap();
+        assert_eq!(restored.version, 4);
+        assert_eq!(archive.read_payload().unwrap(), b"v4");
+        assert!(archive.entry("history/4").is_none());
+
+        let mut reopened = SyncArchive::open(&path).unwrap();
+        assert_eq!(reopened.history().len(), 1);
+        reopened.rollback().unwrap();
+        assert_eq!(reopened.read_payload().unwrap(), b"v3");
+        assert!(matches!(
+            reopened.rollback(),
+            Err(crate::Error::HistoryError(_))
+        ));
+
+        // Version numbers are not reused after a rollback.
+        reopened.update_payload(b"v5").unwrap();
+        let versions: Vec<_> = reopened.history().iter().map(|v| v.version).collect();
+        assert_eq!(versions, [5]);
+        assert_eq!(reopened.read_version(5).unwrap(), b"v3");
+
+        // A history record cannot point at another entry.
+        let mut hostile = reopened.manifest().clone();
+        hostile.history[0].entry = "manifest.toml".to_string();
+        assert!(matches!(
+            reopened.update_manifest(&hostile),
+            Err(crate::Error::HistoryError(_))
+        ));
+        let hostile_text = hostile.to_toml().unwrap();
+        reopened
+            .rewrite_entries(&[("manifest.toml", hostile_text.as_bytes())], &[])
+            .unwrap_err();
+        assert!(matches!(
+            SyncArchive::open(&path),
+            Err(crate::Error::HistoryError(_))
+        ));
+    }
+
+    #[test]
+    fn test_updates_with_fixed_clock_are_reproducible() {
+        let dir = tempdir().unwrap();
+        let clock = crate::FixedClock::from_unix_timestamp(1_700_000_000).unwrap();
+        let options = OpenOptions::new().clock(clock);
+
+        let mut manifest = create_test_manifest();
+        manifest.policy.keep_versions = 1;
+        let update = |name: &str| {
+            let path = SyncBuilder::new()
+                .with_manifest(manifest.clone())
+                .with_payload_bytes(b"v1".to_vec())
+                .with_wasm_bytes(create_minimal_wasm())
+                .reproducible(true)
+                .write_to(dir.path().join(name))
+                .unwrap();
+            let mut archive = options.open(&path).unwrap();
+            archive.update_payload(b"v2").unwrap();
+            archive
+        };
+
+        let first = update("first.sync");
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let second = update("second.sync");
+
+        assert_eq!(
+            fs::read(first.path()).unwrap(),
+            fs::read(second.path()).unwrap()
+        );
+        let manifest = first.manifest();
+        assert_eq!(manifest.history[0].replaced_at, "2023-11-14T22:13:20Z");
+        assert_eq!(manifest.provenance[0].timestamp, "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    fn test_update_rolls_back_interrupted_append() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("interrupted.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"committed".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        // Simulate a crash after part of an append reached the disk.
+        let original_len = fs::metadata(&path).unwrap().len();
+        let file = File::open(&path).unwrap();
+        append::write_journal(
+            &append::journal_path(&path),
+            &file,
+            original_len,
+            original_len,
+        )
+        .unwrap();
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"PK\x03\x04partial payload").unwrap();
+        drop(file);
+
+        // Opening only reads; the next update rolls the append back.
+        let mut archive = SyncArchive::open(&path).unwrap();
+        assert_eq!(archive.read_payload().unwrap(), b"committed");
+        assert!(fs::metadata(&path).unwrap().len() > original_len);
+        assert!(append::journal_path(&path).exists());
+
+        archive.update_payload(b"next").unwrap();
+        assert!(!append::journal_path(&path).exists());
+        let reopened = SyncArchive::open(&path).unwrap();
+        assert_eq!(reopened.read_payload().unwrap(), b"next");
+        assert!(reopened.has_wasm());
+    }
+
+    #[test]
+    fn test_update_overwrites_trailing_payload_in_place() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("trailing.sync");
+        let payload = vec![b'x'; 256 * 1024];
+
+        // Without aux entries the payload is the last local entry.
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(payload.clone())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        archive.update_payload(&payload).unwrap();
+        let len = fs::metadata(&path).unwrap().len();
+        for round in 0..4u8 {
+            let mut next = payload.clone();
+            next[0] = round;
+            archive.update_payload(&next).unwrap();
+
+            // No dead copies pile up, and the payload stays page aligned.
+            assert!(fs::metadata(&path).unwrap().len() <= len + 512);
+            let offset = archive.payload_offset().unwrap();
+            assert_eq!(offset % crate::PAYLOAD_ALIGNMENT as u64, 0);
+            assert_eq!(archive.read_payload().unwrap(), next);
+        }
+
+        // Simulate a crash after the in-place update truncated the archive.
+        let original = fs::read(&path).unwrap();
+        let write_start = archive.payload_offset().unwrap();
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        append::write_journal(
+            &append::journal_path(&path),
+            &file,
+            original.len() as u64,
+            write_start,
+        )
+        .unwrap();
+        file.set_len(write_start).unwrap();
+        drop(file);
+        drop(archive);
+
+        assert!(SyncArchive::open(&path).is_err());
+        SyncArchive::recover(&path).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), original);
+        assert!(!append::journal_path(&path).exists());
+        SyncArchive::recover(&path).unwrap();
+
+        let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).unwrap();
+            std::io::copy(&mut entry, &mut std::io::sink()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_payload_reader_seek_and_read_at() {
+        use std::io::{Seek, SeekFrom};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("reader.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"0123456789".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let archive = SyncArchive::open(&path).unwrap();
+        let mut reader = archive.payload_reader().unwrap();
+        assert_eq!(reader.len(), 10);
+
+        reader.seek(SeekFrom::Start(4)).unwrap();
+        let mut rest = String::new();
+        reader.read_to_string(&mut rest).unwrap();
+        assert_eq!(rest, "456789");
+
+        let mut buf = [0u8; 8];
+        let n = reader.read_at(&mut buf, 7).unwrap();
+        assert_eq!(&buf[..n], b"789");
+
+        reader.seek(SeekFrom::End(-2)).unwrap();
+        let mut tail = Vec::new();
+        reader.read_to_end(&mut tail).unwrap();
+        assert_eq!(tail, b"89");
+
+        assert!(reader.seek(SeekFrom::Current(-100)).is_err());
+        assert_eq!(archive.read_payload().unwrap(), b"0123456789");
+    }
+
+    #[test]
+    fn test_open_from_bytes_and_reader() {
+        use std::io::Cursor;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("memory.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"in memory".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+        let bytes = fs::read(&path).unwrap();
+
+        let archive = SyncArchive::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(archive.manifest().sync.content_type, "application/json");
+        assert_eq!(archive.read_payload().unwrap(), b"in memory");
+        let reader = archive.payload_reader().unwrap();
+        let mut buf = [0u8; 6];
+        reader.read_exact_at(&mut buf, 3).unwrap();
+        assert_eq!(&buf, b"memory");
+
+        let archive = SyncArchive::from_reader(Cursor::new(bytes.clone())).unwrap();
+        assert!(archive.has_wasm());
+        assert_eq!(archive.read_payload().unwrap(), b"in memory");
+
+        let hardened = OpenOptions::default()
+            .hardened(ArchiveLimits::default())
+            .open_bytes(bytes.clone())
+            .unwrap();
+        assert_eq!(hardened.payload_size(), Some(9));
+
+        let truncated = &bytes[..bytes.len() - 10];
+        assert!(SyncArchive::from_bytes(truncated).is_err());
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn test_map_payload_is_page_aligned() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("mapped.sync");
+        let payload = b"id,value\n1,alpha\n2,beta\n".to_vec();
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(payload.clone())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let archive = SyncArchive::open(&path).unwrap();
+        let offset = archive.payload_offset().unwrap();
+        assert_eq!(offset % crate::PAYLOAD_ALIGNMENT as u64, 0);
+
+        let mapped = archive.map_payload().unwrap();
+        assert_eq!(&mapped[..], &payload[..]);
+    }
+
+    #[test]
+    fn test_strict_open_rejects_invalid_manifest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("strict.sync");
+
+        let mut manifest = create_test_manifest();
+        manifest.policy.ttl = 0;
+        SyncBuilder::new()
+            .with_manifest(manifest.clone())
+            .with_payload_bytes(b"data".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        // Warnings alone do not fail strict mode.
+        OpenOptions::new().strict(true).open(&path).unwrap();
+
+        manifest.policy.timeout = 0;
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"data".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        assert!(SyncArchive::open(&path).is_ok());
+        match OpenOptions::new().strict(true).open(&path) {
+            Err(crate::Error::ValidationError(errors)) => {
+                assert_eq!(errors.len(), 1);
+                assert_eq!(errors[0].kind, crate::DiagnosticKind::ZeroTimeout);
+            }
+            other => panic!("expected validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_open_migrates_and_writes_back_manifest() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("legacy.sync");
+
+        let mut manifest = create_test_manifest();
+        manifest.sync.version = "1.0".to_string();
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"data".to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let plain = SyncArchive::open(&path).unwrap();
+        assert_eq!(plain.manifest().sync.version, "1.0");
+        assert!(plain.migration_notes().is_empty());
+
+        let mut archive = OpenOptions::new().migrate(true).open(&path).unwrap();
+        assert_eq!(archive.manifest().sync.version, "1.3");
+        assert!(archive.write_migrated_manifest().unwrap());
+        assert_eq!(archive.read_payload().unwrap(), b"data");
+
+        let reopened = SyncArchive::open(&path).unwrap();
+        assert_eq!(reopened.manifest().sync.version, "1.3");
+
+        let mut current = OpenOptions::new().migrate(true).open(&path).unwrap();
+        assert!(current.migration_notes().is_empty());
+        assert!(!current.write_migrated_manifest().unwrap());
+    }
+
+    #[cfg(all(feature = "encryption", feature = "signatures"))]
+    #[test]
+    fn test_vault_shared_with_manifest_recipients() {
+        use crate::{vault, EncryptionMeta, SyncSigner};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("shared.sync");
+        let alice = SyncSigner::from_bytes(&[1u8; 32]);
+        let bob = SyncSigner::from_bytes(&[2u8; 32]);
+
+        let mut manifest = create_test_manifest();
+        manifest.sync.variant = SyncVariant::Vault;
+        manifest.encryption.enabled = true;
+        manifest.encryption.meta = Some(EncryptionMeta {
+            recipients: vec![alice.did().to_string(), bob.did().to_string()],
+            ..Default::default()
+        });
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(Vec::new())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let recipients = archive.manifest_recipients().unwrap();
+        assert_eq!(recipients.len(), 2);
+        archive
+            .write_payload_for_recipients(b"team secret", &recipients)
+            .unwrap();
+        assert_ne!(archive.read_payload().unwrap(), b"team secret");
+
+        let identity = vault::identity_from_signer(&bob);
+        assert_eq!(
+            archive.read_payload_with_identities(&[identity]).unwrap(),
+            b"team secret"
+        );
+        let stranger = vault::Identity::generate();
+        assert!(archive.read_payload_with_identities(&[stranger]).is_err());
+    }
+
+    #[cfg(all(feature = "encryption", feature = "signatures"))]
+    #[test]
+    fn test_rekey_passphrase_to_recipients() {
+        use crate::vault::{self, Credential, Protection};
+        use crate::SyncSigner;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rekey.sync");
+        let password = secrecy::SecretString::new("old password".to_string());
+        let teammate = SyncSigner::from_bytes(&[3u8; 32]);
+
+        let mut manifest = create_test_manifest();
+        manifest.sync.variant = SyncVariant::Vault;
+        manifest.encryption.enabled = true;
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(encrypt_data(b"vault data", &password).unwrap())
+            .with_wasm_bytes(create_minimal_wasm())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let wrong = Credential::Passphrase(secrecy::SecretString::new("guess".to_string()));
+        let new = Protection::Recipients(vec![teammate.did().to_string()]);
+        assert!(archive.rekey(&wrong, &new).is_err());
+
+        archive
+            .rekey(&Credential::Passphrase(password.clone()), &new)
+            .unwrap();
+        assert!(archive.read_payload_with_password(&password).is_err());
+        let identity = vault::identity_from_signer(&teammate);
+        assert_eq!(
+            archive.read_payload_with_identities(&[identity]).unwrap(),
+            b"vault data"
+        );
+
+        let reopened = SyncArchive::open(&path).unwrap();
+        let encryption = &reopened.manifest().encryption;
+        assert_eq!(encryption.algorithm.as_deref(), Some("age-v1"));
+        let meta = encryption.meta.as_ref().unwrap();
+        assert_eq!(meta.recipients, [teammate.did()]);
+        assert_eq!(meta.kdf, None);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(all(feature = "encryption", feature = "signatures"))]
+    #[test]
+    fn test_vault_encrypts_selected_entries() {
+        use crate::vault::{self, Credential, Protection};
+        use crate::SyncSigner;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("entries.sync");
+        let owner = SyncSigner::from_bytes(&[4u8; 32]);
+        let successor = SyncSigner::from_bytes(&[5u8; 32]);
+        let context = br#"{"api_key":"secret"}"#;
+
+        let builder = SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"payload".to_vec())
+            .with_context_bytes(context.to_vec())
+            .with_wasm_bytes(create_minimal_wasm())
+            .encrypt_entry("context.json");
+        assert!(builder.write_to(&path).is_err());
+        builder
+            .with_vault(Protection::Recipients(vec![owner.did().to_string()]))
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        assert!(archive.is_vault());
+        assert_eq!(archive.manifest().encryption.entries, ["context.json"]);
+        assert!(archive.is_entry_encrypted("context.json"));
+        assert!(!archive.is_entry_encrypted("sync.wasm"));
+        assert_ne!(
+            archive
+                .entry_reader("context.json")
+                .unwrap()
+                .read_all()
+                .unwrap(),
+            context
+        );
+
+        let credential = Credential::Identities(vec![vault::identity_from_signer(&owner)]);
+        assert_eq!(
+            archive
+                .read_entry_with_credential("context.json", &credential)
+                .unwrap(),
+            context
+        );
+        assert_eq!(
+            archive
+                .read_entry_with_credential("sync.wasm", &credential)
+                .unwrap(),
+            create_minimal_wasm()
+        );
+
+        let new = Protection::Recipients(vec![successor.did().to_string()]);
+        archive.rekey(&credential, &new).unwrap();
+        assert!(archive
+            .read_entry_with_credential("context.json", &credential)
+            .is_err());
+        let credential = Credential::Identities(vec![vault::identity_from_signer(&successor)]);
+        assert_eq!(
+            archive
+                .read_entry_with_credential("context.json", &credential)
+                .unwrap(),
+            context
+        );
+        assert_eq!(
+            archive.read_payload_with_credential(&credential).unwrap(),
+            b"payload"
+        );
+    }
+
+    #[cfg(all(feature = "encryption", feature = "signatures"))]
+    #[test]
+    fn test_streamed_vault_payload_supports_range_reads() {
+        use crate::vault::{self, Credential, Protection};
+        use crate::SyncSigner;
+        use std::io::{Seek, SeekFrom};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("large-vault.sync");
+        let owner = SyncSigner::from_bytes(&[6u8; 32]);
+        let payload: Vec<u8> = (0..300_000u32).map(|i| (i % 251) as u8).collect();
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_reader(std::io::Cursor::new(payload.clone()))
+            .with_wasm_bytes(create_minimal_wasm())
+            .with_vault(Protection::Recipients(vec![owner.did().to_string()]))
+            .with_signer(owner.clone())
+            .write_to(&path)
+            .unwrap();
+
+        let result = crate::verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+
+        let archive = SyncArchive::open(&path).unwrap();
+        let credential = Credential::Identities(vec![vault::identity_from_signer(&owner)]);
+        let mut reader = archive.decrypting_payload_reader(&credential).unwrap();
+        assert_eq!(reader.seek(SeekFrom::End(0)).unwrap(), payload.len() as u64);
+
+        let mut range = vec![0u8; 1000];
+        reader.seek(SeekFrom::Start(200_000)).unwrap();
+        reader.read_exact(&mut range).unwrap();
+        assert_eq!(range, payload[200_000..201_000]);
+        assert!(archive
+            .decrypting_entry_reader("sync.wasm", &credential)
+            .is_err());
+    }
+
     #[test]
     fn test_has_context_and_proof() {
         let dir = tempdir().unwrap();
@@ -0,0 +1,412 @@
+//! Local keystore for Ed25519 signing identities.
+//!
+//! Keys are identified by did:key strings (multicodec 0xed01, base58btc) and
+//! stored on disk as one TOML file per key. With the `encryption` feature the
+//! secret key can be protected with an age passphrase.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use sync_format::keystore::Keystore;
+//!
+//! let keystore = Keystore::open("~/.config/sync/keys")?;
+//! let signer = keystore.generate("publisher")?;
+//! manifest.meta.created_by = signer.did().to_string();
+//! ```
+
+#[cfg(feature = "fs")]
+use crate::signing::{SyncSigner, SIGNATURE_ALGORITHM};
+use crate::verification::extract_public_key;
+use crate::{Error, Result};
+#[cfg(feature = "fs")]
+use chrono::{SecondsFormat, Utc};
+use ed25519_dalek::VerifyingKey;
+#[cfg(feature = "fs")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::fs;
+#[cfg(feature = "fs")]
+use std::io::Write;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+/// Multicodec prefix for Ed25519 public keys.
+pub const ED25519_MULTICODEC: [u8; 2] = [0xed, 0x01];
+
+/// File suffix for stored keys.
+#[cfg(feature = "fs")]
+const KEY_FILE_SUFFIX: &str = ".key.toml";
+
+/// Protection value for keys stored in the clear.
+#[cfg(feature = "fs")]
+const PROTECTION_NONE: &str = "none";
+
+/// Protection value for keys encrypted with an age passphrase.
+#[cfg(all(feature = "fs", feature = "encryption"))]
+const PROTECTION_AGE_PASSPHRASE: &str = "age-passphrase";
+
+/// Encode an Ed25519 public key as a did:key identity.
+pub fn encode_did_key(public_key: &VerifyingKey) -> String {
+    let mut bytes = ED25519_MULTICODEC.to_vec();
+    bytes.extend_from_slice(public_key.as_bytes());
+    format!("did:key:z{}", bs58::encode(&bytes).into_string())
+}
+
+/// Decode a did:key identity into an Ed25519 public key.
+pub fn decode_did_key(did: &str) -> Result<VerifyingKey> {
+    let bytes = extract_public_key(did).map_err(|e| Error::KeystoreError(e.to_string()))?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| Error::KeystoreError(e.to_string()))
+}
+
+/// Summary of a key held in the keystore.
+#[derive(Debug, Clone)]
+pub struct KeyInfo {
+    /// Name of the key within the keystore.
+    pub name: String,
+    /// Public identity in did:key format.
+    pub did: String,
+    /// RFC3339 timestamp of when the key was stored.
+    pub created_at: String,
+    /// Whether the secret key is passphrase-protected.
+    pub protected: bool,
+}
+
+/// On-disk representation of a stored key.
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyFile {
+    algorithm: String,
+    did: String,
+    created_at: String,
+    protection: String,
+    /// Base64-encoded secret key, or age ciphertext when protected.
+    secret_key: String,
+}
+
+#[cfg(feature = "fs")]
+impl KeyFile {
+    fn info(&self, name: &str) -> KeyInfo {
+        KeyInfo {
+            name: name.to_string(),
+            did: self.did.clone(),
+            created_at: self.created_at.clone(),
+            protected: self.protection != PROTECTION_NONE,
+        }
+    }
+}
+
+/// Directory-backed keystore.
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+#[cfg(feature = "fs")]
+impl Keystore {
+    /// Open a keystore directory, creating it if needed.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    /// Get the keystore directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Generate a new key and store it unprotected.
+    pub fn generate(&self, name: &str) -> Result<SyncSigner> {
+        let signer = SyncSigner::generate();
+        self.store(
+            name,
+            &signer,
+            PROTECTION_NONE,
+            encode_secret(&signer.to_bytes()),
+        )?;
+        Ok(signer)
+    }
+
+    /// Import an existing 32-byte secret key and store it unprotected.
+    pub fn import(&self, name: &str, secret_key: &[u8; 32]) -> Result<SyncSigner> {
+        let signer = SyncSigner::from_bytes(secret_key);
+        self.store(name, &signer, PROTECTION_NONE, encode_secret(secret_key))?;
+        Ok(signer)
+    }
+
+    /// Load an unprotected key.
+    pub fn load(&self, name: &str) -> Result<SyncSigner> {
+        let key_file = self.read_key_file(name)?;
+        if key_file.protection != PROTECTION_NONE {
+            return Err(Error::KeystoreError(format!(
+                "key '{}' is passphrase-protected",
+                name
+            )));
+        }
+        let secret = decode_secret(&key_file.secret_key)?;
+        signer_for(&key_file, &secret)
+    }
+
+    /// Generate a new key protected with an age passphrase.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn generate_with_password(
+        &self,
+        name: &str,
+        password: &secrecy::SecretString,
+    ) -> Result<SyncSigner> {
+        let signer = SyncSigner::generate();
+        self.store_protected(name, &signer, password)?;
+        Ok(signer)
+    }
+
+    /// Import an existing secret key protected with an age passphrase.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn import_with_password(
+        &self,
+        name: &str,
+        secret_key: &[u8; 32],
+        password: &secrecy::SecretString,
+    ) -> Result<SyncSigner> {
+        let signer = SyncSigner::from_bytes(secret_key);
+        self.store_protected(name, &signer, password)?;
+        Ok(signer)
+    }
+
+    /// Load a key, decrypting it with the passphrase if it is protected.
+    ///
+    /// Requires the `encryption` feature.
+    #[cfg(feature = "encryption")]
+    pub fn load_with_password(
+        &self,
+        name: &str,
+        password: &secrecy::SecretString,
+    ) -> Result<SyncSigner> {
+        let key_file = self.read_key_file(name)?;
+        if key_file.protection == PROTECTION_NONE {
+            let secret = decode_secret(&key_file.secret_key)?;
+            return signer_for(&key_file, &secret);
+        }
+
+        let encrypted = base64::Engine::decode(
+            &base64::engine::general_purpose::STANDARD,
+            &key_file.secret_key,
+        )
+        .map_err(|e| Error::KeystoreError(format!("invalid secret key encoding: {}", e)))?;
+        let decrypted = crate::decrypt_data(encrypted, password)?;
+        let secret: [u8; 32] = decrypted
+            .try_into()
+            .map_err(|_| Error::KeystoreError("invalid secret key length".to_string()))?;
+        signer_for(&key_file, &secret)
+    }
+
+    /// List all keys in the keystore, sorted by name.
+    pub fn list(&self) -> Result<Vec<KeyInfo>> {
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if let Some(name) = file_name.strip_suffix(KEY_FILE_SUFFIX) {
+                let key_file = self.read_key_file(name)?;
+                keys.push(key_file.info(name));
+            }
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+
+    /// Get information about a stored key.
+    pub fn info(&self, name: &str) -> Result<KeyInfo> {
+        Ok(self.read_key_file(name)?.info(name))
+    }
+
+    /// Remove a key from the keystore.
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.key_path(name)?;
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "encryption")]
+    fn store_protected(
+        &self,
+        name: &str,
+        signer: &SyncSigner,
+        password: &secrecy::SecretString,
+    ) -> Result<()> {
+        let encrypted = crate::encrypt_data(&signer.to_bytes(), password)?;
+        self.store(
+            name,
+            signer,
+            PROTECTION_AGE_PASSPHRASE,
+            base64::Engine::encode(&base64::engine::general_purpose::STANDARD, encrypted),
+        )
+    }
+
+    fn store(
+        &self,
+        name: &str,
+        signer: &SyncSigner,
+        protection: &str,
+        secret_key: String,
+    ) -> Result<()> {
+        let path = self.key_path(name)?;
+        let key_file = KeyFile {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            did: signer.did().to_string(),
+            created_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            protection: protection.to_string(),
+            secret_key,
+        };
+        let text =
+            toml::to_string_pretty(&key_file).map_err(|e| Error::KeystoreError(e.to_string()))?;
+
+        // The temporary file is created private (0600 on Unix), and the
+        // no-clobber persist fails rather than replace an existing key, even
+        // one stored concurrently.
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let mut temp = tempfile::Builder::new()
+            .prefix(".key-")
+            .suffix(".toml.tmp")
+            .tempfile_in(dir)?;
+        temp.write_all(text.as_bytes())?;
+        temp.as_file().sync_all()?;
+        temp.persist_noclobber(&path).map_err(|e| {
+            if e.error.kind() == std::io::ErrorKind::AlreadyExists {
+                Error::KeystoreError(format!("key '{}' already exists", name))
+            } else {
+                e.error.into()
+            }
+        })?;
+
+        Ok(())
+    }
+
+    fn read_key_file(&self, name: &str) -> Result<KeyFile> {
+        let path = self.key_path(name)?;
+        if !path.exists() {
+            return Err(Error::KeystoreError(format!("key '{}' not found", name)));
+        }
+        let text = fs::read_to_string(&path)?;
+        let key_file: KeyFile =
+            toml::from_str(&text).map_err(|e| Error::TomlError(e.to_string()))?;
+        if key_file.algorithm != SIGNATURE_ALGORITHM {
+            return Err(Error::KeystoreError(format!(
+                "unsupported key algorithm: {}",
+                key_file.algorithm
+            )));
+        }
+        Ok(key_file)
+    }
+
+    fn key_path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            && !name.starts_with('.');
+        if !valid {
+            return Err(Error::KeystoreError(format!("invalid key name: {}", name)));
+        }
+        Ok(self.dir.join(format!("{}{}", name, KEY_FILE_SUFFIX)))
+    }
+}
+
+#[cfg(feature = "fs")]
+fn encode_secret(secret_key: &[u8; 32]) -> String {
+    base64::Engine::encode(&base64::engine::general_purpose::STANDARD, secret_key)
+}
+
+#[cfg(feature = "fs")]
+fn decode_secret(encoded: &str) -> Result<[u8; 32]> {
+    let bytes = base64::Engine::decode(&base64::engine::general_purpose::STANDARD, encoded)
+        .map_err(|e| Error::KeystoreError(format!("invalid secret key encoding: {}", e)))?;
+    bytes
+        .try_into()
+        .map_err(|_| Error::KeystoreError("invalid secret key length".to_string()))
+}
+
+/// Rebuild a signer and check that it matches the recorded identity.
+#[cfg(feature = "fs")]
+fn signer_for(key_file: &KeyFile, secret_key: &[u8; 32]) -> Result<SyncSigner> {
+    let signer = SyncSigner::from_bytes(secret_key);
+    if signer.did() != key_file.did {
+        return Err(Error::KeystoreError(
+            "stored did:key does not match secret key".to_string(),
+        ));
+    }
+    Ok(signer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_did_key_round_trip() {
+        let signer = SyncSigner::from_bytes(&[3u8; 32]);
+        let did = encode_did_key(&signer.verifying_key());
+
+        assert!(did.starts_with("did:key:z6Mk"));
+        assert_eq!(decode_did_key(&did).unwrap(), signer.verifying_key());
+        assert!(decode_did_key("did:web:example.com").is_err());
+    }
+
+    #[test]
+    fn test_generate_and_load() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+
+        let generated = keystore.generate("publisher").unwrap();
+        let loaded = keystore.load("publisher").unwrap();
+        assert_eq!(generated.did(), loaded.did());
+
+        let keys = keystore.list().unwrap();
+        assert_eq!(keys.len(), 1);
+        assert_eq!(keys[0].did, generated.did());
+        assert!(!keys[0].protected);
+
+        // A second key under the same name never replaces the first.
+        let err = keystore.generate("publisher").unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{err}");
+        assert_eq!(keystore.load("publisher").unwrap().did(), generated.did());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_invalid_key_names_are_rejected() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+
+        assert!(keystore.generate("../escape").is_err());
+        assert!(keystore.generate("").is_err());
+        assert!(keystore.load("missing").is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_password_protected_key() {
+        let dir = tempdir().unwrap();
+        let keystore = Keystore::open(dir.path()).unwrap();
+        let password = secrecy::SecretString::new("correct horse".to_string());
+
+        let imported = keystore
+            .import_with_password("reviewer", &[9u8; 32], &password)
+            .unwrap();
+        assert!(keystore.info("reviewer").unwrap().protected);
+        assert!(keystore.load("reviewer").is_err());
+
+        let loaded = keystore.load_with_password("reviewer", &password).unwrap();
+        assert_eq!(imported.did(), loaded.did());
+
+        let wrong = secrecy::SecretString::new("wrong".to_string());
+        assert!(keystore.load_with_password("reviewer", &wrong).is_err());
+    }
+}
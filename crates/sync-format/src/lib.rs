@@ -3,17 +3,34 @@
 //! Core library for `.sync` format parsing and creation.
 //!
 //! This crate provides:
-//! - `.sync` archive reading and writing
+//! - `.sync` archive reading and writing, from files, memory or any `Read + Seek` source
 //! - `manifest.toml` parsing and validation
-//! - Archive builder for creating new `.sync` files
+//! - Archive builder for creating new `.sync` files, with a reproducible mode
+//!   and an injectable [`Clock`] for byte-for-byte rebuilds
+//! - Per-variant archive profiles of required and optional entries
+//! - Content-defined chunking and delta payload patches
+//! - Payload version history with checkout and rollback
+//! - A hash-chained provenance log of payload updates, verifiable with the
+//!   `signatures` feature
+//! - Hardened parsing with configurable limits for archives from untrusted peers
 //! - Encryption/decryption support (with `encryption` feature)
-//! - Signature verification (with `signatures` feature)
+//! - Manifest signing and signature verification (with `signatures` feature),
+//!   including an embedded whole-archive signature in `sync.sig`
+//! - did:key keystore for signing identities (with `signatures` feature)
+//! - Trust store and revocation list for accepting signers (with `signatures` feature)
 //!
 //! ## Features
 //!
-//! - `signatures` (default): Ed25519 signature verification
-//! - `encryption`: age-based payload encryption/decryption for vault archives
+//! - `fs` (default): archives opened from a path, the builder, in-place
+//!   updates, the keystore and path-based verification. Without it (e.g. on
+//!   `wasm32-unknown-unknown`) archives are parsed from memory or a
+//!   `Read + Seek` source and verified with the `_from_reader` functions
+//! - `signatures` (default): Ed25519 manifest and archive signing and verification
+//! - `encryption`: age-based payload encryption/decryption for vault archives,
+//!   with a shared passphrase or for X25519/did:key recipients
 //! - `crypto`: Enables both `signatures` and `encryption`
+//! - `mmap` (default): memory-mapped payload access via `SyncArchive::map_payload`;
+//!   implies `fs`
 //!
 //! ## Example
 //!
@@ -32,27 +49,78 @@
 //!     .write_to("new.sync")?;
 //! ```
 
+#[cfg(feature = "fs")]
+mod append;
+#[cfg(feature = "fs")]
 mod builder;
+mod clock;
+pub mod delta;
 mod error;
 mod format;
+#[cfg(feature = "signatures")]
+pub mod keystore;
+mod limits;
 mod manifest;
+mod migration;
+mod profile;
+mod reader;
+mod records;
+#[cfg(feature = "signatures")]
+pub mod signing;
+#[cfg(feature = "signatures")]
+pub mod trust;
+mod validation;
+#[cfg(feature = "encryption")]
+pub mod vault;
 #[cfg(feature = "signatures")]
 pub mod verification;
 
-pub use builder::SyncBuilder;
+#[cfg(feature = "fs")]
+pub use builder::{SyncBuilder, PAYLOAD_ALIGNMENT};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use delta::{ChunkedPayload, ChunkerConfig, PayloadPatch};
 pub use error::{Error, Result};
 #[cfg(feature = "encryption")]
 pub use format::{decrypt_data, encrypt_data};
-pub use format::{SyncArchive, SyncEntry};
+pub use format::{OpenOptions, SyncArchive, SyncEntry, HISTORY_DIR, HOST_ACTOR, PAYLOAD_DIR};
+#[cfg(all(feature = "signatures", feature = "fs"))]
+pub use keystore::Keystore;
+#[cfg(feature = "signatures")]
+pub use keystore::{decode_did_key, encode_did_key, KeyInfo};
+pub use limits::ArchiveLimits;
 pub use manifest::{
     EncryptionMeta, Manifest, ManifestCapabilities, ManifestEncryption, ManifestMetadata,
     ManifestOwnership, ManifestPermissions, ManifestPolicy, ManifestSignature,
-    ManifestVerification, NetworkScope, SyncManifest, SyncSection, SyncVariant,
+    ManifestVerification, NetworkScope, PayloadVersion, ProvenanceCheckpoint, ProvenanceRecord,
+    SyncManifest, SyncSection, SyncVariant,
+};
+pub use migration::{ManifestVersion, MigratedManifest, MigrationNote};
+pub use profile::ArchiveProfile;
+#[cfg(feature = "mmap")]
+pub use reader::EntryMap;
+pub use reader::{EntryReader, ReadAt};
+#[cfg(feature = "signatures")]
+pub use signing::SyncSigner;
+#[cfg(all(feature = "signatures", feature = "fs"))]
+pub use trust::verify_with_trust;
+#[cfg(feature = "signatures")]
+pub use trust::{
+    verify_with_trust_from_reader, verify_with_trust_from_reader_with_clock, TrustStore,
+    TrustVerdict, TrustedKey,
+};
+pub use validation::{Diagnostic, DiagnosticKind, Severity};
+#[cfg(all(feature = "signatures", feature = "fs"))]
+pub use verification::{
+    compute_content_hash, verify_archive_signature, verify_manifest_signature,
+    verify_manifest_signatures, verify_provenance, verify_sync_file,
 };
 #[cfg(feature = "signatures")]
 pub use verification::{
-    compute_content_hash, verify_manifest_signature, verify_sync_file, ManifestSignatureResult,
-    SyncSignature, VerificationResult,
+    verify_archive_signature_from_reader, verify_manifest_signature_from_reader,
+    verify_manifest_signatures_from_reader, verify_provenance_from_reader, ArchiveSignature,
+    ArchiveSignatureStatus, ManifestSignatureResult, MultiSignatureResult, ProvenanceResult,
+    ProvenanceState, ProvenanceStep, SignaturePolicy, SignerState, SignerStatus, SyncSignature,
+    VerificationResult, ARCHIVE_SIGNATURE_ENTRY,
 };
 
 // Re-export secrecy for consumers using the encryption feature
@@ -0,0 +1,547 @@
+//! Hardened parsing for archives from untrusted sources.
+//!
+//! The zip reader trusts the central directory: a later entry with the same
+//! name silently shadows an earlier one, and names, offsets and sizes are
+//! taken as declared. [`check_archive`] walks the raw central directory and
+//! every local header before the archive is opened and rejects
+//!
+//! - duplicate entry names ([`Error::DuplicateEntry`])
+//! - absolute, `..` or otherwise unsafe names ([`Error::UnsafeEntryName`])
+//! - entries whose data ranges overlap each other or the central directory
+//!   ([`Error::OverlappingEntries`])
+//! - entries that extend beyond the end of the file ([`Error::EntryOutOfBounds`])
+//! - offsets and sizes that overflow a `u64` ([`Error::SizeOverflow`])
+//! - anything exceeding the configured [`ArchiveLimits`] ([`Error::LimitExceeded`])
+
+use crate::records::{
+    name_len, read_central_record, u16_at, u32_at, CENTRAL_HEADER_LEN,
+    END_OF_CENTRAL_DIRECTORY_LEN, END_OF_CENTRAL_DIRECTORY_SIGNATURE, LOCAL_HEADER_LEN,
+    LOCAL_HEADER_SIGNATURE,
+};
+use crate::{Error, Result};
+use std::collections::HashSet;
+use std::io::{self, Read, Seek, SeekFrom};
+
+const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LEN: u64 = 56;
+const ZIP64_LOCATOR_LEN: u64 = 20;
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x0807_4b50;
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+
+/// Largest archive comment, which bounds the search for the end record.
+const MAX_COMMENT_LEN: u64 = u16::MAX as u64;
+
+/// Entries smaller than this are exempt from the compression ratio limit.
+const RATIO_EXEMPT_SIZE: u64 = 1024 * 1024;
+
+/// Limits enforced when an archive is opened in hardened mode.
+///
+/// See [`OpenOptions::hardened`](crate::OpenOptions::hardened).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveLimits {
+    /// Maximum number of entries.
+    pub max_entries: u64,
+    /// Maximum uncompressed size of any entry, in bytes.
+    pub max_entry_size: u64,
+    /// Maximum uncompressed size of `manifest.toml`, in bytes.
+    pub max_manifest_size: u64,
+    /// Maximum ratio of uncompressed to compressed size. Entries below 1 MiB
+    /// are exempt.
+    pub max_compression_ratio: u64,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_entries: 10_000,
+            max_entry_size: 64 * 1024 * 1024 * 1024,
+            max_manifest_size: 1024 * 1024,
+            max_compression_ratio: 100,
+        }
+    }
+}
+
+/// One central directory entry, with the byte range it occupies.
+struct EntryRange {
+    name: String,
+    start: u64,
+    end: u64,
+}
+
+/// Location of the central directory, from the (zip64) end record.
+struct CentralDirectory {
+    entries: u64,
+    offset: u64,
+    size: u64,
+    /// Where the central directory must end: the first end record.
+    end: u64,
+}
+
+/// Check the raw structure of an archive against `limits`.
+pub(crate) fn check_archive<R: Read + Seek>(reader: &mut R, limits: &ArchiveLimits) -> Result<()> {
+    let file_len = reader.seek(SeekFrom::End(0))?;
+    let directory = find_central_directory(reader, file_len)?;
+
+    if directory.entries > limits.max_entries {
+        return Err(Error::LimitExceeded(format!(
+            "{} entries, at most {} allowed",
+            directory.entries, limits.max_entries
+        )));
+    }
+    let directory_end = checked_add(directory.offset, directory.size, "central directory")?;
+    if directory_end != directory.end {
+        return Err(Error::InvalidFormat(
+            "central directory does not end at the end record".to_string(),
+        ));
+    }
+
+    reader.seek(SeekFrom::Start(directory.offset))?;
+    let mut central = io::BufReader::new(&mut *reader).take(directory.size);
+    let mut names = HashSet::new();
+    let mut headers = Vec::new();
+    for _ in 0..directory.entries {
+        let record = read_central_record(&mut central)?;
+        let raw_name = &record[CENTRAL_HEADER_LEN..][..name_len(&record)];
+        let name = std::str::from_utf8(raw_name)
+            .map_err(|_| Error::UnsafeEntryName(String::from_utf8_lossy(raw_name).into_owned()))?
+            .to_string();
+        check_name(&name)?;
+        if !names.insert(name.clone()) {
+            return Err(Error::DuplicateEntry(name));
+        }
+
+        let sizes = EntrySizes::parse(&record, &name)?;
+        check_sizes(&name, &sizes, limits)?;
+        headers.push((name, sizes, u16_at(&record, 8)));
+    }
+    if central.limit() != 0 {
+        return Err(Error::InvalidFormat(
+            "central directory size does not match its entries".to_string(),
+        ));
+    }
+    drop(central);
+
+    let mut ranges = Vec::with_capacity(headers.len());
+    for (name, sizes, flags) in headers {
+        let end = local_entry_end(reader, &name, &sizes, flags, file_len)?;
+        ranges.push(EntryRange {
+            name,
+            start: sizes.header_offset,
+            end,
+        });
+    }
+
+    ranges.sort_by_key(|range| range.start);
+    for pair in ranges.windows(2) {
+        if pair[1].start < pair[0].end {
+            return Err(Error::OverlappingEntries(
+                pair[0].name.clone(),
+                pair[1].name.clone(),
+            ));
+        }
+    }
+    if let Some(last) = ranges.last() {
+        if last.end > directory.offset {
+            return Err(Error::OverlappingEntries(
+                last.name.clone(),
+                "central directory".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Locate the end of central directory record, following a zip64 locator.
+fn find_central_directory<R: Read + Seek>(
+    reader: &mut R,
+    file_len: u64,
+) -> Result<CentralDirectory> {
+    let tail_len = file_len.min(END_OF_CENTRAL_DIRECTORY_LEN + MAX_COMMENT_LEN);
+    let tail_start = file_len - tail_len;
+    let mut tail = vec![0u8; tail_len as usize];
+    reader.seek(SeekFrom::Start(tail_start))?;
+    reader.read_exact(&mut tail)?;
+
+    // The end record must be followed by exactly its comment.
+    let end_len = END_OF_CENTRAL_DIRECTORY_LEN as usize;
+    let record_at = (0..tail.len().saturating_sub(end_len - 1))
+        .rev()
+        .find(|&at| {
+            u32_at(&tail, at) == END_OF_CENTRAL_DIRECTORY_SIGNATURE
+                && at + end_len + u16_at(&tail, at + 20) as usize == tail.len()
+        })
+        .ok_or_else(|| Error::InvalidFormat("missing end of central directory".to_string()))?;
+    let record = &tail[record_at..record_at + end_len];
+    let record_start = tail_start + record_at as u64;
+
+    // A zip64 locator, if any, sits right before the end record.
+    let mut locator = [0u8; ZIP64_LOCATOR_LEN as usize];
+    let has_locator = match record_start.checked_sub(ZIP64_LOCATOR_LEN) {
+        Some(locator_start) => {
+            reader.seek(SeekFrom::Start(locator_start))?;
+            reader.read_exact(&mut locator)?;
+            u32_at(&locator, 0) == ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE
+        }
+        None => false,
+    };
+    if !has_locator {
+        let entries = u16_at(record, 10);
+        let size = u32_at(record, 12);
+        let offset = u32_at(record, 16);
+        if entries == u16::MAX || size == u32::MAX || offset == u32::MAX {
+            return Err(Error::InvalidFormat("missing zip64 locator".to_string()));
+        }
+        return Ok(CentralDirectory {
+            entries: entries.into(),
+            offset: offset.into(),
+            size: size.into(),
+            end: record_start,
+        });
+    }
+
+    let locator_start = record_start - ZIP64_LOCATOR_LEN;
+    let zip64_start = u64_at(&locator, 8);
+    if checked_add(
+        zip64_start,
+        ZIP64_END_OF_CENTRAL_DIRECTORY_LEN,
+        "zip64 end record",
+    )? > locator_start
+    {
+        return Err(Error::EntryOutOfBounds("zip64 end record".to_string()));
+    }
+    let mut zip64 = [0u8; ZIP64_END_OF_CENTRAL_DIRECTORY_LEN as usize];
+    reader.seek(SeekFrom::Start(zip64_start))?;
+    reader.read_exact(&mut zip64)?;
+    if u32_at(&zip64, 0) != ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE {
+        return Err(Error::InvalidFormat(
+            "invalid zip64 end of central directory".to_string(),
+        ));
+    }
+
+    Ok(CentralDirectory {
+        entries: u64_at(&zip64, 32),
+        size: u64_at(&zip64, 40),
+        offset: u64_at(&zip64, 48),
+        end: zip64_start,
+    })
+}
+
+/// Sizes and offset of a central directory entry, resolved through the
+/// zip64 extra field where the 32-bit fields are saturated.
+struct EntrySizes {
+    compressed: u64,
+    uncompressed: u64,
+    header_offset: u64,
+}
+
+impl EntrySizes {
+    fn parse(record: &[u8], name: &str) -> Result<Self> {
+        let mut compressed = u64::from(u32_at(record, 20));
+        let mut uncompressed = u64::from(u32_at(record, 24));
+        let mut header_offset = u64::from(u32_at(record, 42));
+
+        let extra_start = CENTRAL_HEADER_LEN + name_len(record);
+        let mut extra = &record[extra_start..extra_start + u16_at(record, 30) as usize];
+        while extra.len() >= 4 {
+            let id = u16_at(extra, 0);
+            let len = u16_at(extra, 2) as usize;
+            let data = extra.get(4..4 + len).ok_or_else(|| {
+                Error::InvalidFormat(format!("truncated extra field in {}", name))
+            })?;
+            if id == ZIP64_EXTRA_FIELD_ID {
+                let mut values = data.chunks_exact(8).map(|value| u64_at(value, 0));
+                let mut next = |field: &mut u64| -> Result<()> {
+                    if *field == u64::from(u32::MAX) {
+                        *field = values.next().ok_or_else(|| {
+                            Error::InvalidFormat(format!("truncated zip64 field in {}", name))
+                        })?;
+                    }
+                    Ok(())
+                };
+                next(&mut uncompressed)?;
+                next(&mut compressed)?;
+                next(&mut header_offset)?;
+            }
+            extra = &extra[4 + len..];
+        }
+
+        Ok(Self {
+            compressed,
+            uncompressed,
+            header_offset,
+        })
+    }
+}
+
+/// Reject names that could escape an extraction directory or alias another
+/// entry.
+pub(crate) fn check_name(name: &str) -> Result<()> {
+    let directory = name.strip_suffix('/').unwrap_or(name);
+    let unsafe_name = directory.is_empty()
+        || directory.contains('\\')
+        || directory.contains('\0')
+        || directory.as_bytes().get(1) == Some(&b':')
+        || directory
+            .split('/')
+            .any(|part| part.is_empty() || part == "." || part == "..");
+    if unsafe_name {
+        return Err(Error::UnsafeEntryName(name.to_string()));
+    }
+    Ok(())
+}
+
+fn check_sizes(name: &str, sizes: &EntrySizes, limits: &ArchiveLimits) -> Result<()> {
+    if name == "manifest.toml" && sizes.uncompressed > limits.max_manifest_size {
+        return Err(Error::LimitExceeded(format!(
+            "manifest.toml is {} bytes, at most {} allowed",
+            sizes.uncompressed, limits.max_manifest_size
+        )));
+    }
+    if sizes.uncompressed > limits.max_entry_size {
+        return Err(Error::LimitExceeded(format!(
+            "{} is {} bytes, at most {} allowed",
+            name, sizes.uncompressed, limits.max_entry_size
+        )));
+    }
+    if sizes.uncompressed >= RATIO_EXEMPT_SIZE
+        && sizes.uncompressed / sizes.compressed.max(1) > limits.max_compression_ratio
+    {
+        return Err(Error::LimitExceeded(format!(
+            "{} expands {} bytes to {}, more than {}:1",
+            name, sizes.compressed, sizes.uncompressed, limits.max_compression_ratio
+        )));
+    }
+    Ok(())
+}
+
+/// Check the local header of an entry and return where its data (and data
+/// descriptor, if any) ends.
+fn local_entry_end<R: Read + Seek>(
+    reader: &mut R,
+    name: &str,
+    sizes: &EntrySizes,
+    flags: u16,
+    file_len: u64,
+) -> Result<u64> {
+    let header_end = checked_add(sizes.header_offset, LOCAL_HEADER_LEN, name)?;
+    if header_end > file_len {
+        return Err(Error::EntryOutOfBounds(name.to_string()));
+    }
+    let mut header = [0u8; LOCAL_HEADER_LEN as usize];
+    reader.seek(SeekFrom::Start(sizes.header_offset))?;
+    reader.read_exact(&mut header)?;
+    if u32_at(&header, 0) != LOCAL_HEADER_SIGNATURE {
+        return Err(Error::InvalidFormat(format!(
+            "invalid local header for {}",
+            name
+        )));
+    }
+
+    let local_name_len = u16_at(&header, 26) as usize;
+    let local_extra_len = u64::from(u16_at(&header, 28));
+    if checked_add(header_end, local_name_len as u64, name)? > file_len {
+        return Err(Error::EntryOutOfBounds(name.to_string()));
+    }
+    let mut local_name = vec![0u8; local_name_len];
+    reader.read_exact(&mut local_name)?;
+    if local_name != name.as_bytes() {
+        return Err(Error::InvalidFormat(format!(
+            "local header name of {} does not match the central directory",
+            name
+        )));
+    }
+
+    let data_start = checked_add(header_end, local_name_len as u64 + local_extra_len, name)?;
+    let mut end = checked_add(data_start, sizes.compressed, name)?;
+    if flags & FLAG_DATA_DESCRIPTOR != 0 && end <= file_len.saturating_sub(4) {
+        let mut signature = [0u8; 4];
+        reader.seek(SeekFrom::Start(end))?;
+        reader.read_exact(&mut signature)?;
+        let zip64 =
+            sizes.compressed > u64::from(u32::MAX) || sizes.uncompressed > u64::from(u32::MAX);
+        let descriptor_len = if zip64 { 20 } else { 12 };
+        let signature_len = if u32_at(&signature, 0) == DATA_DESCRIPTOR_SIGNATURE {
+            4
+        } else {
+            0
+        };
+        end = checked_add(end, descriptor_len + signature_len, name)?;
+    }
+    if end > file_len {
+        return Err(Error::EntryOutOfBounds(name.to_string()));
+    }
+    Ok(end)
+}
+
+fn checked_add(a: u64, b: u64, what: &str) -> Result<u64> {
+    a.checked_add(b)
+        .ok_or_else(|| Error::SizeOverflow(what.to_string()))
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().expect("8 bytes"))
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod tests {
+    use super::*;
+    use crate::{OpenOptions, SyncArchive, SyncBuilder, SyncManifest};
+    use std::path::Path;
+    use tempfile::tempdir;
+
+    fn write_archive(path: &Path, context: &[u8]) {
+        let manifest = SyncManifest::from_toml(
+            br#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "did:key:z6MkTest"
+hash_algo = "blake3"
+
+[sync]
+version = "1.3"
+content_type = "text/plain"
+display_ext = "txt"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#,
+        )
+        .unwrap();
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"payload data".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_context_bytes(context.to_vec())
+            .write_to(path)
+            .unwrap();
+    }
+
+    /// Rename an entry in both its local and central header.
+    fn rename_entry(path: &Path, from: &str, to: &str) {
+        assert_eq!(from.len(), to.len());
+        let mut bytes = std::fs::read(path).unwrap();
+        let mut renamed = 0;
+        for at in 0..=bytes.len() - from.len() {
+            if &bytes[at..at + from.len()] == from.as_bytes() {
+                bytes[at..at + to.len()].copy_from_slice(to.as_bytes());
+                renamed += 1;
+            }
+        }
+        assert_eq!(renamed, 2);
+        std::fs::write(path, bytes).unwrap();
+    }
+
+    fn open_hardened(path: &Path) -> Result<SyncArchive> {
+        OpenOptions::new()
+            .hardened(ArchiveLimits::default())
+            .open(path)
+    }
+
+    #[test]
+    fn test_well_formed_archive_passes() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("ok.sync");
+        write_archive(&path, b"{}");
+
+        let mut archive = open_hardened(&path).unwrap();
+        // Appended updates leave dead space but no overlaps.
+        archive.update_payload(b"new payload").unwrap();
+        open_hardened(&path).unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_and_unsafe_names_are_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("traversal.sync");
+        write_archive(&path, b"{}");
+        rename_entry(&path, "context.json", "../../etc/pw");
+        assert!(matches!(
+            open_hardened(&path),
+            Err(Error::UnsafeEntryName(name)) if name == "../../etc/pw"
+        ));
+
+        let path = dir.path().join("duplicate.sync");
+        write_archive(&path, b"{}");
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .unwrap();
+        let mut zip = zip::ZipWriter::new_append(file).unwrap();
+        zip.start_file("payloaX", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"shadow").unwrap();
+        zip.finish().unwrap();
+        rename_entry(&path, "payloaX", "payload");
+
+        // The plain reader lets the second payload shadow the first.
+        assert_eq!(
+            SyncArchive::open(&path).unwrap().read_payload().unwrap(),
+            b"shadow"
+        );
+        assert!(matches!(
+            open_hardened(&path),
+            Err(Error::DuplicateEntry(name)) if name == "payload"
+        ));
+    }
+
+    #[test]
+    fn test_overlapping_and_out_of_bounds_entries_are_rejected() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("overlap.sync");
+        write_archive(&path, b"{}");
+        let original = std::fs::read(&path).unwrap();
+
+        // Grow the declared size of the first entry's central record.
+        let central = original
+            .windows(4)
+            .position(|w| w == 0x0201_4b50u32.to_le_bytes())
+            .unwrap();
+        let mut bytes = original.clone();
+        bytes[central + 20..central + 24].copy_from_slice(&1000u32.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            open_hardened(&path),
+            Err(Error::OverlappingEntries(..))
+        ));
+
+        let mut bytes = original;
+        bytes[central + 20..central + 24].copy_from_slice(&0xFFFF_0000u32.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            open_hardened(&path),
+            Err(Error::EntryOutOfBounds(_))
+        ));
+    }
+
+    #[test]
+    fn test_limits_are_enforced() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("limits.sync");
+        write_archive(&path, &[b' '; 4096]);
+
+        let limits = ArchiveLimits {
+            max_entries: 3,
+            ..ArchiveLimits::default()
+        };
+        let err = OpenOptions::new().hardened(limits).open(&path).unwrap_err();
+        assert!(matches!(err, Error::LimitExceeded(_)), "{err}");
+
+        let limits = ArchiveLimits {
+            max_manifest_size: 16,
+            ..ArchiveLimits::default()
+        };
+        let err = OpenOptions::new().hardened(limits).open(&path).unwrap_err();
+        assert!(err.to_string().contains("manifest.toml"), "{err}");
+
+        let limits = ArchiveLimits {
+            max_entry_size: 1024,
+            ..ArchiveLimits::default()
+        };
+        let err = OpenOptions::new().hardened(limits).open(&path).unwrap_err();
+        assert!(err.to_string().contains("context.json"), "{err}");
+    }
+}
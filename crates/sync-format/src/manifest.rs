@@ -1,5 +1,8 @@
 use chrono::{DateTime, Duration, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use toml::Table;
 
 /// Sync variant type (v1.3).
 ///
@@ -51,6 +54,7 @@ impl std::str::FromStr for SyncVariant {
 
 /// The `[sync]` section of the manifest.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SyncSection {
     /// Format version (e.g., "1.2", "1.3").
     pub version: String,
@@ -61,10 +65,14 @@ pub struct SyncSection {
     /// Sync variant (v1.3): "plain", "vault", or "app".
     #[serde(default)]
     pub variant: SyncVariant,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// The `[meta]` section of the manifest.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestMetadata {
     /// Identifier of the creator.
     pub created_by: String,
@@ -72,15 +80,69 @@ pub struct ManifestMetadata {
     pub created_at: String,
     /// Hash algorithm used (e.g., "blake3").
     pub hash_algo: String,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// The `[policy]` section of the manifest.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestPolicy {
     /// Time-to-live in seconds.
     pub ttl: u64,
     /// Execution timeout in seconds.
     pub timeout: u64,
+    /// Number of replaced payload versions kept under `history/`.
+    ///
+    /// 0 (the default) keeps no history.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub keep_versions: u32,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+impl SyncSection {
+    /// A `[sync]` section for the latest format version and the plain
+    /// variant.
+    pub fn new(content_type: impl Into<String>, display_ext: impl Into<String>) -> Self {
+        Self {
+            version: crate::ManifestVersion::LATEST.to_string(),
+            content_type: content_type.into(),
+            display_ext: display_ext.into(),
+            variant: SyncVariant::default(),
+            extra: Table::new(),
+        }
+    }
+}
+
+impl ManifestMetadata {
+    /// Metadata hashed with `blake3`.
+    pub fn new(created_by: impl Into<String>, created_at: impl Into<String>) -> Self {
+        Self {
+            created_by: created_by.into(),
+            created_at: created_at.into(),
+            hash_algo: "blake3".to_string(),
+            extra: Table::new(),
+        }
+    }
+}
+
+impl ManifestPolicy {
+    /// A policy with the given TTL and timeout, in seconds, keeping no
+    /// history.
+    pub fn new(ttl: u64, timeout: u64) -> Self {
+        Self {
+            ttl,
+            timeout,
+            ..Self::default()
+        }
+    }
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
 }
 
 impl Default for ManifestPolicy {
@@ -88,12 +150,15 @@ impl Default for ManifestPolicy {
         Self {
             ttl: 3600,
             timeout: 30,
+            keep_versions: 0,
+            extra: Table::new(),
         }
     }
 }
 
 /// The `[permissions]` section of the manifest.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestPermissions {
     /// List of allowed network hosts.
     #[serde(default)]
@@ -101,10 +166,14 @@ pub struct ManifestPermissions {
     /// List of allowed environment variables.
     #[serde(default)]
     pub allow_env: Vec<String>,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// The `[ownership]` section of the manifest.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestOwnership {
     /// Optional owner capsule identifier.
     #[serde(default)]
@@ -112,10 +181,14 @@ pub struct ManifestOwnership {
     /// Whether writing is allowed.
     #[serde(default)]
     pub write_allowed: bool,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// The `[verification]` section of the manifest.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestVerification {
     /// Whether verification is enabled.
     #[serde(default)]
@@ -126,12 +199,17 @@ pub struct ManifestVerification {
     /// Type of proof for verification.
     #[serde(default)]
     pub proof_type: Option<String>,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// Encryption configuration (v1.3).
 ///
-/// Controls whether the payload is encrypted and which algorithm to use.
+/// Controls whether the payload, and optionally other entries, are encrypted
+/// and which algorithm to use.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestEncryption {
     /// Whether encryption is enabled.
     #[serde(default)]
@@ -142,12 +220,19 @@ pub struct ManifestEncryption {
     /// UI/UX hint information (not a trusted source).
     #[serde(default)]
     pub meta: Option<EncryptionMeta>,
+    /// Entries besides the payload that are encrypted (e.g., "context.json").
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<String>,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// Encryption metadata for UI/UX hints (v1.3).
 ///
 /// This section provides non-authoritative hints for the UI.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct EncryptionMeta {
     /// Key names (e.g., `["OPENAI_API_KEY"]`).
     #[serde(default)]
@@ -161,34 +246,180 @@ pub struct EncryptionMeta {
     /// User-facing hint message.
     #[serde(default)]
     pub hint: Option<String>,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// The `[capabilities]` section of the manifest.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestCapabilities {
     /// Capability identifiers (e.g., "local-first", "private-network").
     #[serde(default)]
     pub values: Vec<String>,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
-/// The `[signature]` section of the manifest.
+/// The `[signature]` section of the manifest, or one `[[cosignatures]]` entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct ManifestSignature {
     /// Signature algorithm (e.g., "Ed25519").
     pub algo: String,
+    /// Signer identity in did:key format.
+    ///
+    /// Required for co-signatures. The `[signature]` section omits it, since
+    /// its signer is always `meta.created_by`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
     /// Hash of the canonicalized manifest (e.g., "blake3:...").
     pub manifest_hash: String,
     /// Optional hash of the payload (e.g., "blake3:...").
     #[serde(skip_serializing_if = "Option::is_none")]
     pub payload_hash: Option<String>,
+    /// Hash of the `sync.wasm` module (e.g., "blake3:..."), set when the
+    /// archive has one so the signature also covers the code it runs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_hash: Option<String>,
     /// RFC3339 timestamp.
     pub timestamp: String,
     /// Base64-encoded signature.
     pub value: String,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+/// One `[[history]]` entry: a replaced payload kept in the archive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct PayloadVersion {
+    /// Version number, increasing with every replacement.
+    pub version: u64,
+    /// Archive entry holding the payload (e.g., "history/3").
+    pub entry: String,
+    /// Hash of the stored payload (e.g., "blake3:...").
+    pub hash: String,
+    /// Stored size in bytes.
+    pub size: u64,
+    /// RFC3339 timestamp of when this version was replaced.
+    pub replaced_at: String,
+    /// Unrecognized keys, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+/// One `[[provenance]]` entry: a recorded payload update.
+///
+/// Each record names the hash of the record before it, so the log forms a
+/// hash chain from the payload the archive was created with to the current
+/// one. See [`verify_provenance`](crate::verification::verify_provenance).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ProvenanceRecord {
+    /// Hash of the previous record; absent for the first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_record: Option<String>,
+    /// Hash of the stored payload before the update (e.g., "blake3:...").
+    pub previous_payload_hash: String,
+    /// Hash of the stored payload after the update.
+    pub payload_hash: String,
+    /// RFC3339 timestamp of the update.
+    pub timestamp: String,
+    /// Who made the update: a did:key, the hash of the wasm module
+    /// (e.g., "blake3:..."), or [`HOST_ACTOR`](crate::HOST_ACTOR).
+    pub actor: String,
+    /// Base64-encoded Ed25519 signature by `actor` over
+    /// [`signing_payload`](Self::signing_payload), if the actor is a did:key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Unrecognized keys, kept so they survive a rewrite. Not covered by the
+    /// record hash.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+impl ProvenanceRecord {
+    /// The bytes signed by the actor: every field except the signature.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let fields = [
+            "sync-provenance-v1",
+            self.previous_record.as_deref().unwrap_or(""),
+            &self.previous_payload_hash,
+            &self.payload_hash,
+            &self.timestamp,
+            &self.actor,
+        ];
+        fields.join("\n").into_bytes()
+    }
+
+    /// Hash of this record, including its signature, in "blake3:<hex>" format.
+    ///
+    /// The next record stores it as `previous_record`.
+    pub fn record_hash(&self) -> String {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&self.signing_payload());
+        hasher.update(b"\n");
+        hasher.update(self.signature.as_deref().unwrap_or("").as_bytes());
+        format!("blake3:{}", hex::encode(hasher.finalize().as_bytes()))
+    }
+}
+
+/// The `[provenance_checkpoint]` section: stands in for provenance records
+/// dropped when the log was compacted.
+///
+/// It commits to the last dropped record, so the first kept record still
+/// links up, and to the payload the archive was created with, so the chain
+/// stays anchored to the signed original. The signer vouches for the dropped
+/// records, which can no longer be checked. See
+/// [`SyncSigner::checkpoint_provenance`](crate::SyncSigner::checkpoint_provenance).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct ProvenanceCheckpoint {
+    /// Hash of the payload the archive was created with.
+    pub origin_payload_hash: String,
+    /// Hash of the last dropped record; the first kept record links to it.
+    pub last_record: String,
+    /// Hash of the stored payload after the last dropped record.
+    pub payload_hash: String,
+    /// Number of records dropped, including those of earlier checkpoints.
+    pub records: u64,
+    /// RFC3339 timestamp of the last dropped record.
+    pub timestamp: String,
+    /// did:key of the signer vouching for the dropped records.
+    pub signer: String,
+    /// Base64-encoded Ed25519 signature by `signer` over
+    /// [`signing_payload`](Self::signing_payload).
+    pub signature: String,
+    /// Unrecognized keys, kept so they survive a rewrite. Not covered by the
+    /// signature.
+    #[serde(flatten)]
+    pub extra: Table,
+}
+
+impl ProvenanceCheckpoint {
+    /// The bytes signed by the signer: every field except the signature.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let records = self.records.to_string();
+        let fields = [
+            "sync-provenance-checkpoint-v1",
+            &self.origin_payload_hash,
+            &self.last_record,
+            &self.payload_hash,
+            &records,
+            &self.timestamp,
+            &self.signer,
+        ];
+        fields.join("\n").into_bytes()
+    }
 }
 
 /// Complete manifest structure for a `.sync` archive.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[non_exhaustive]
 pub struct SyncManifest {
     /// The sync format section.
     pub sync: SyncSection,
@@ -211,9 +442,46 @@ pub struct SyncManifest {
     /// Signature section.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub signature: Option<ManifestSignature>,
+    /// Co-signatures over the same manifest and payload hashes
+    /// (`[[cosignatures]]`), e.g. from reviewers of a release.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<ManifestSignature>,
+    /// Newest payload version number handed out to the history.
+    ///
+    /// Versions are numbered from this counter, so a number is never reused
+    /// after a rollback or after old versions are dropped. Like the history
+    /// it changes with every update, so it is not covered by signatures.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub last_version: u64,
+    /// Previous payload versions, oldest first (`[[history]]`).
+    ///
+    /// Kept according to [`ManifestPolicy::keep_versions`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<PayloadVersion>,
+    /// Log of payload updates, oldest first (`[[provenance]]`).
+    ///
+    /// Every update appends a record of about 400 to 500 bytes, so the
+    /// hardened `max_manifest_size` of 1 MiB is reached after roughly two
+    /// thousand updates. Compact a long-lived log with
+    /// [`SyncSigner::checkpoint_provenance`](crate::SyncSigner::checkpoint_provenance).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provenance: Vec<ProvenanceRecord>,
+    /// Signed stand-in for the records dropped from the start of
+    /// [`provenance`](Self::provenance) (`[provenance_checkpoint]`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance_checkpoint: Option<ProvenanceCheckpoint>,
     /// Encryption settings (v1.3).
     #[serde(default)]
     pub encryption: ManifestEncryption,
+    /// Namespaced extension tables (`[extensions.<namespace>]`).
+    ///
+    /// Use [`SyncManifest::extension`] and [`SyncManifest::set_extension`]
+    /// rather than editing this map directly.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, Table>,
+    /// Unrecognized top-level keys and tables, kept so they survive a rewrite.
+    #[serde(flatten)]
+    pub extra: Table,
 }
 
 /// Network scope for share policy decisions.
@@ -229,6 +497,33 @@ pub enum NetworkScope {
 pub type Manifest = SyncManifest;
 
 impl SyncManifest {
+    /// A manifest with the given `[sync]` and `[meta]` sections and defaults
+    /// everywhere else.
+    ///
+    /// The manifest structs are `#[non_exhaustive]`, so code outside this
+    /// crate starts here (or from [`SyncManifest::from_toml`]) and sets the
+    /// fields it needs.
+    pub fn new(sync: SyncSection, meta: ManifestMetadata) -> Self {
+        Self {
+            sync,
+            meta,
+            policy: ManifestPolicy::default(),
+            permissions: ManifestPermissions::default(),
+            ownership: ManifestOwnership::default(),
+            verification: ManifestVerification::default(),
+            capabilities: ManifestCapabilities::default(),
+            signature: None,
+            cosignatures: Vec::new(),
+            last_version: 0,
+            history: Vec::new(),
+            provenance: Vec::new(),
+            provenance_checkpoint: None,
+            encryption: ManifestEncryption::default(),
+            extensions: BTreeMap::new(),
+            extra: Table::new(),
+        }
+    }
+
     /// Parse a manifest from TOML bytes.
     pub fn from_toml(data: &[u8]) -> crate::Result<Self> {
         let text = std::str::from_utf8(data)
@@ -236,6 +531,90 @@ impl SyncManifest {
         toml::from_str(text).map_err(|e| crate::Error::TomlError(e.to_string()))
     }
 
+    /// Parse a manifest from TOML bytes, upgrading older versions.
+    ///
+    /// The result always uses [`ManifestVersion::LATEST`](crate::ManifestVersion::LATEST)
+    /// and records every change made along the way.
+    pub fn from_toml_migrated(data: &[u8]) -> crate::Result<crate::MigratedManifest> {
+        let text = std::str::from_utf8(data)
+            .map_err(|e| crate::Error::ManifestError(format!("Invalid UTF-8: {}", e)))?;
+        crate::migration::migrate_toml(text)
+    }
+
+    /// Validate the manifest beyond what deserialization checks.
+    ///
+    /// Returns every finding, in a stable order; an empty list means the
+    /// manifest is valid.
+    pub fn validate(&self) -> Vec<crate::Diagnostic> {
+        crate::validation::validate_manifest(self)
+    }
+
+    /// Serialize the manifest to TOML text.
+    pub fn to_toml(&self) -> crate::Result<String> {
+        toml::to_string_pretty(self).map_err(|e| crate::Error::ManifestError(e.to_string()))
+    }
+
+    /// Put the manifest in canonical form, so equal manifests serialize to
+    /// the same bytes.
+    ///
+    /// Lists whose order carries no meaning (`permissions.allow_hosts`,
+    /// `permissions.allow_env`, `capabilities.values` and
+    /// `encryption.entries`) are sorted and deduplicated. Ordered lists such
+    /// as `history` and `provenance` are kept as they are, and tables are
+    /// already written with sorted keys.
+    pub fn canonicalize(&mut self) {
+        for list in [
+            &mut self.permissions.allow_hosts,
+            &mut self.permissions.allow_env,
+            &mut self.capabilities.values,
+            &mut self.encryption.entries,
+        ] {
+            list.sort();
+            list.dedup();
+        }
+    }
+
+    /// Get the extension table for `namespace`, if present.
+    pub fn extension(&self, namespace: &str) -> Option<&Table> {
+        self.extensions.get(namespace)
+    }
+
+    /// Deserialize the extension table for `namespace` into `T`.
+    ///
+    /// Returns `Ok(None)` if the manifest has no such extension.
+    pub fn extension_as<T: DeserializeOwned>(&self, namespace: &str) -> crate::Result<Option<T>> {
+        self.extensions
+            .get(namespace)
+            .map(|table| {
+                T::deserialize(table.clone()).map_err(|e| {
+                    crate::Error::ManifestError(format!("Invalid extension {}: {}", namespace, e))
+                })
+            })
+            .transpose()
+    }
+
+    /// Set the extension table for `namespace`, replacing any previous one.
+    ///
+    /// `value` must serialize to a TOML table. Namespaces should be unique to
+    /// the tool that owns them (e.g., "com.example.sync-tool").
+    pub fn set_extension<T: Serialize>(&mut self, namespace: &str, value: &T) -> crate::Result<()> {
+        if namespace.is_empty() {
+            return Err(crate::Error::ManifestError(
+                "Extension namespace must not be empty".to_string(),
+            ));
+        }
+        let table = Table::try_from(value).map_err(|e| {
+            crate::Error::ManifestError(format!("Invalid extension {}: {}", namespace, e))
+        })?;
+        self.extensions.insert(namespace.to_string(), table);
+        Ok(())
+    }
+
+    /// Remove and return the extension table for `namespace`.
+    pub fn remove_extension(&mut self, namespace: &str) -> Option<Table> {
+        self.extensions.remove(namespace)
+    }
+
     /// Get the creation timestamp.
     pub fn get_created_at(&self) -> crate::Result<DateTime<Utc>> {
         self.meta
@@ -295,3 +674,105 @@ impl SyncManifest {
             .any(|h| h == host || host.ends_with(h.trim_start_matches("*.")))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTENDED_MANIFEST: &str = r#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "did:key:z6MkTest"
+hash_algo = "blake3"
+spec_version = "1.0"
+
+[sync]
+version = "1.3"
+content_type = "text/plain"
+display_ext = "txt"
+
+[policy]
+ttl = 3600
+timeout = 30
+max_retries = 3
+
+[extensions."com.example.viewer"]
+theme = "dark"
+zoom = 2
+
+[future]
+released = 2027-01-01T00:00:00Z
+"#;
+
+    #[test]
+    fn test_unknown_fields_survive_round_trip() {
+        let mut manifest = SyncManifest::from_toml(EXTENDED_MANIFEST.as_bytes()).unwrap();
+        manifest.policy.ttl = 60;
+
+        let reparsed = SyncManifest::from_toml(manifest.to_toml().unwrap().as_bytes()).unwrap();
+        assert_eq!(reparsed.policy.ttl, 60);
+        assert_eq!(reparsed.meta.extra["spec_version"].as_str(), Some("1.0"));
+        assert_eq!(reparsed.policy.extra["max_retries"].as_integer(), Some(3));
+        assert!(reparsed.extra["future"]["released"].is_datetime());
+        assert_eq!(
+            reparsed.extension("com.example.viewer").unwrap()["theme"].as_str(),
+            Some("dark")
+        );
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        let mut first = SyncManifest::from_toml(EXTENDED_MANIFEST.as_bytes()).unwrap();
+        first.permissions.allow_hosts = vec!["b.example".into(), "a.example".into()];
+        first.capabilities.values = vec!["net".into(), "fs".into(), "net".into()];
+        let mut second = first.clone();
+        second.permissions.allow_hosts.reverse();
+
+        first.canonicalize();
+        second.canonicalize();
+        assert_eq!(first.permissions.allow_hosts, ["a.example", "b.example"]);
+        assert_eq!(first.capabilities.values, ["fs", "net"]);
+        assert_eq!(first.to_toml().unwrap(), second.to_toml().unwrap());
+    }
+
+    #[test]
+    fn test_extension_api() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Viewer {
+            theme: String,
+            zoom: u32,
+        }
+
+        let mut manifest = SyncManifest::from_toml(EXTENDED_MANIFEST.as_bytes()).unwrap();
+        let viewer: Viewer = manifest
+            .extension_as("com.example.viewer")
+            .unwrap()
+            .unwrap();
+        assert_eq!(viewer.zoom, 2);
+        assert!(manifest
+            .extension_as::<Viewer>("missing")
+            .unwrap()
+            .is_none());
+
+        let updated = Viewer {
+            theme: "light".to_string(),
+            zoom: 1,
+        };
+        manifest
+            .set_extension("com.example.viewer", &updated)
+            .unwrap();
+        assert!(manifest.set_extension("bad", &42).is_err());
+        assert!(manifest.set_extension("", &updated).is_err());
+
+        let reparsed = SyncManifest::from_toml(manifest.to_toml().unwrap().as_bytes()).unwrap();
+        assert_eq!(
+            reparsed
+                .extension_as::<Viewer>("com.example.viewer")
+                .unwrap(),
+            Some(updated)
+        );
+
+        manifest.remove_extension("com.example.viewer").unwrap();
+        assert!(!manifest.to_toml().unwrap().contains("extensions"));
+    }
+}
@@ -0,0 +1,313 @@
+//! Versioned manifest migration.
+//!
+//! Manifests declare their format in `sync.version`. Older manifests are
+//! upgraded one version at a time on the raw TOML table, before it is
+//! deserialized into the latest [`SyncManifest`] model, and every change is
+//! recorded as a [`MigrationNote`].
+//!
+//! | Step      | Changes                                                        |
+//! |-----------|----------------------------------------------------------------|
+//! | 1.0 → 1.2 | default `meta.hash_algo` and `[policy]`; keep `meta.spec_version` as an extra field |
+//! | 1.2 → 1.3 | make the implicit `sync.variant = "plain"` explicit             |
+
+use crate::manifest::{ManifestPolicy, SyncManifest};
+use crate::{Error, Result};
+use toml::value::Table;
+use toml::Value;
+
+/// A manifest format version known to this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ManifestVersion {
+    /// The original format.
+    V1_0,
+    /// Adds the required `meta.hash_algo` and `[policy]` fields.
+    V1_2,
+    /// Adds `sync.variant` and the `[encryption]` section.
+    V1_3,
+}
+
+impl ManifestVersion {
+    /// The version written by this crate.
+    pub const LATEST: ManifestVersion = ManifestVersion::V1_3;
+
+    /// All known versions, oldest first.
+    pub const ALL: [ManifestVersion; 3] = [
+        ManifestVersion::V1_0,
+        ManifestVersion::V1_2,
+        ManifestVersion::V1_3,
+    ];
+
+    /// The `sync.version` string for this version.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ManifestVersion::V1_0 => "1.0",
+            ManifestVersion::V1_2 => "1.2",
+            ManifestVersion::V1_3 => "1.3",
+        }
+    }
+
+    /// Detect the version of a raw manifest table.
+    ///
+    /// A manifest without `sync.version` is treated as 1.0.
+    pub fn detect(manifest: &Value) -> Result<Self> {
+        match manifest.get("sync").and_then(|sync| sync.get("version")) {
+            None => Ok(ManifestVersion::V1_0),
+            Some(Value::String(version)) => version.parse(),
+            Some(other) => Err(Error::ManifestError(format!(
+                "sync.version must be a string, got {}",
+                other.type_str()
+            ))),
+        }
+    }
+
+    fn next(&self) -> Option<Self> {
+        match self {
+            ManifestVersion::V1_0 => Some(ManifestVersion::V1_2),
+            ManifestVersion::V1_2 => Some(ManifestVersion::V1_3),
+            ManifestVersion::V1_3 => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ManifestVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ManifestVersion {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ManifestVersion::ALL
+            .into_iter()
+            .find(|version| version.as_str() == s)
+            .ok_or_else(|| Error::ManifestError(format!("unsupported manifest version: {}", s)))
+    }
+}
+
+/// A change made while upgrading a manifest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationNote {
+    /// Version the step started from.
+    pub from: ManifestVersion,
+    /// Version the step upgraded to.
+    pub to: ManifestVersion,
+    /// Description of the change.
+    pub message: String,
+}
+
+impl std::fmt::Display for MigrationNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} -> {}: {}", self.from, self.to, self.message)
+    }
+}
+
+/// A manifest upgraded to [`ManifestVersion::LATEST`].
+#[derive(Debug, Clone)]
+pub struct MigratedManifest {
+    /// The upgraded manifest.
+    pub manifest: SyncManifest,
+    /// The version the manifest was stored as.
+    pub source_version: ManifestVersion,
+    /// Every change made during the upgrade, in order.
+    pub notes: Vec<MigrationNote>,
+}
+
+impl MigratedManifest {
+    /// Check if the manifest was upgraded from an older version.
+    pub fn was_upgraded(&self) -> bool {
+        self.source_version != ManifestVersion::LATEST
+    }
+}
+
+/// Parse and upgrade a manifest from TOML text.
+pub(crate) fn migrate_toml(text: &str) -> Result<MigratedManifest> {
+    let mut value: Value = toml::from_str(text).map_err(|e| Error::TomlError(e.to_string()))?;
+    let source_version = ManifestVersion::detect(&value)?;
+    let root = value
+        .as_table_mut()
+        .ok_or_else(|| Error::ManifestError("manifest must be a TOML table".to_string()))?;
+
+    let mut notes = Vec::new();
+    let mut version = source_version;
+    while let Some(next) = version.next() {
+        let mut step = Step {
+            from: version,
+            to: next,
+            notes: &mut notes,
+        };
+        match version {
+            ManifestVersion::V1_0 => step.upgrade_1_0(root),
+            ManifestVersion::V1_2 => step.upgrade_1_2(root),
+            ManifestVersion::V1_3 => unreachable!("latest version has no successor"),
+        }
+        table_mut(root, "sync").insert(
+            "version".to_string(),
+            Value::String(next.as_str().to_string()),
+        );
+        version = next;
+    }
+
+    let manifest = value
+        .try_into()
+        .map_err(|e: toml::de::Error| Error::TomlError(e.to_string()))?;
+    Ok(MigratedManifest {
+        manifest,
+        source_version,
+        notes,
+    })
+}
+
+/// One upgrade step, collecting notes as it goes.
+struct Step<'a> {
+    from: ManifestVersion,
+    to: ManifestVersion,
+    notes: &'a mut Vec<MigrationNote>,
+}
+
+impl Step<'_> {
+    fn note(&mut self, message: impl Into<String>) {
+        self.notes.push(MigrationNote {
+            from: self.from,
+            to: self.to,
+            message: message.into(),
+        });
+    }
+
+    fn upgrade_1_0(&mut self, root: &mut Table) {
+        let meta = table_mut(root, "meta");
+        if meta.contains_key("spec_version") {
+            self.note("meta.spec_version is superseded by sync.version; kept as an extra field");
+        }
+        if !meta.contains_key("hash_algo") {
+            meta.insert("hash_algo".to_string(), Value::String("blake3".to_string()));
+            self.note("set missing meta.hash_algo to \"blake3\"");
+        }
+
+        let defaults = ManifestPolicy::default();
+        let policy = table_mut(root, "policy");
+        let mut filled = Vec::new();
+        for (key, default) in [("ttl", defaults.ttl), ("timeout", defaults.timeout)] {
+            if !policy.contains_key(key) {
+                policy.insert(key.to_string(), Value::Integer(default as i64));
+                filled.push(format!("policy.{} = {}", key, default));
+            }
+        }
+        if !filled.is_empty() {
+            self.note(format!("set missing {}", filled.join(", ")));
+        }
+    }
+
+    fn upgrade_1_2(&mut self, root: &mut Table) {
+        let sync = table_mut(root, "sync");
+        if !sync.contains_key("variant") {
+            sync.insert("variant".to_string(), Value::String("plain".to_string()));
+            self.note("set sync.variant to \"plain\", implicit before 1.3");
+        }
+    }
+}
+
+/// Get a sub-table, creating it (or replacing a non-table value) as needed.
+fn table_mut<'a>(root: &'a mut Table, key: &str) -> &'a mut Table {
+    let entry = root
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Table(Table::new()));
+    if !entry.is_table() {
+        *entry = Value::Table(Table::new());
+    }
+    match entry {
+        Value::Table(table) => table,
+        _ => unreachable!("entry was just made a table"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyncVariant;
+
+    #[test]
+    fn test_upgrade_1_0_to_latest() {
+        let migrated = SyncManifest::from_toml_migrated(
+            br#"
+[meta]
+spec_version = "1.0"
+created_at = "2026-01-01T00:00:00Z"
+created_by = "legacy"
+
+[sync]
+version = "1.0"
+content_type = "text/plain"
+display_ext = "txt"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(migrated.source_version, ManifestVersion::V1_0);
+        assert!(migrated.was_upgraded());
+        assert_eq!(migrated.manifest.sync.version, "1.3");
+        assert_eq!(migrated.manifest.sync.variant, SyncVariant::Plain);
+        assert_eq!(migrated.manifest.meta.hash_algo, "blake3");
+        assert_eq!(migrated.manifest.policy.ttl, 3600);
+        assert_eq!(
+            migrated.manifest.meta.extra["spec_version"].as_str(),
+            Some("1.0")
+        );
+
+        let steps: Vec<_> = migrated.notes.iter().map(|n| (n.from, n.to)).collect();
+        assert_eq!(
+            steps,
+            [
+                (ManifestVersion::V1_0, ManifestVersion::V1_2),
+                (ManifestVersion::V1_0, ManifestVersion::V1_2),
+                (ManifestVersion::V1_0, ManifestVersion::V1_2),
+                (ManifestVersion::V1_2, ManifestVersion::V1_3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_latest_manifest_is_unchanged() {
+        let migrated = SyncManifest::from_toml_migrated(
+            br#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "current"
+hash_algo = "blake3"
+
+[sync]
+version = "1.3"
+content_type = "text/plain"
+display_ext = "txt"
+variant = "vault"
+
+[policy]
+ttl = 60
+timeout = 5
+
+[encryption]
+enabled = true
+"#,
+        )
+        .unwrap();
+
+        assert!(!migrated.was_upgraded());
+        assert!(migrated.notes.is_empty());
+        assert_eq!(migrated.manifest.sync.variant, SyncVariant::Vault);
+    }
+
+    #[test]
+    fn test_unknown_version_is_rejected() {
+        let err = SyncManifest::from_toml_migrated(
+            br#"
+[sync]
+version = "2.0"
+"#,
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("unsupported manifest version: 2.0"));
+    }
+}
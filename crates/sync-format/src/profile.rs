@@ -0,0 +1,122 @@
+//! Per-variant archive profiles.
+//!
+//! The manifest's [`SyncVariant`] decides which entries an archive must
+//! carry. Plain, data and vault archives hold content, so a manifest and a
+//! payload are enough and `sync.wasm` is optional. App archives are driven
+//! by their module, so they need `sync.wasm` plus their assets as the
+//! payload. [`SyncArchive`](crate::SyncArchive) checks the profile on open,
+//! and [`SyncBuilder`](crate::SyncBuilder) before writing.
+
+use crate::format::PAYLOAD_DIR;
+use crate::manifest::SyncVariant;
+use crate::{Error, Result};
+
+/// Entries a content archive may carry besides its required ones.
+const CONTENT_OPTIONAL: &[&str] = &["sync.wasm", "context.json", "sync.proof", "sync.sig"];
+
+/// Entries an app archive may carry besides its required ones.
+const APP_OPTIONAL: &[&str] = &["context.json", "sync.proof", "sync.sig"];
+
+/// Entries an archive of one [`SyncVariant`] must or may contain.
+///
+/// The `payload` entry stands for the payload in either form: a single
+/// `payload` entry or a [`PAYLOAD_DIR`] tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveProfile {
+    variant: SyncVariant,
+    required: &'static [&'static str],
+    optional: &'static [&'static str],
+}
+
+impl ArchiveProfile {
+    /// The profile for archives of `variant`.
+    pub fn for_variant(variant: &SyncVariant) -> Self {
+        match variant {
+            SyncVariant::Plain | SyncVariant::Data | SyncVariant::Vault => Self {
+                variant: variant.clone(),
+                required: &["manifest.toml", "payload"],
+                optional: CONTENT_OPTIONAL,
+            },
+            SyncVariant::App => Self {
+                variant: SyncVariant::App,
+                required: &["manifest.toml", "payload", "sync.wasm"],
+                optional: APP_OPTIONAL,
+            },
+        }
+    }
+
+    /// The variant this profile applies to.
+    pub fn variant(&self) -> &SyncVariant {
+        &self.variant
+    }
+
+    /// Entries the archive must contain.
+    pub fn required(&self) -> &'static [&'static str] {
+        self.required
+    }
+
+    /// Entries the archive may contain besides the required ones.
+    pub fn optional(&self) -> &'static [&'static str] {
+        self.optional
+    }
+
+    /// Whether `name` must be present.
+    pub fn requires(&self, name: &str) -> bool {
+        self.required.contains(&name)
+    }
+
+    /// Check that the entry `names` of an archive include every required
+    /// entry.
+    ///
+    /// Entries outside the profile are not rejected, so archives written by
+    /// newer tools that add entries still open.
+    pub fn check<'a>(&self, names: impl IntoIterator<Item = &'a str>) -> Result<()> {
+        let names: Vec<&str> = names.into_iter().collect();
+        for required in self.required {
+            let present = names.iter().any(|name| {
+                name == required || (*required == "payload" && name.starts_with(PAYLOAD_DIR))
+            });
+            if !present {
+                return Err(Error::MissingEntry(required.to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl SyncVariant {
+    /// The [`ArchiveProfile`] for archives of this variant.
+    pub fn profile(&self) -> ArchiveProfile {
+        ArchiveProfile::for_variant(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_is_required_only_for_apps() {
+        let content = ["manifest.toml", "payload"];
+        for variant in [SyncVariant::Plain, SyncVariant::Data, SyncVariant::Vault] {
+            variant.profile().check(content).unwrap();
+            assert!(variant.profile().optional().contains(&"sync.wasm"));
+        }
+
+        let err = SyncVariant::App.profile().check(content).unwrap_err();
+        assert!(matches!(err, Error::MissingEntry(ref name) if name == "sync.wasm"));
+        SyncVariant::App
+            .profile()
+            .check(["manifest.toml", "payload/index.html", "sync.wasm"])
+            .unwrap();
+    }
+
+    #[test]
+    fn test_payload_is_always_required() {
+        let err = SyncVariant::Plain
+            .profile()
+            .check(["manifest.toml", "sync.wasm"])
+            .unwrap_err();
+        assert!(matches!(err, Error::MissingEntry(ref name) if name == "payload"));
+    }
+}
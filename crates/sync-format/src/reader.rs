@@ -0,0 +1,293 @@
+//! Streaming and memory-mapped access to stored archive entries.
+//!
+//! Entries in a `.sync` archive are STORED, so their bytes sit contiguously
+//! in the archive. [`EntryReader`] reads that range directly with positional
+//! reads through [`ReadAt`] (`pread` for files) instead of going through the
+//! ZIP decoder, and [`EntryMap`] (with the `mmap` feature) maps it into
+//! memory.
+
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Positional reads from the storage an archive is parsed from.
+///
+/// Implemented for [`File`] (with `pread`), for archives held in memory as a
+/// `Vec<u8>`, and for any `Read + Seek` source behind a [`Mutex`].
+pub trait ReadAt {
+    /// Read bytes starting at `offset`, returning how many were read.
+    ///
+    /// Returns 0 at or past the end.
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize>;
+
+    /// Total size in bytes.
+    fn size(&self) -> io::Result<u64>;
+}
+
+impl ReadAt for File {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        read_file_at(self, buf, offset)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+impl ReadAt for Vec<u8> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let start = usize::try_from(offset)
+            .unwrap_or(usize::MAX)
+            .min(self.len());
+        let len = buf.len().min(self.len() - start);
+        buf[..len].copy_from_slice(&self[start..start + len]);
+        Ok(len)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        Ok(self.len() as u64)
+    }
+}
+
+impl<R: Read + Seek> ReadAt for Mutex<R> {
+    fn read_at(&self, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+        let mut reader = self
+            .lock()
+            .map_err(|_| io::Error::other("archive reader lock poisoned"))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read(buf)
+    }
+
+    fn size(&self) -> io::Result<u64> {
+        let mut reader = self
+            .lock()
+            .map_err(|_| io::Error::other("archive reader lock poisoned"))?;
+        reader.seek(SeekFrom::End(0))
+    }
+}
+
+/// `Read + Seek` view over a stored entry's byte range.
+///
+/// Positions are relative to the start of the entry. Reads never go past the
+/// end of the entry, even though the underlying archive continues.
+pub struct EntryReader<S: ReadAt = File> {
+    source: Arc<S>,
+    offset: u64,
+    size: u64,
+    position: u64,
+}
+
+impl<S: ReadAt> std::fmt::Debug for EntryReader<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntryReader")
+            .field("offset", &self.offset)
+            .field("size", &self.size)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "fs")]
+impl EntryReader {
+    /// Open a reader over `size` bytes starting at `offset` in the archive file.
+    pub fn open<P: AsRef<Path>>(archive_path: P, offset: u64, size: u64) -> io::Result<Self> {
+        let file = File::open(archive_path)?;
+        Ok(Self::new(file, offset, size))
+    }
+
+    /// Create a reader from an already opened archive file.
+    pub fn new(file: File, offset: u64, size: u64) -> Self {
+        Self::from_source(Arc::new(file), offset, size)
+    }
+}
+
+impl<S: ReadAt> EntryReader<S> {
+    /// Create a reader over `size` bytes starting at `offset` in a shared
+    /// archive source.
+    pub fn from_source(source: Arc<S>, offset: u64, size: u64) -> Self {
+        Self {
+            source,
+            offset,
+            size,
+            position: 0,
+        }
+    }
+
+    /// Size of the entry in bytes.
+    pub fn len(&self) -> u64 {
+        self.size
+    }
+
+    /// Whether the entry is empty.
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    /// Byte offset of the entry data within the archive.
+    pub fn archive_offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Read bytes at a position relative to the entry start.
+    ///
+    /// This does not move the reader's cursor, so it can be called
+    /// concurrently through a shared reference.
+    pub fn read_at(&self, buf: &mut [u8], position: u64) -> io::Result<usize> {
+        if position >= self.size {
+            return Ok(0);
+        }
+        let remaining = self.size - position;
+        let len = std::cmp::min(buf.len() as u64, remaining) as usize;
+        self.source.read_at(&mut buf[..len], self.offset + position)
+    }
+
+    /// Fill `buf` completely from a position relative to the entry start.
+    pub fn read_exact_at(&self, mut buf: &mut [u8], mut position: u64) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read_at(buf, position) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "read past end of entry",
+                    ))
+                }
+                Ok(n) => {
+                    buf = &mut buf[n..];
+                    position += n as u64;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read the whole entry into memory.
+    pub fn read_all(&self) -> io::Result<Vec<u8>> {
+        let mut data = vec![0u8; self.size as usize];
+        self.read_exact_at(&mut data, 0)?;
+        Ok(data)
+    }
+}
+
+impl<S: ReadAt> Read for EntryReader<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.read_at(buf, self.position)?;
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<S: ReadAt> Seek for EntryReader<S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.size.checked_add_signed(n),
+            SeekFrom::Current(n) => self.position.checked_add_signed(n),
+        };
+
+        match new_pos {
+            Some(pos) => {
+                self.position = pos;
+                Ok(pos)
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
+
+/// Memory-mapped view over a stored entry's byte range.
+///
+/// Dereferences to the entry bytes without copying them. The map borrows the
+/// [`SyncArchive`](crate::SyncArchive) it came from, so the archive cannot be
+/// updated through that handle while the map is alive.
+///
+/// Requires the `mmap` feature.
+#[cfg(feature = "mmap")]
+#[derive(Debug)]
+pub struct EntryMap<'a> {
+    map: Option<memmap2::Mmap>,
+    _archive: std::marker::PhantomData<&'a ()>,
+}
+
+#[cfg(feature = "mmap")]
+impl EntryMap<'_> {
+    /// Map `size` bytes starting at `offset` in the archive file.
+    pub(crate) fn map(file: &File, offset: u64, size: u64) -> io::Result<Self> {
+        if size == 0 {
+            return Ok(Self {
+                map: None,
+                _archive: std::marker::PhantomData,
+            });
+        }
+
+        let file_len = file.metadata()?.len();
+        let in_bounds = offset.checked_add(size).is_some_and(|end| end <= file_len);
+        if !in_bounds {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "entry range extends past end of archive",
+            ));
+        }
+        let len = usize::try_from(size)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "entry too large to map"))?;
+
+        // SAFETY: The mapping is read-only, and the map borrows the archive,
+        // so it cannot be updated through this handle while the map is
+        // alive. Rewrites write a new file and rename it into place; only an
+        // in-place update through another handle could touch the mapped
+        // range, which `SyncArchive::map_payload` documents.
+        let map = unsafe {
+            memmap2::MmapOptions::new()
+                .offset(offset)
+                .len(len)
+                .map(file)?
+        };
+
+        Ok(Self {
+            map: Some(map),
+            _archive: std::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl std::ops::Deref for EntryMap<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.map.as_deref().unwrap_or(&[])
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl AsRef<[u8]> for EntryMap<'_> {
+    fn as_ref(&self) -> &[u8] {
+        self
+    }
+}
+
+#[cfg(unix)]
+fn read_file_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::unix::fs::FileExt;
+    FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_file_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    use std::os::windows::fs::FileExt;
+    FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_file_at(_file: &File, _buf: &mut [u8], _offset: u64) -> io::Result<usize> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "positional file reads are not supported on this platform",
+    ))
+}
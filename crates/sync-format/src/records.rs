@@ -0,0 +1,40 @@
+//! Raw zip record layout shared by in-place appends and hardened parsing.
+
+use crate::error::Result;
+use std::io::Read;
+
+pub(crate) const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+pub(crate) const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+pub(crate) const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+
+pub(crate) const LOCAL_HEADER_LEN: u64 = 30;
+pub(crate) const CENTRAL_HEADER_LEN: usize = 46;
+pub(crate) const END_OF_CENTRAL_DIRECTORY_LEN: u64 = 22;
+
+pub(crate) fn read_central_record<R: Read>(reader: &mut R) -> Result<Vec<u8>> {
+    let mut record = vec![0u8; CENTRAL_HEADER_LEN];
+    reader.read_exact(&mut record)?;
+    if u32_at(&record, 0) != CENTRAL_HEADER_SIGNATURE {
+        return Err(crate::Error::InvalidFormat(
+            "invalid central directory record".to_string(),
+        ));
+    }
+
+    let variable_len =
+        name_len(&record) + u16_at(&record, 30) as usize + u16_at(&record, 32) as usize;
+    record.resize(CENTRAL_HEADER_LEN + variable_len, 0);
+    reader.read_exact(&mut record[CENTRAL_HEADER_LEN..])?;
+    Ok(record)
+}
+
+pub(crate) fn name_len(record: &[u8]) -> usize {
+    u16_at(record, 28) as usize
+}
+
+pub(crate) fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+pub(crate) fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
@@ -0,0 +1,846 @@
+//! Manifest signing for `.sync` files.
+//!
+//! This module produces the manifest `[signature]` section checked by
+//! [`verify_manifest_signature`](crate::verification::verify_manifest_signature).
+//! Hashes are computed exactly as the verifier computes them, so a signed
+//! archive always round-trips through verification.
+//!
+//! [`SyncSigner::embed_archive_signature`] adds the embedded `sync.sig`
+//! signature checked by
+//! [`verify_archive_signature`](crate::verification::verify_archive_signature).
+
+use crate::clock::{Clock, SystemClock};
+#[cfg(feature = "fs")]
+use crate::format::hash_reader;
+use crate::keystore::encode_did_key;
+use crate::manifest::{ManifestSignature, SyncManifest};
+#[cfg(feature = "fs")]
+use crate::manifest::{ProvenanceCheckpoint, ProvenanceRecord};
+#[cfg(feature = "fs")]
+use crate::verification::{
+    build_archive_signing_payload, compute_archive_hash, compute_payload_tree_hash,
+    verify_provenance_from_reader, ArchiveSignature, ARCHIVE_SIGNATURE_ENTRY,
+};
+use crate::verification::{build_signing_payload, compute_manifest_hash, compute_payload_hash};
+#[cfg(feature = "fs")]
+use crate::SyncArchive;
+use crate::{Error, Result};
+use chrono::SecondsFormat;
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey};
+#[cfg(feature = "fs")]
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Signature algorithm written to the `[signature]` section.
+pub const SIGNATURE_ALGORITHM: &str = "Ed25519";
+
+/// Ed25519 signer for `.sync` manifests.
+///
+/// The signer identity is the did:key of its public key. Signing a manifest
+/// sets `meta.created_by` to that identity, since the verifier resolves the
+/// public key from `created_by`.
+///
+/// Signature timestamps come from the system clock unless another
+/// [`Clock`] is set with [`with_clock`](Self::with_clock).
+#[derive(Clone)]
+pub struct SyncSigner {
+    signing_key: SigningKey,
+    did: String,
+    clock: Arc<dyn Clock>,
+}
+
+impl std::fmt::Debug for SyncSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncSigner")
+            .field("did", &self.did)
+            .field("clock", &self.clock)
+            .finish()
+    }
+}
+
+impl SyncSigner {
+    /// Create a signer from an Ed25519 signing key.
+    pub fn new(signing_key: SigningKey) -> Self {
+        let did = encode_did_key(&signing_key.verifying_key());
+        Self {
+            signing_key,
+            did,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Read signature timestamps from `clock`.
+    pub fn with_clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Create a signer from a 32-byte Ed25519 secret key.
+    pub fn from_bytes(secret_key: &[u8; 32]) -> Self {
+        Self::new(SigningKey::from_bytes(secret_key))
+    }
+
+    /// Generate a signer with a fresh random key.
+    pub fn generate() -> Self {
+        Self::new(SigningKey::generate(&mut rand::rngs::OsRng))
+    }
+
+    /// Get the 32-byte Ed25519 secret key.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.signing_key.to_bytes()
+    }
+
+    /// Get the signer identity in did:key format.
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    /// Get the Ed25519 public key.
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+
+    /// Sign a manifest in place.
+    ///
+    /// Sets `meta.created_by` to the signer identity, computes the canonical
+    /// manifest hash (and the payload hash when `payload` is given), and
+    /// stores the resulting `[signature]` section on the manifest.
+    pub fn sign_manifest(
+        &self,
+        manifest: &mut SyncManifest,
+        payload: Option<&[u8]>,
+    ) -> Result<ManifestSignature> {
+        self.sign_manifest_with_payload_hash(manifest, payload.map(compute_payload_hash))
+    }
+
+    /// Sign a manifest in place using a precomputed "blake3:<hex>" payload hash.
+    ///
+    /// Useful when the payload is streamed and never held in memory.
+    pub fn sign_manifest_with_payload_hash(
+        &self,
+        manifest: &mut SyncManifest,
+        payload_hash: Option<String>,
+    ) -> Result<ManifestSignature> {
+        self.sign_manifest_with_hashes(manifest, payload_hash, None)
+    }
+
+    /// Sign a manifest in place using precomputed payload and `sync.wasm`
+    /// hashes.
+    ///
+    /// Pass the module hash whenever the archive has a `sync.wasm` entry:
+    /// trust verification rejects signatures that do not cover it.
+    pub fn sign_manifest_with_hashes(
+        &self,
+        manifest: &mut SyncManifest,
+        payload_hash: Option<String>,
+        module_hash: Option<String>,
+    ) -> Result<ManifestSignature> {
+        manifest.meta.created_by = self.did.clone();
+        manifest.signature = None;
+
+        let manifest_hash =
+            compute_manifest_hash(manifest).map_err(|e| Error::SignatureError(e.to_string()))?;
+        let signature = self.sign_hashes(manifest_hash, payload_hash, module_hash, None)?;
+
+        manifest.signature = Some(signature.clone());
+        Ok(signature)
+    }
+
+    /// Add this signer's co-signature to a signed manifest.
+    ///
+    /// The co-signature covers the same manifest and payload hashes as the
+    /// `[signature]` section, so the manifest must not have changed since it
+    /// was signed. `meta.created_by` is left alone. An earlier co-signature
+    /// by the same signer is replaced.
+    pub fn cosign_manifest(&self, manifest: &mut SyncManifest) -> Result<ManifestSignature> {
+        let primary = manifest.signature.as_ref().ok_or_else(|| {
+            Error::SignatureError("manifest must be signed before co-signing".to_string())
+        })?;
+        let manifest_hash =
+            compute_manifest_hash(manifest).map_err(|e| Error::SignatureError(e.to_string()))?;
+        if manifest_hash != primary.manifest_hash {
+            return Err(Error::SignatureError(
+                "manifest changed since it was signed".to_string(),
+            ));
+        }
+
+        let payload_hash = primary.payload_hash.clone();
+        let module_hash = primary.module_hash.clone();
+        let signature = self.sign_hashes(
+            manifest_hash,
+            payload_hash,
+            module_hash,
+            Some(self.did.clone()),
+        )?;
+
+        manifest
+            .cosignatures
+            .retain(|cosignature| cosignature.signer.as_deref() != Some(self.did.as_str()));
+        manifest.cosignatures.push(signature.clone());
+        Ok(signature)
+    }
+
+    /// Co-sign an existing signed archive and rewrite its manifest.
+    #[cfg(feature = "fs")]
+    pub fn cosign_archive(&self, archive: &mut SyncArchive) -> Result<ManifestSignature> {
+        let mut manifest = archive.manifest().clone();
+        let signature = self.cosign_manifest(&mut manifest)?;
+        archive.update_manifest(&manifest)?;
+
+        Ok(signature)
+    }
+
+    /// Sign precomputed hashes, producing a `[signature]` or co-signature entry.
+    fn sign_hashes(
+        &self,
+        manifest_hash: String,
+        payload_hash: Option<String>,
+        module_hash: Option<String>,
+        signer: Option<String>,
+    ) -> Result<ManifestSignature> {
+        let mut signature = ManifestSignature {
+            algo: SIGNATURE_ALGORITHM.to_string(),
+            signer,
+            manifest_hash,
+            payload_hash,
+            module_hash,
+            timestamp: self.clock.now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            value: String::new(),
+            extra: Default::default(),
+        };
+
+        let signing_payload = build_signing_payload(&signature, &self.did)
+            .map_err(|e| Error::SignatureError(e.to_string()))?;
+        let ed_sig = self.signing_key.sign(&signing_payload);
+        signature.value = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            ed_sig.to_bytes(),
+        );
+
+        Ok(signature)
+    }
+
+    /// Sign an existing archive and rewrite its manifest.
+    ///
+    /// The payload hash is included when the archive has a payload entry or
+    /// a payload directory, and the module hash when it has `sync.wasm`.
+    #[cfg(feature = "fs")]
+    pub fn sign_archive(&self, archive: &mut SyncArchive) -> Result<ManifestSignature> {
+        let payload_hash = if archive.payload_entry().is_some() {
+            Some(hash_reader(archive.payload_reader()?)?)
+        } else if archive.has_payload_dir() {
+            let mut files = BTreeMap::new();
+            for entry in archive.payload_entries() {
+                files.insert(
+                    entry.payload_path().unwrap_or_default().to_string(),
+                    hash_reader(archive.entry_reader(&entry.name)?)?,
+                );
+            }
+            Some(compute_payload_tree_hash(&files))
+        } else {
+            None
+        };
+        let module_hash = if archive.has_wasm() {
+            Some(hash_reader(archive.entry_reader("sync.wasm")?)?)
+        } else {
+            None
+        };
+
+        let mut manifest = archive.manifest().clone();
+        let signature = self.sign_manifest_with_hashes(&mut manifest, payload_hash, module_hash)?;
+        archive.update_manifest(&manifest)?;
+
+        Ok(signature)
+    }
+
+    /// Sign every entry of an archive and store the result in `sync.sig`.
+    ///
+    /// The signature covers all other entries, including the manifest, so
+    /// embed it last: any later update invalidates it. An existing `sync.sig`
+    /// is replaced.
+    #[cfg(feature = "fs")]
+    pub fn embed_archive_signature(&self, archive: &mut SyncArchive) -> Result<ArchiveSignature> {
+        let file = std::fs::File::open(archive.archive_path())?;
+        let archive_hash = compute_archive_hash(&mut zip::ZipArchive::new(file)?)
+            .map_err(|e| Error::SignatureError(e.to_string()))?;
+
+        let mut signature = ArchiveSignature {
+            algorithm: SIGNATURE_ALGORITHM.to_string(),
+            public_key: self.did.clone(),
+            archive_hash,
+            signed_at: self.clock.now().timestamp().max(0) as u64,
+            signature: String::new(),
+        };
+
+        let signing_payload = build_archive_signing_payload(&signature)
+            .map_err(|e| Error::SignatureError(e.to_string()))?;
+        let ed_sig = self.signing_key.sign(&signing_payload);
+        signature.signature = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            ed_sig.to_bytes(),
+        );
+
+        let signature_json = serde_json::to_vec_pretty(&signature)?;
+        archive.rewrite(&[(ARCHIVE_SIGNATURE_ENTRY, &signature_json)])?;
+
+        Ok(signature)
+    }
+
+    /// Update an archive's payload, logging the update in its provenance
+    /// with this signer as the actor and signing the record.
+    ///
+    /// See [`SyncArchive::update_payload`] for how the payload is written.
+    #[cfg(feature = "fs")]
+    pub fn update_payload(&self, archive: &mut SyncArchive, new_payload: &[u8]) -> Result<()> {
+        let sign = |payload: &[u8]| {
+            base64::Engine::encode(
+                &base64::engine::general_purpose::STANDARD,
+                self.signing_key.sign(payload).to_bytes(),
+            )
+        };
+        archive.update_payload_recorded(new_payload, self.did.clone(), Some(&sign))
+    }
+
+    /// Compact an archive's `[[provenance]]` log, keeping the newest `keep`
+    /// records and replacing the rest with a `[provenance_checkpoint]`
+    /// signed by this signer.
+    ///
+    /// The log is verified first and a broken chain is refused, since the
+    /// checkpoint vouches for the records it replaces. Returns `None`, and
+    /// leaves the archive alone, when the log has no more than `keep`
+    /// records.
+    #[cfg(feature = "fs")]
+    pub fn checkpoint_provenance(
+        &self,
+        archive: &mut SyncArchive,
+        keep: usize,
+    ) -> Result<Option<ProvenanceCheckpoint>> {
+        let mut manifest = archive.manifest().clone();
+        let excess = manifest.provenance.len().saturating_sub(keep);
+        if excess == 0 {
+            return Ok(None);
+        }
+
+        let file = std::fs::File::open(archive.archive_path())?;
+        let result = verify_provenance_from_reader(file)
+            .map_err(|e| Error::SignatureError(e.to_string()))?;
+        if let Some(error) = result.error {
+            return Err(Error::SignatureError(format!(
+                "cannot checkpoint a broken provenance log: {}",
+                error
+            )));
+        }
+
+        let dropped: Vec<ProvenanceRecord> = manifest.provenance.drain(..excess).collect();
+        let last = &dropped[excess - 1];
+        let earlier = manifest
+            .provenance_checkpoint
+            .as_ref()
+            .map_or(0, |checkpoint| checkpoint.records);
+        let mut checkpoint = ProvenanceCheckpoint {
+            origin_payload_hash: result.origin_payload_hash,
+            last_record: last.record_hash(),
+            payload_hash: last.payload_hash.clone(),
+            records: earlier + excess as u64,
+            timestamp: last.timestamp.clone(),
+            signer: self.did.clone(),
+            signature: String::new(),
+            extra: Default::default(),
+        };
+        checkpoint.signature = base64::Engine::encode(
+            &base64::engine::general_purpose::STANDARD,
+            self.signing_key
+                .sign(&checkpoint.signing_payload())
+                .to_bytes(),
+        );
+
+        manifest.provenance_checkpoint = Some(checkpoint.clone());
+        archive.update_manifest(&manifest)?;
+        Ok(Some(checkpoint))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::verify_manifest_signature;
+    use crate::SyncBuilder;
+    use tempfile::tempdir;
+
+    fn create_test_manifest() -> SyncManifest {
+        let toml_str = r#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "placeholder"
+hash_algo = "blake3"
+
+[sync]
+version = "1.2"
+content_type = "text/plain"
+display_ext = "txt"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#;
+        SyncManifest::from_toml(toml_str.as_bytes()).unwrap()
+    }
+
+    fn test_signer() -> SyncSigner {
+        SyncSigner::from_bytes(&[7u8; 32])
+    }
+
+    #[test]
+    fn test_sign_manifest_sets_identity() {
+        let signer = test_signer();
+        let mut manifest = create_test_manifest();
+
+        let signature = signer.sign_manifest(&mut manifest, Some(b"data")).unwrap();
+
+        assert_eq!(manifest.meta.created_by, signer.did());
+        assert!(signer.did().starts_with("did:key:z6Mk"));
+        assert_eq!(signature.algo, SIGNATURE_ALGORITHM);
+        assert!(signature.payload_hash.is_some());
+    }
+
+    #[test]
+    fn test_builder_signature_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("signed.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"signed payload".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert!(result.payload_hash.is_some());
+    }
+
+    #[test]
+    fn test_verify_signed_archive_from_memory() {
+        use crate::verification::{
+            verify_manifest_signature_from_reader, verify_provenance_from_reader,
+        };
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("signed.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"signed payload".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let archive = SyncArchive::from_bytes(bytes.clone()).unwrap();
+        let result =
+            verify_manifest_signature_from_reader(archive.archive_reader().unwrap()).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        let provenance = verify_provenance_from_reader(std::io::Cursor::new(&bytes[..])).unwrap();
+        assert!(provenance.valid);
+
+        let mut tampered = bytes;
+        let at = tampered
+            .windows(14)
+            .position(|window| window == b"signed payload")
+            .unwrap();
+        tampered[at] = b'S';
+        // The zip reader may already reject the altered entry by its CRC.
+        let result = verify_manifest_signature_from_reader(std::io::Cursor::new(tampered));
+        assert!(!result.is_ok_and(|result| result.valid));
+    }
+
+    #[test]
+    fn test_builder_signs_streamed_payload() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("signed-stream.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_reader(std::io::Cursor::new(b"streamed payload".to_vec()))
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+
+        let archive = SyncArchive::open(&path).unwrap();
+        assert_eq!(archive.read_payload().unwrap(), b"streamed payload");
+
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        // The spooled payload must not be left behind.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_sign_existing_archive_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("unsigned.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"payload".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        test_signer().sign_archive(&mut archive).unwrap();
+        assert!(archive.manifest().signature.is_some());
+
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+    }
+
+    #[test]
+    fn test_rewrite_keeps_unknown_fields_signed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("extended.sync");
+
+        let mut manifest = create_test_manifest();
+        manifest.extra.insert(
+            "x-future".to_string(),
+            toml::Value::Table(toml::Table::new()),
+        );
+        manifest
+            .meta
+            .extra
+            .insert("origin".to_string(), "newer-tool".into());
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"payload".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+
+        // A tool that does not know the extra fields rewrites the manifest.
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let manifest = archive.manifest().clone();
+        archive.update_manifest(&manifest).unwrap();
+
+        assert_eq!(
+            archive.manifest().meta.extra["origin"].as_str(),
+            Some("newer-tool")
+        );
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+    }
+
+    #[test]
+    fn test_payload_directory_signature_round_trip() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("signed-dir.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .add_payload_bytes("a.txt", b"alpha".to_vec())
+            .add_payload_reader("nested/b.txt", std::io::Cursor::new(b"beta".to_vec()))
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+
+        // Re-signing the archive yields the same payload hash.
+        let mut archive = SyncArchive::open(&path).unwrap();
+        let signature = test_signer().sign_archive(&mut archive).unwrap();
+        assert_eq!(signature.payload_hash, result.payload_hash);
+    }
+
+    #[test]
+    fn test_tampered_payload_fails_verification() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("tampered.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"original".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(test_signer())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        archive.update_payload(b"tampered").unwrap();
+
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(!result.valid);
+        assert_eq!(result.error.as_deref(), Some("payload hash mismatch"));
+    }
+
+    #[test]
+    fn test_embedded_archive_signature() {
+        use crate::verification::{verify_archive_signature, ArchiveSignatureStatus};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("sealed.sync");
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"original".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        assert_eq!(
+            archive.signature_status(),
+            &ArchiveSignatureStatus::Unsigned
+        );
+
+        let signer = test_signer();
+        let signature = signer.embed_archive_signature(&mut archive).unwrap();
+        assert_eq!(signature.public_key, signer.did());
+        let valid = ArchiveSignatureStatus::Valid {
+            public_key: signer.did().to_string(),
+        };
+        assert_eq!(archive.signature_status(), &valid);
+        assert_eq!(verify_archive_signature(&path).unwrap(), valid);
+        assert_eq!(archive.read_payload().unwrap(), b"original");
+
+        // Any change to another entry breaks the signature.
+        archive.update_payload(b"tampered").unwrap();
+        assert!(matches!(
+            archive.signature_status(),
+            ArchiveSignatureStatus::Invalid(reason) if reason.contains("hash mismatch")
+        ));
+
+        // Signing again replaces sync.sig rather than adding a second one.
+        signer.embed_archive_signature(&mut archive).unwrap();
+        assert!(archive.signature_status().is_valid());
+        let signatures = archive
+            .entries()
+            .iter()
+            .filter(|entry| entry.name == "sync.sig")
+            .count();
+        assert_eq!(signatures, 1);
+    }
+
+    #[test]
+    fn test_cosignatures_meet_threshold_policy() {
+        use crate::verification::{
+            verify_manifest_signatures, SignaturePolicy, SignerState, VerificationError,
+        };
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("release.sync");
+        let publisher = test_signer();
+        let reviewer = SyncSigner::from_bytes(&[8u8; 32]);
+        let absent = SyncSigner::from_bytes(&[9u8; 32]);
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"release".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(publisher.clone())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        reviewer.cosign_archive(&mut archive).unwrap();
+        reviewer.cosign_archive(&mut archive).unwrap();
+        let cosignatures = &archive.manifest().cosignatures;
+        assert_eq!(cosignatures.len(), 1);
+        assert_eq!(cosignatures[0].signer.as_deref(), Some(reviewer.did()));
+
+        // Co-signing leaves the publisher's signature intact.
+        let result = verify_manifest_signature(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+
+        let keys = [publisher.did(), reviewer.did(), absent.did()];
+        let result =
+            verify_manifest_signatures(&path, &SignaturePolicy::threshold(2, keys)).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert_eq!(result.signers[2].status, SignerState::Missing);
+
+        // A signer listed twice still counts once.
+        let doubled = SignaturePolicy {
+            signers: vec![publisher.did().to_string(), publisher.did().to_string()],
+            threshold: 2,
+        };
+        assert!(matches!(
+            verify_manifest_signatures(&path, &doubled),
+            Err(VerificationError::InvalidPolicy(_))
+        ));
+
+        let result = verify_manifest_signatures(&path, &SignaturePolicy::all(keys)).unwrap();
+        assert!(!result.valid);
+        let error = result.error.unwrap();
+        assert!(
+            error.contains("2 of 3 required signatures valid"),
+            "{error}"
+        );
+        assert!(
+            error.contains(&format!("{}: missing", absent.did())),
+            "{error}"
+        );
+
+        // Editing the manifest invalidates every signature.
+        let mut manifest = archive.manifest().clone();
+        manifest.policy.ttl = 60;
+        archive.update_manifest(&manifest).unwrap();
+        let result =
+            verify_manifest_signatures(&path, &SignaturePolicy::threshold(1, keys)).unwrap();
+        assert!(!result.valid);
+        assert_eq!(
+            result.signers[1].status,
+            SignerState::Invalid("manifest hash mismatch".to_string())
+        );
+        assert!(reviewer.cosign_archive(&mut archive).is_err());
+    }
+
+    #[test]
+    fn test_provenance_chain_from_signed_creation() {
+        use crate::verification::{verify_provenance, ProvenanceState};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("lineage.sync");
+        let publisher = test_signer();
+        let editor = SyncSigner::from_bytes(&[8u8; 32]);
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"v1".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec())
+            .with_signer(publisher)
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        editor.update_payload(&mut archive, b"v2").unwrap();
+        archive.update_payload(b"v3").unwrap();
+
+        let result = verify_provenance(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert!(result.anchored);
+        assert!(!result.is_fully_signed());
+        assert_eq!(result.origin_payload_hash, compute_payload_hash(b"v1"));
+        assert_eq!(result.payload_hash, compute_payload_hash(b"v3"));
+        let states: Vec<_> = result.steps.iter().map(|s| s.state.clone()).collect();
+        assert_eq!(states, [ProvenanceState::Signed, ProvenanceState::Unsigned]);
+        assert_eq!(result.steps[0].actor, editor.did());
+        assert!(result.steps[1].actor.starts_with("blake3:"));
+
+        // Rewriting an earlier record breaks its signature and the link to it.
+        let mut manifest = archive.manifest().clone();
+        manifest.provenance[0].payload_hash = compute_payload_hash(b"forged");
+        archive.update_manifest(&manifest).unwrap();
+        let result = verify_provenance(&path).unwrap();
+        assert!(!result.valid);
+        assert!(matches!(result.steps[0].state, ProvenanceState::Broken(_)));
+        assert!(matches!(result.steps[1].state, ProvenanceState::Broken(_)));
+    }
+
+    #[test]
+    fn test_provenance_checkpoint_compacts_the_log() {
+        use crate::verification::{verify_provenance, ProvenanceState};
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("compacted.sync");
+        let publisher = test_signer();
+        let editor = SyncSigner::from_bytes(&[8u8; 32]);
+
+        SyncBuilder::new()
+            .with_manifest(create_test_manifest())
+            .with_payload_bytes(b"v1".to_vec())
+            .with_signer(publisher.clone())
+            .write_to(&path)
+            .unwrap();
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        for payload in [&b"v2"[..], b"v3", b"v4"] {
+            editor.update_payload(&mut archive, payload).unwrap();
+        }
+        assert!(publisher
+            .checkpoint_provenance(&mut archive, 5)
+            .unwrap()
+            .is_none());
+
+        let checkpoint = publisher
+            .checkpoint_provenance(&mut archive, 1)
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.records, 2);
+        assert_eq!(checkpoint.payload_hash, compute_payload_hash(b"v3"));
+        assert_eq!(archive.manifest().provenance.len(), 1);
+
+        // Later updates chain onto the kept records, and a second checkpoint
+        // can drop every record.
+        archive.update_payload(b"v5").unwrap();
+        let result = verify_provenance(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert!(result.anchored);
+        assert_eq!(result.origin_payload_hash, compute_payload_hash(b"v1"));
+        assert_eq!(result.steps.len(), 2);
+        let checkpoint_step = result.checkpoint.unwrap();
+        assert_eq!(checkpoint_step.actor, publisher.did());
+        assert_eq!(checkpoint_step.state, ProvenanceState::Signed);
+
+        let checkpoint = editor
+            .checkpoint_provenance(&mut archive, 0)
+            .unwrap()
+            .unwrap();
+        assert_eq!(checkpoint.records, 4);
+        assert!(archive.manifest().provenance.is_empty());
+        archive.update_payload(b"v6").unwrap();
+        let result = verify_provenance(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert!(result.anchored);
+        assert_eq!(result.steps.len(), 1);
+
+        // Claiming another origin breaks the checkpoint signature.
+        let mut manifest = archive.manifest().clone();
+        let forged = manifest.provenance_checkpoint.as_mut().unwrap();
+        forged.origin_payload_hash = compute_payload_hash(b"forged");
+        archive.update_manifest(&manifest).unwrap();
+        let result = verify_provenance(&path).unwrap();
+        assert!(!result.valid);
+        assert!(result.error.unwrap().starts_with("checkpoint:"));
+        assert!(!result.anchored);
+        assert!(publisher.checkpoint_provenance(&mut archive, 0).is_err());
+    }
+
+    #[test]
+    fn test_signed_archive_keeps_anchor_with_history() {
+        use crate::verification::verify_provenance;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("versioned.sync");
+        let signer = test_signer();
+        let mut manifest = create_test_manifest();
+        manifest.policy.keep_versions = 2;
+
+        SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"v1".to_vec())
+            .with_signer(signer.clone())
+            .write_to(&path)
+            .unwrap();
+        let signed_hash = verify_manifest_signature(&path).unwrap().manifest_hash;
+
+        let mut archive = SyncArchive::open(&path).unwrap();
+        signer.update_payload(&mut archive, b"v2").unwrap();
+        signer.update_payload(&mut archive, b"v3").unwrap();
+        assert_eq!(archive.manifest().last_version, 2);
+        assert_eq!(archive.manifest().history.len(), 2);
+
+        let result = verify_provenance(&path).unwrap();
+        assert!(result.valid, "verification failed: {:?}", result.error);
+        assert!(result.anchored);
+        assert!(result.is_fully_signed());
+        assert_eq!(
+            verify_manifest_signature(&path).unwrap().manifest_hash,
+            signed_hash
+        );
+    }
+}
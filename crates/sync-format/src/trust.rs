@@ -0,0 +1,509 @@
+//! Trust store for deciding which signers to accept.
+//!
+//! Signature verification only proves that an archive was signed by the key
+//! named in `meta.created_by`. A [`TrustStore`] decides whether that key is
+//! trusted: it lists trusted did:keys, the variants each may sign and an
+//! optional expiry, plus a local revocation list.
+//!
+//! ```toml
+//! [[trusted]]
+//! did = "did:key:z6Mk..."
+//! name = "release-publisher"
+//! variants = ["app", "plain"]
+//! expires_at = "2027-01-01T00:00:00Z"
+//!
+//! [[revoked]]
+//! did = "did:key:z6Mk..."
+//! reason = "key compromised"
+//! ```
+//!
+//! # Example
+//!
+//! ```ignore
+//! use sync_format::trust::{verify_with_trust, TrustStore};
+//!
+//! let store = TrustStore::load("~/.config/sync/trust.toml")?;
+//! let verdict = verify_with_trust("app.sync".as_ref(), &store)?;
+//! if !verdict.is_trusted() {
+//!     eprintln!("refusing to run: {}", verdict);
+//! }
+//! ```
+
+use crate::clock::{Clock, SystemClock};
+use crate::keystore::decode_did_key;
+use crate::manifest::SyncVariant;
+use crate::verification::{read_manifest, verify_manifest_signature_from_reader};
+use crate::{Error, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// A signer trusted by a [`TrustStore`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustedKey {
+    /// Signer identity in did:key format.
+    pub did: String,
+    /// Optional human-readable label.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Variants this signer may sign. Empty means every variant.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<SyncVariant>,
+    /// When the trust ends, if ever.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl TrustedKey {
+    /// Trust `did` for every variant, without expiry.
+    pub fn new(did: impl Into<String>) -> Self {
+        Self {
+            did: did.into(),
+            name: None,
+            variants: Vec::new(),
+            expires_at: None,
+        }
+    }
+
+    /// Set a human-readable label.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Restrict the signer to the given variants.
+    pub fn with_variants(mut self, variants: Vec<SyncVariant>) -> Self {
+        self.variants = variants;
+        self
+    }
+
+    /// End the trust at `expires_at`.
+    pub fn with_expiry(mut self, expires_at: DateTime<Utc>) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    /// Check if this signer may sign archives of `variant`.
+    pub fn allows(&self, variant: &SyncVariant) -> bool {
+        self.variants.is_empty() || self.variants.contains(variant)
+    }
+}
+
+/// A signer on the revocation list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevokedKey {
+    /// Signer identity in did:key format.
+    pub did: String,
+    /// Why the key was revoked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Trusted signers and revoked keys, stored as TOML.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrustStore {
+    /// Trusted signers.
+    #[serde(default)]
+    pub trusted: Vec<TrustedKey>,
+    /// Revoked keys. Revocation overrides trust.
+    #[serde(default)]
+    pub revoked: Vec<RevokedKey>,
+}
+
+impl TrustStore {
+    /// Create an empty trust store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a trust store from a TOML file.
+    #[cfg(feature = "fs")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::from_toml(&text)
+    }
+
+    /// Save the trust store to a TOML file.
+    #[cfg(feature = "fs")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, self.to_toml()?)?;
+        Ok(())
+    }
+
+    /// Parse a trust store, checking that every did:key is well formed.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let store: Self = toml::from_str(text).map_err(|e| Error::TomlError(e.to_string()))?;
+        let dids = store
+            .trusted
+            .iter()
+            .map(|key| &key.did)
+            .chain(store.revoked.iter().map(|key| &key.did));
+        for did in dids {
+            decode_did_key(did)
+                .map_err(|e| Error::TrustError(format!("invalid did:key {:?}: {}", did, e)))?;
+        }
+        Ok(store)
+    }
+
+    /// Serialize the trust store to TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| Error::TomlError(e.to_string()))
+    }
+
+    /// Trust a signer, replacing any earlier entry for the same did:key.
+    pub fn trust(&mut self, key: TrustedKey) {
+        self.trusted.retain(|trusted| trusted.did != key.did);
+        self.trusted.push(key);
+    }
+
+    /// Add a key to the revocation list.
+    pub fn revoke(&mut self, did: impl Into<String>, reason: Option<String>) {
+        let did = did.into();
+        self.revoked.retain(|revoked| revoked.did != did);
+        self.revoked.push(RevokedKey { did, reason });
+    }
+
+    /// Find the trust entry for a did:key.
+    pub fn trusted_key(&self, did: &str) -> Option<&TrustedKey> {
+        self.trusted.iter().find(|key| key.did == did)
+    }
+
+    /// Find the revocation entry for a did:key.
+    pub fn revocation(&self, did: &str) -> Option<&RevokedKey> {
+        self.revoked.iter().find(|key| key.did == did)
+    }
+
+    /// Decide whether `signer` may sign an archive of `variant` at time `at`.
+    ///
+    /// The signature itself is assumed to be valid.
+    pub fn check_signer(
+        &self,
+        signer: &str,
+        variant: &SyncVariant,
+        at: DateTime<Utc>,
+    ) -> TrustVerdict {
+        if let Some(revoked) = self.revocation(signer) {
+            return TrustVerdict::Revoked {
+                signer: signer.to_string(),
+                reason: revoked.reason.clone(),
+            };
+        }
+
+        let Some(key) = self.trusted_key(signer) else {
+            return TrustVerdict::Untrusted {
+                signer: Some(signer.to_string()),
+                reason: "signer is not in the trust store".to_string(),
+            };
+        };
+        if let Some(expires_at) = key.expires_at.filter(|expires_at| *expires_at <= at) {
+            return TrustVerdict::Expired {
+                signer: signer.to_string(),
+                expired_at: expires_at,
+            };
+        }
+        if !key.allows(variant) {
+            return TrustVerdict::Untrusted {
+                signer: Some(signer.to_string()),
+                reason: format!("signer is not trusted for {} archives", variant),
+            };
+        }
+
+        TrustVerdict::Trusted {
+            signer: signer.to_string(),
+        }
+    }
+}
+
+/// Whether an archive's signer is trusted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrustVerdict {
+    /// The signature is valid and the signer is trusted for this archive.
+    Trusted {
+        /// Signer identity in did:key format.
+        signer: String,
+    },
+    /// The archive is unsigned, the signature is invalid, or the signer is
+    /// unknown or out of scope.
+    Untrusted {
+        /// Signer identity, if the signature was valid.
+        signer: Option<String>,
+        /// Why the archive is not trusted.
+        reason: String,
+    },
+    /// The signer is on the revocation list.
+    Revoked {
+        /// Signer identity in did:key format.
+        signer: String,
+        /// Why the key was revoked.
+        reason: Option<String>,
+    },
+    /// The signer's trust has expired.
+    Expired {
+        /// Signer identity in did:key format.
+        signer: String,
+        /// When the trust ended.
+        expired_at: DateTime<Utc>,
+    },
+}
+
+impl TrustVerdict {
+    /// Check if the archive may be used.
+    pub fn is_trusted(&self) -> bool {
+        matches!(self, TrustVerdict::Trusted { .. })
+    }
+}
+
+impl std::fmt::Display for TrustVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrustVerdict::Trusted { signer } => write!(f, "trusted signer {}", signer),
+            TrustVerdict::Untrusted {
+                signer: Some(signer),
+                reason,
+            } => write!(f, "untrusted signer {}: {}", signer, reason),
+            TrustVerdict::Untrusted {
+                signer: None,
+                reason,
+            } => write!(f, "untrusted: {}", reason),
+            TrustVerdict::Revoked {
+                signer,
+                reason: Some(reason),
+            } => write!(f, "revoked signer {}: {}", signer, reason),
+            TrustVerdict::Revoked {
+                signer,
+                reason: None,
+            } => write!(f, "revoked signer {}", signer),
+            TrustVerdict::Expired { signer, expired_at } => {
+                write!(f, "trust in signer {} expired at {}", signer, expired_at)
+            }
+        }
+    }
+}
+
+/// Verify the manifest signature of a `.sync` file and check its signer.
+///
+/// The signer is `meta.created_by`, and the archive variant is checked
+/// against the signer's scope. When the archive has a `sync.wasm` module the
+/// signature must cover its hash, otherwise the archive is untrusted.
+/// Co-signatures are not considered.
+#[cfg(feature = "fs")]
+pub fn verify_with_trust(path: &Path, store: &TrustStore) -> Result<TrustVerdict> {
+    verify_with_trust_from_reader(fs::File::open(path)?, store)
+}
+
+/// Verify the manifest signature of an archive read from `reader` and check
+/// its signer. See [`verify_with_trust`].
+pub fn verify_with_trust_from_reader<R: Read + Seek>(
+    reader: R,
+    store: &TrustStore,
+) -> Result<TrustVerdict> {
+    verify_with_trust_from_reader_with_clock(reader, store, &SystemClock)
+}
+
+/// Like [`verify_with_trust_from_reader`], checking key expiry against the
+/// time read from `clock`.
+pub fn verify_with_trust_from_reader_with_clock<R: Read + Seek>(
+    mut reader: R,
+    store: &TrustStore,
+    clock: &dyn Clock,
+) -> Result<TrustVerdict> {
+    let result = verify_manifest_signature_from_reader(&mut reader)
+        .map_err(|e| Error::SignatureError(e.to_string()))?;
+    if !result.valid {
+        return Ok(TrustVerdict::Untrusted {
+            signer: None,
+            reason: result
+                .error
+                .unwrap_or_else(|| "invalid signature".to_string()),
+        });
+    }
+
+    reader.seek(SeekFrom::Start(0))?;
+    let (archive, manifest) =
+        read_manifest(reader).map_err(|e| Error::SignatureError(e.to_string()))?;
+    let covers_module = manifest
+        .signature
+        .as_ref()
+        .is_some_and(|signature| signature.module_hash.is_some());
+    if !covers_module && archive.index_for_name("sync.wasm").is_some() {
+        return Ok(TrustVerdict::Untrusted {
+            signer: Some(manifest.meta.created_by),
+            reason: "signature does not cover sync.wasm".to_string(),
+        });
+    }
+    Ok(store.check_signer(
+        &manifest.meta.created_by,
+        &manifest.sync.variant,
+        clock.now(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SyncBuilder, SyncManifest, SyncSigner};
+    use tempfile::tempdir;
+
+    fn write_signed(path: &Path, signer: Option<&SyncSigner>) {
+        let manifest = SyncManifest::from_toml(
+            br#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "placeholder"
+hash_algo = "blake3"
+
+[sync]
+version = "1.3"
+content_type = "text/plain"
+display_ext = "txt"
+variant = "app"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#,
+        )
+        .unwrap();
+        let mut builder = SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_payload_bytes(b"payload".to_vec())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0".to_vec());
+        if let Some(signer) = signer {
+            builder = builder.with_signer(signer.clone());
+        }
+        builder.write_to(path).unwrap();
+    }
+
+    #[test]
+    fn test_trust_store_round_trip() {
+        let signer = SyncSigner::from_bytes(&[1u8; 32]);
+        let mut store = TrustStore::new();
+        store.trust(
+            TrustedKey::new(signer.did())
+                .with_name("publisher")
+                .with_variants(vec![SyncVariant::App])
+                .with_expiry("2030-01-01T00:00:00Z".parse().unwrap()),
+        );
+        store.revoke(
+            SyncSigner::from_bytes(&[2u8; 32]).did(),
+            Some("lost".to_string()),
+        );
+
+        let text = store.to_toml().unwrap();
+        assert!(text.contains("variants = [\"app\"]"), "{text}");
+        assert_eq!(TrustStore::from_toml(&text).unwrap(), store);
+
+        let err =
+            TrustStore::from_toml("[[trusted]]\ndid = \"did:web:example.com\"\n").unwrap_err();
+        assert!(err.to_string().contains("invalid did:key"));
+    }
+
+    #[test]
+    fn test_verdicts() {
+        let dir = tempdir().unwrap();
+        let signer = SyncSigner::from_bytes(&[1u8; 32]);
+        let signed = dir.path().join("signed.sync");
+        let unsigned = dir.path().join("unsigned.sync");
+        write_signed(&signed, Some(&signer));
+        write_signed(&unsigned, None);
+
+        let mut store = TrustStore::new();
+        let verdict = verify_with_trust(&signed, &store).unwrap();
+        assert!(matches!(
+            verdict,
+            TrustVerdict::Untrusted {
+                signer: Some(_),
+                ..
+            }
+        ));
+
+        store.trust(TrustedKey::new(signer.did()).with_variants(vec![SyncVariant::App]));
+        assert!(verify_with_trust(&signed, &store).unwrap().is_trusted());
+        assert!(matches!(
+            verify_with_trust(&unsigned, &store).unwrap(),
+            TrustVerdict::Untrusted { signer: None, .. }
+        ));
+
+        store.trust(TrustedKey::new(signer.did()).with_variants(vec![SyncVariant::Vault]));
+        let verdict = verify_with_trust(&signed, &store).unwrap();
+        assert!(verdict.to_string().contains("not trusted for app archives"));
+
+        let expiry: DateTime<Utc> = "2026-06-01T00:00:00Z".parse().unwrap();
+        store.trust(TrustedKey::new(signer.did()).with_expiry(expiry));
+        let before = "2026-05-01T00:00:00Z".parse().unwrap();
+        assert!(store
+            .check_signer(signer.did(), &SyncVariant::App, before)
+            .is_trusted());
+        assert_eq!(
+            store.check_signer(signer.did(), &SyncVariant::App, expiry),
+            TrustVerdict::Expired {
+                signer: signer.did().to_string(),
+                expired_at: expiry,
+            }
+        );
+
+        let at = |now| {
+            let clock = crate::FixedClock::new(now);
+            verify_with_trust_from_reader_with_clock(
+                fs::File::open(&signed).unwrap(),
+                &store,
+                &clock,
+            )
+            .unwrap()
+        };
+        assert!(at(before).is_trusted());
+        assert!(matches!(at(expiry), TrustVerdict::Expired { .. }));
+
+        // Swapping the module breaks the signature even though the manifest
+        // and payload are untouched.
+        let swapped = dir.path().join("swapped.sync");
+        fs::copy(&signed, &swapped).unwrap();
+        crate::SyncArchive::open(&swapped)
+            .unwrap()
+            .rewrite(&[("sync.wasm", &b"\0asm\x01\0\0\0\x00"[..])])
+            .unwrap();
+        let verdict = verify_with_trust(&swapped, &store).unwrap();
+        assert!(
+            matches!(verdict, TrustVerdict::Untrusted { ref reason, .. } if reason == "module hash mismatch"),
+            "{verdict:?}"
+        );
+
+        // A signature made without a module hash does not vouch for the module.
+        let uncovered = dir.path().join("uncovered.sync");
+        fs::copy(&signed, &uncovered).unwrap();
+        let mut archive = crate::SyncArchive::open(&uncovered).unwrap();
+        let mut manifest = archive.manifest().clone();
+        signer
+            .sign_manifest_with_payload_hash(
+                &mut manifest,
+                Some(crate::verification::compute_payload_hash(b"payload")),
+            )
+            .unwrap();
+        archive.update_manifest(&manifest).unwrap();
+        assert!(
+            crate::verification::verify_manifest_signature(&uncovered)
+                .unwrap()
+                .valid
+        );
+        assert_eq!(
+            verify_with_trust(&uncovered, &store).unwrap(),
+            TrustVerdict::Untrusted {
+                signer: Some(signer.did().to_string()),
+                reason: "signature does not cover sync.wasm".to_string(),
+            }
+        );
+
+        store.revoke(signer.did(), None);
+        assert_eq!(
+            verify_with_trust(&signed, &store).unwrap(),
+            TrustVerdict::Revoked {
+                signer: signer.did().to_string(),
+                reason: None,
+            }
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Structured manifest validation.
+//!
+//! [`SyncManifest::from_toml`] only checks that the TOML deserializes.
+//! [`SyncManifest::validate`] goes further and reports every problem it finds
+//! as a typed [`Diagnostic`], so callers can decide whether warnings matter.
+
+use crate::manifest::{SyncManifest, SyncVariant};
+use crate::migration::ManifestVersion;
+use chrono::DateTime;
+
+/// How serious a validation finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The manifest is usable, but probably not what the author intended.
+    Warning,
+    /// The manifest is invalid.
+    Error,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// The kind of problem a [`Diagnostic`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// `sync.content_type` is not a valid MIME type.
+    InvalidContentType,
+    /// `sync.display_ext` contains characters that are not allowed.
+    InvalidDisplayExt,
+    /// `policy.ttl` is zero, so the archive expires immediately.
+    ZeroTtl,
+    /// `policy.timeout` is zero, so no execution can finish.
+    ZeroTimeout,
+    /// An `permissions.allow_hosts` entry is not a valid host pattern.
+    InvalidAllowHost,
+    /// `meta.created_at` is not an RFC 3339 timestamp.
+    InvalidCreatedAt,
+    /// `encryption.enabled` disagrees with `sync.variant`.
+    EncryptionVariantMismatch,
+    /// `sync.version` is not a known [`ManifestVersion`].
+    UnknownVersion,
+}
+
+/// A single manifest validation finding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// What kind of problem was found.
+    pub kind: DiagnosticKind,
+    /// How serious it is.
+    pub severity: Severity,
+    /// Dotted path of the offending field (e.g., "sync.content_type").
+    pub field: &'static str,
+    /// Human-readable description.
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(kind: DiagnosticKind, field: &'static str, message: String) -> Self {
+        Self {
+            kind,
+            severity: Severity::Error,
+            field,
+            message,
+        }
+    }
+
+    fn warning(kind: DiagnosticKind, field: &'static str, message: String) -> Self {
+        Self {
+            kind,
+            severity: Severity::Warning,
+            field,
+            message,
+        }
+    }
+
+    /// Check if this diagnostic has error severity.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.field, self.message)
+    }
+}
+
+/// Validate a manifest and collect every finding.
+pub(crate) fn validate_manifest(manifest: &SyncManifest) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if manifest.sync.version.parse::<ManifestVersion>().is_err() {
+        let supported: Vec<_> = ManifestVersion::ALL.iter().map(|v| v.as_str()).collect();
+        diagnostics.push(Diagnostic::warning(
+            DiagnosticKind::UnknownVersion,
+            "sync.version",
+            format!(
+                "unknown version {:?} (supported: {})",
+                manifest.sync.version,
+                supported.join(", ")
+            ),
+        ));
+    }
+
+    if !is_valid_mime_type(&manifest.sync.content_type) {
+        diagnostics.push(Diagnostic::error(
+            DiagnosticKind::InvalidContentType,
+            "sync.content_type",
+            format!("{:?} is not a valid MIME type", manifest.sync.content_type),
+        ));
+    }
+
+    if !is_valid_display_ext(&manifest.sync.display_ext) {
+        diagnostics.push(Diagnostic::error(
+            DiagnosticKind::InvalidDisplayExt,
+            "sync.display_ext",
+            format!(
+                "{:?} may only contain ASCII letters, digits, '.', '-', '_' and '+'",
+                manifest.sync.display_ext
+            ),
+        ));
+    }
+
+    if DateTime::parse_from_rfc3339(&manifest.meta.created_at).is_err() {
+        diagnostics.push(Diagnostic::error(
+            DiagnosticKind::InvalidCreatedAt,
+            "meta.created_at",
+            format!(
+                "{:?} is not an RFC 3339 timestamp",
+                manifest.meta.created_at
+            ),
+        ));
+    }
+
+    if manifest.policy.ttl == 0 {
+        diagnostics.push(Diagnostic::warning(
+            DiagnosticKind::ZeroTtl,
+            "policy.ttl",
+            "ttl is 0, so the archive expires immediately".to_string(),
+        ));
+    }
+
+    if manifest.policy.timeout == 0 {
+        diagnostics.push(Diagnostic::error(
+            DiagnosticKind::ZeroTimeout,
+            "policy.timeout",
+            "timeout is 0, so no execution can complete".to_string(),
+        ));
+    }
+
+    for host in &manifest.permissions.allow_hosts {
+        if !is_valid_host_pattern(host) {
+            diagnostics.push(Diagnostic::error(
+                DiagnosticKind::InvalidAllowHost,
+                "permissions.allow_hosts",
+                format!("{:?} is not a valid host or \"*.domain\" pattern", host),
+            ));
+        }
+    }
+
+    let is_vault = manifest.sync.variant == SyncVariant::Vault;
+    if is_vault != manifest.encryption.enabled {
+        let message = if is_vault {
+            "variant is \"vault\" but encryption.enabled is false".to_string()
+        } else {
+            format!(
+                "encryption.enabled is true but variant is {:?}, not \"vault\"",
+                manifest.sync.variant.to_string()
+            )
+        };
+        diagnostics.push(Diagnostic::error(
+            DiagnosticKind::EncryptionVariantMismatch,
+            "encryption.enabled",
+            message,
+        ));
+    }
+
+    diagnostics
+}
+
+/// RFC 2045 token characters.
+fn is_token(s: &str) -> bool {
+    const SPECIALS: &[u8] = b"!#$%&'*+-.^_`|~";
+    !s.is_empty()
+        && s.bytes()
+            .all(|b| b.is_ascii_alphanumeric() || SPECIALS.contains(&b))
+}
+
+/// Check `type/subtype` with optional `; name=value` parameters.
+fn is_valid_mime_type(content_type: &str) -> bool {
+    let mut parts = content_type.split(';');
+    let essence = parts.next().unwrap_or_default().trim();
+    let Some((kind, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+    if !is_token(kind) || !is_token(subtype) {
+        return false;
+    }
+
+    parts.all(|param| {
+        let Some((name, value)) = param.trim().split_once('=') else {
+            return false;
+        };
+        let quoted = value.len() >= 2 && value.starts_with('"') && value.ends_with('"');
+        is_token(name) && (is_token(value) || quoted)
+    })
+}
+
+/// Check an extension such as `csv`, `.tar.gz` or `c++`: at least one
+/// alphanumeric character and no `..` that could turn into a path component.
+fn is_valid_display_ext(display_ext: &str) -> bool {
+    let ext = display_ext.strip_prefix('.').unwrap_or(display_ext);
+    !display_ext.contains("..")
+        && ext.bytes().any(|b| b.is_ascii_alphanumeric())
+        && ext
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_' | b'+'))
+}
+
+/// Check a hostname, optionally with a leading `*.` wildcard label.
+fn is_valid_host_pattern(pattern: &str) -> bool {
+    let host = pattern.strip_prefix("*.").unwrap_or(pattern);
+    if host.is_empty() || host.len() > 253 {
+        return false;
+    }
+
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn valid_manifest() -> SyncManifest {
+        let toml_str = r#"
+[meta]
+created_at = "2026-01-01T00:00:00Z"
+created_by = "did:key:z6MkTest"
+hash_algo = "blake3"
+
+[sync]
+version = "1.2"
+content_type = "text/csv; charset=utf-8"
+display_ext = "csv"
+
+[policy]
+ttl = 3600
+timeout = 30
+
+[permissions]
+allow_hosts = ["api.example.com", "*.example.org"]
+"#;
+        SyncManifest::from_toml(toml_str.as_bytes()).unwrap()
+    }
+
+    fn kinds(manifest: &SyncManifest) -> Vec<DiagnosticKind> {
+        manifest.validate().into_iter().map(|d| d.kind).collect()
+    }
+
+    #[test]
+    fn test_valid_manifest_has_no_diagnostics() {
+        assert!(valid_manifest().validate().is_empty());
+    }
+
+    #[test]
+    fn test_display_ext() {
+        for ok in ["csv", ".csv", "tar.gz", ".tar.gz", "c++", "x_y-z"] {
+            assert!(is_valid_display_ext(ok), "{ok}");
+        }
+        for bad in [
+            "", ".", "..", "...", "..csv", "a..b", "-", "+", "a/b", "a b",
+        ] {
+            assert!(!is_valid_display_ext(bad), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_each_check_reports_its_kind() {
+        let mut manifest = valid_manifest();
+        manifest.sync.version = "9.9".to_string();
+        manifest.sync.content_type = "text csv".to_string();
+        manifest.sync.display_ext = "../csv".to_string();
+        manifest.meta.created_at = "yesterday".to_string();
+        manifest.policy.ttl = 0;
+        manifest.policy.timeout = 0;
+        manifest.permissions.allow_hosts = vec!["bad host".to_string(), "*.".to_string()];
+        manifest.encryption.enabled = true;
+
+        assert_eq!(
+            kinds(&manifest),
+            [
+                DiagnosticKind::UnknownVersion,
+                DiagnosticKind::InvalidContentType,
+                DiagnosticKind::InvalidDisplayExt,
+                DiagnosticKind::InvalidCreatedAt,
+                DiagnosticKind::ZeroTtl,
+                DiagnosticKind::ZeroTimeout,
+                DiagnosticKind::InvalidAllowHost,
+                DiagnosticKind::InvalidAllowHost,
+                DiagnosticKind::EncryptionVariantMismatch,
+            ]
+        );
+
+        let diagnostics = manifest.validate();
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert!(diagnostics[1].is_error());
+        assert_eq!(diagnostics[1].field, "sync.content_type");
+    }
+
+    #[test]
+    fn test_vault_requires_encryption() {
+        let mut manifest = valid_manifest();
+        manifest.sync.variant = SyncVariant::Vault;
+        assert_eq!(
+            kinds(&manifest),
+            [DiagnosticKind::EncryptionVariantMismatch]
+        );
+
+        manifest.encryption.enabled = true;
+        assert!(manifest.validate().is_empty());
+    }
+
+    #[test]
+    fn test_mime_type_syntax() {
+        assert!(is_valid_mime_type("application/json"));
+        assert!(is_valid_mime_type("application/vnd.api+json"));
+        assert!(is_valid_mime_type("text/plain; charset=\"utf-8\""));
+        assert!(!is_valid_mime_type("application"));
+        assert!(!is_valid_mime_type("application/"));
+        assert!(!is_valid_mime_type("text/plain; charset"));
+    }
+}
@@ -0,0 +1,354 @@
+//! Recipient-based vault encryption.
+//!
+//! Passphrase vaults ([`encrypt_data`](crate::encrypt_data)) need a shared
+//! secret. Recipient vaults instead wrap the payload key for each of several
+//! X25519 public keys, and any one matching identity can decrypt.
+//!
+//! Recipients can be given as age strings (`age1...`) or as did:key
+//! identities. X25519 did:keys (multicodec 0xec01) are used as-is; Ed25519
+//! did:keys (0xed01) are converted to their Montgomery form, so a teammate's
+//! signing identity doubles as an encryption recipient and [`SyncSigner`]
+//! keys can decrypt with [`identity_from_signer`].
+//!
+//! Besides the payload, a vault can encrypt any of [`ENCRYPTABLE_ENTRIES`]
+//! with the same credential. They are listed in `encryption.entries` and
+//! decrypted by [`SyncArchive::read_entry_with_credential`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use sync_format::vault;
+//!
+//! let recipients = vault::parse_recipients(&["did:key:z6Mk...", "age1..."])?;
+//! let encrypted = vault::encrypt_to_recipients(b"secret", &recipients)?;
+//!
+//! let identity = vault::identity_from_signer(&signer);
+//! let plaintext = vault::decrypt_with_identities(&encrypted, &[identity])?;
+//! ```
+//!
+//! Requires the `encryption` feature; did:key support also requires
+//! `signatures`.
+//!
+//! [`SyncSigner`]: crate::SyncSigner
+//! [`SyncArchive::read_entry_with_credential`]: crate::SyncArchive::read_entry_with_credential
+
+#[cfg(feature = "fs")]
+use crate::manifest::ManifestEncryption;
+use crate::{Error, Result};
+use bech32::{ToBase32, Variant};
+use std::io::{Read, Write};
+
+pub use age::x25519::{Identity, Recipient};
+
+/// Multicodec prefix for X25519 public keys.
+pub const X25519_MULTICODEC: [u8; 2] = [0xec, 0x01];
+
+/// Entries that a vault may encrypt in addition to the payload.
+pub const ENCRYPTABLE_ENTRIES: [&str; 3] = ["context.json", "sync.wasm", "sync.proof"];
+
+/// `encryption.algorithm` recorded for age-encrypted vaults.
+#[cfg(feature = "fs")]
+const ALGORITHM: &str = "age-v1";
+
+/// `encryption.meta.kdf` recorded for passphrase vaults.
+#[cfg(feature = "fs")]
+const PASSPHRASE_KDF: &str = "scrypt";
+
+/// Bech32 prefix of age X25519 recipients.
+const RECIPIENT_HRP: &str = "age";
+
+/// Bech32 prefix of age X25519 identities.
+const IDENTITY_HRP: &str = "age-secret-key-";
+
+/// A writer that encrypts everything written through it.
+///
+/// Call [`finish`](age::stream::StreamWriter::finish) to write the final
+/// chunk; dropping the writer truncates the ciphertext.
+pub type VaultWriter<W> = age::stream::StreamWriter<W>;
+
+/// A reader that decrypts a vault entry as it is read.
+///
+/// age encrypts in independently authenticated 64 KiB chunks, so over a
+/// seekable input such as [`EntryReader`](crate::EntryReader) this reader is
+/// seekable too and a seek only decrypts the chunk it lands in.
+pub type VaultReader<R> = age::stream::StreamReader<R>;
+
+/// Start encrypting to `output` for `recipients`.
+pub fn recipients_writer<W: Write>(output: W, recipients: &[Recipient]) -> Result<VaultWriter<W>> {
+    let recipients = recipients
+        .iter()
+        .cloned()
+        .map(|r| Box::new(r) as Box<dyn age::Recipient + Send>)
+        .collect();
+    age::Encryptor::with_recipients(recipients)
+        .ok_or_else(|| Error::EncryptError("at least one recipient is required".to_string()))?
+        .wrap_output(output)
+        .map_err(|e| Error::EncryptError(e.to_string()))
+}
+
+/// Start encrypting to `output` with a passphrase.
+pub fn passphrase_writer<W: Write>(
+    output: W,
+    password: &secrecy::SecretString,
+) -> Result<VaultWriter<W>> {
+    use secrecy::ExposeSecret;
+    age::Encryptor::with_user_passphrase(password.expose_secret().clone().into())
+        .wrap_output(output)
+        .map_err(|e| Error::EncryptError(e.to_string()))
+}
+
+/// Start decrypting recipient-encrypted `input` with the first matching
+/// identity.
+pub fn identities_reader<R: Read>(input: R, identities: &[Identity]) -> Result<VaultReader<R>> {
+    match age::Decryptor::new(input).map_err(|e| Error::DecryptError(e.to_string()))? {
+        age::Decryptor::Recipients(d) => d
+            .decrypt(identities.iter().map(|i| i as &dyn age::Identity))
+            .map_err(|e| Error::DecryptError(e.to_string())),
+        _ => Err(Error::InvalidFormat(
+            "Expected recipient-encrypted data".to_string(),
+        )),
+    }
+}
+
+/// Start decrypting passphrase-encrypted `input`.
+pub fn passphrase_reader<R: Read>(
+    input: R,
+    password: &secrecy::SecretString,
+) -> Result<VaultReader<R>> {
+    match age::Decryptor::new(input).map_err(|e| Error::DecryptError(e.to_string()))? {
+        age::Decryptor::Passphrase(d) => d
+            .decrypt(password, None)
+            .map_err(|e| Error::DecryptError(e.to_string())),
+        _ => Err(Error::InvalidFormat(
+            "Expected passphrase-encrypted data".to_string(),
+        )),
+    }
+}
+
+/// Encrypt data so that any of `recipients` can decrypt it.
+pub fn encrypt_to_recipients(data: &[u8], recipients: &[Recipient]) -> Result<Vec<u8>> {
+    let mut writer = recipients_writer(Vec::new(), recipients)?;
+    writer.write_all(data)?;
+    Ok(writer.finish()?)
+}
+
+/// Decrypt recipient-encrypted data with the first matching identity.
+pub fn decrypt_with_identities(encrypted: &[u8], identities: &[Identity]) -> Result<Vec<u8>> {
+    let mut decrypted = Vec::new();
+    identities_reader(encrypted, identities)?.read_to_end(&mut decrypted)?;
+    Ok(decrypted)
+}
+
+/// A credential that opens an existing vault.
+#[derive(Clone)]
+pub enum Credential {
+    /// The vault passphrase.
+    Passphrase(secrecy::SecretString),
+    /// Identities, any one of which is a recipient of the vault.
+    Identities(Vec<Identity>),
+}
+
+impl Credential {
+    /// Start decrypting a vault entry read from `input`.
+    pub fn decrypt_reader<R: Read>(&self, input: R) -> Result<VaultReader<R>> {
+        match self {
+            Credential::Passphrase(password) => passphrase_reader(input, password),
+            Credential::Identities(identities) => identities_reader(input, identities),
+        }
+    }
+
+    /// Decrypt a vault payload with this credential.
+    pub fn decrypt(&self, encrypted: Vec<u8>) -> Result<Vec<u8>> {
+        let mut decrypted = Vec::new();
+        self.decrypt_reader(&encrypted[..])?
+            .read_to_end(&mut decrypted)?;
+        Ok(decrypted)
+    }
+}
+
+/// How a vault payload is protected.
+#[derive(Debug, Clone)]
+pub enum Protection {
+    /// A shared passphrase.
+    Passphrase(secrecy::SecretString),
+    /// Recipients given as age strings or did:keys; see [`parse_recipient`].
+    Recipients(Vec<String>),
+}
+
+impl Protection {
+    /// Start encrypting a vault entry written to `output`.
+    pub fn encrypt_writer<W: Write>(&self, output: W) -> Result<VaultWriter<W>> {
+        match self {
+            Protection::Passphrase(password) => passphrase_writer(output, password),
+            Protection::Recipients(recipients) => {
+                recipients_writer(output, &parse_recipients(recipients)?)
+            }
+        }
+    }
+
+    /// Encrypt a vault payload with this protection.
+    pub fn encrypt(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut writer = self.encrypt_writer(Vec::new())?;
+        writer.write_all(data)?;
+        Ok(writer.finish()?)
+    }
+
+    /// Record this protection in the manifest `[encryption]` section.
+    ///
+    /// Key names and the hint are kept.
+    #[cfg(feature = "fs")]
+    pub(crate) fn apply_to(&self, encryption: &mut ManifestEncryption) {
+        encryption.enabled = true;
+        encryption.algorithm = Some(ALGORITHM.to_string());
+        let meta = encryption.meta.get_or_insert_with(Default::default);
+        match self {
+            Protection::Passphrase(_) => {
+                meta.recipients.clear();
+                meta.kdf = Some(PASSPHRASE_KDF.to_string());
+            }
+            Protection::Recipients(recipients) => {
+                meta.recipients = recipients.clone();
+                meta.kdf = None;
+            }
+        }
+    }
+}
+
+/// Build a recipient from a raw X25519 public key.
+pub fn recipient_from_bytes(public_key: &[u8; 32]) -> Recipient {
+    bech32::encode(RECIPIENT_HRP, public_key.to_base32(), Variant::Bech32)
+        .expect("HRP is valid")
+        .parse()
+        .expect("32-byte key is a valid recipient")
+}
+
+/// Build an identity from a raw X25519 secret key.
+pub fn identity_from_bytes(secret_key: &[u8; 32]) -> Identity {
+    bech32::encode(IDENTITY_HRP, secret_key.to_base32(), Variant::Bech32)
+        .expect("HRP is valid")
+        .to_uppercase()
+        .parse()
+        .expect("32-byte key is a valid identity")
+}
+
+/// Parse a recipient given as an age string or, with the `signatures`
+/// feature, a did:key.
+pub fn parse_recipient(recipient: &str) -> Result<Recipient> {
+    #[cfg(feature = "signatures")]
+    if recipient.starts_with("did:key:") {
+        return recipient_from_did(recipient);
+    }
+
+    recipient
+        .parse()
+        .map_err(|e| Error::EncryptError(format!("invalid recipient {:?}: {}", recipient, e)))
+}
+
+/// Parse several recipients with [`parse_recipient`].
+pub fn parse_recipients<S: AsRef<str>>(recipients: &[S]) -> Result<Vec<Recipient>> {
+    recipients
+        .iter()
+        .map(|r| parse_recipient(r.as_ref()))
+        .collect()
+}
+
+/// Convert a did:key identity to an age recipient.
+///
+/// Supports X25519 and Ed25519 did:keys.
+///
+/// Requires the `signatures` feature.
+#[cfg(feature = "signatures")]
+pub fn recipient_from_did(did: &str) -> Result<Recipient> {
+    let invalid =
+        |reason: &str| Error::EncryptError(format!("invalid recipient {:?}: {}", did, reason));
+
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| invalid("DID must start with 'did:key:z'"))?;
+    let decoded = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| invalid(&e.to_string()))?;
+    if decoded.len() != 34 {
+        return Err(invalid("unexpected key length"));
+    }
+    let (codec, key) = decoded.split_at(2);
+    let key: [u8; 32] = key.try_into().expect("length checked above");
+
+    if codec == X25519_MULTICODEC {
+        Ok(recipient_from_bytes(&key))
+    } else if codec == crate::keystore::ED25519_MULTICODEC {
+        let verifying_key =
+            ed25519_dalek::VerifyingKey::from_bytes(&key).map_err(|e| invalid(&e.to_string()))?;
+        Ok(recipient_from_bytes(
+            &verifying_key.to_montgomery().to_bytes(),
+        ))
+    } else {
+        Err(invalid(&format!(
+            "unsupported multicodec prefix 0x{:02x}{:02x}",
+            codec[0], codec[1]
+        )))
+    }
+}
+
+/// Derive the age identity matching a signer's Ed25519 did:key.
+///
+/// Data encrypted to [`recipient_from_did`] of the signer's DID decrypts
+/// with this identity.
+///
+/// Requires the `signatures` feature.
+#[cfg(feature = "signatures")]
+pub fn identity_from_signer(signer: &crate::SyncSigner) -> Identity {
+    let signing_key = ed25519_dalek::SigningKey::from_bytes(&signer.to_bytes());
+    identity_from_bytes(&signing_key.to_scalar_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_recipient_can_decrypt() {
+        let alice = Identity::generate();
+        let bob = Identity::generate();
+        let recipients = [alice.to_public(), bob.to_public()];
+
+        let encrypted = encrypt_to_recipients(b"team secret", &recipients).unwrap();
+        assert_eq!(
+            decrypt_with_identities(&encrypted, &[bob]).unwrap(),
+            b"team secret"
+        );
+        assert!(decrypt_with_identities(&encrypted, &[Identity::generate()]).is_err());
+        assert!(encrypt_to_recipients(b"data", &[]).is_err());
+    }
+
+    #[test]
+    fn test_raw_key_round_trip() {
+        let recipient = recipient_from_bytes(&[9u8; 32]);
+        assert_eq!(parse_recipient(&recipient.to_string()).unwrap(), recipient);
+
+        let identity = identity_from_bytes(&[5u8; 32]);
+        let encrypted = encrypt_to_recipients(b"raw", &[identity.to_public()]).unwrap();
+        let same = identity_from_bytes(&[5u8; 32]);
+        assert_eq!(
+            decrypt_with_identities(&encrypted, &[same]).unwrap(),
+            b"raw"
+        );
+    }
+
+    #[cfg(feature = "signatures")]
+    #[test]
+    fn test_signer_did_is_a_recipient() {
+        let signer = crate::SyncSigner::from_bytes(&[7u8; 32]);
+        let other = crate::SyncSigner::from_bytes(&[8u8; 32]);
+        let recipients = parse_recipients(&[signer.did(), other.did()]).unwrap();
+        assert_eq!(recipients[0], identity_from_signer(&signer).to_public());
+
+        let encrypted = encrypt_to_recipients(b"shared", &recipients).unwrap();
+        let identity = identity_from_signer(&other);
+        assert_eq!(
+            decrypt_with_identities(&encrypted, &[identity]).unwrap(),
+            b"shared"
+        );
+        assert!(parse_recipient("did:key:zInvalid").is_err());
+    }
+}
@@ -2,13 +2,31 @@
 //!
 //! This module provides Ed25519 signature verification using BLAKE3 hashes.
 //! See docs/SIGNATURE_SPEC.md for the full specification.
+//!
+//! Three signatures are supported:
+//! - a detached [`SyncSignature`] over the bytes of the whole file
+//! - the manifest `[signature]` section over the manifest and payload, plus
+//!   `[[cosignatures]]` checked against a [`SignaturePolicy`]
+//! - an embedded [`ArchiveSignature`] in the `sync.sig` entry, over every
+//!   other entry (see [`compute_archive_hash`])
+//!
+//! [`verify_provenance`] additionally walks the manifest's `[[provenance]]`
+//! log of payload updates.
+//!
+//! The path-based functions need the `fs` feature. Each has a `_from_reader`
+//! counterpart for archives held in memory or behind any `Read + Seek` source.
 
-use crate::manifest::SyncManifest;
+use crate::manifest::{ManifestSignature, SyncManifest};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
 use serde::{Deserialize, Serialize};
-use std::io::Read;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+#[cfg(feature = "fs")]
 use std::path::Path;
 
+/// Name of the entry holding the embedded [`ArchiveSignature`].
+pub const ARCHIVE_SIGNATURE_ENTRY: &str = "sync.sig";
+
 /// Signature format for Ato AppSync
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncSignature {
@@ -24,6 +42,42 @@ pub struct SyncSignature {
     pub signed_at: u64,
 }
 
+/// Embedded whole-archive signature, stored as JSON in `sync.sig`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSignature {
+    /// Signature algorithm (always "Ed25519")
+    pub algorithm: String,
+    /// Signer's public key in did:key format
+    pub public_key: String,
+    /// Hash of every other entry (format: "blake3:<64-char-hex>")
+    pub archive_hash: String,
+    /// Unix timestamp (seconds since epoch)
+    pub signed_at: u64,
+    /// Base64-encoded Ed25519 signature
+    pub signature: String,
+}
+
+/// Status of the embedded `sync.sig` signature of an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveSignatureStatus {
+    /// The archive has no `sync.sig` entry.
+    Unsigned,
+    /// The signature matches the archive contents.
+    Valid {
+        /// Signer's public key in did:key format
+        public_key: String,
+    },
+    /// The signature is malformed or does not match the archive contents.
+    Invalid(String),
+}
+
+impl ArchiveSignatureStatus {
+    /// Check if the archive carries a valid embedded signature.
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ArchiveSignatureStatus::Valid { .. })
+    }
+}
+
 /// Verification result with details
 #[derive(Debug, Clone)]
 pub struct VerificationResult {
@@ -50,6 +104,154 @@ pub struct ManifestSignatureResult {
     pub error: Option<String>,
 }
 
+/// Which manifest signers must sign for [`verify_manifest_signatures`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignaturePolicy {
+    /// Accepted signer identities in did:key format.
+    ///
+    /// Duplicates are counted once when verifying.
+    pub signers: Vec<String>,
+    /// How many of `signers` must have a valid signature.
+    pub threshold: usize,
+}
+
+impl SignaturePolicy {
+    /// Require `threshold` valid signatures from `signers`, e.g. 2 of 3.
+    ///
+    /// Duplicate signers are counted once.
+    pub fn threshold<I, S>(threshold: usize, signers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut unique: Vec<String> = Vec::new();
+        for signer in signers {
+            let signer = signer.into();
+            if !unique.contains(&signer) {
+                unique.push(signer);
+            }
+        }
+        Self {
+            signers: unique,
+            threshold,
+        }
+    }
+
+    /// Require a valid signature from every one of `signers`.
+    pub fn all<I, S>(signers: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut policy = Self::threshold(0, signers);
+        policy.threshold = policy.signers.len();
+        policy
+    }
+}
+
+/// Outcome for one signer of a [`SignaturePolicy`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerState {
+    /// The signer's signature is valid.
+    Valid,
+    /// The signer signed, but the signature does not verify.
+    Invalid(String),
+    /// The manifest has no signature from this signer.
+    Missing,
+}
+
+/// Status of one policy signer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerStatus {
+    /// Signer identity in did:key format.
+    pub signer: String,
+    /// Whether the signer's signature verified.
+    pub status: SignerState,
+}
+
+impl std::fmt::Display for SignerStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.status {
+            SignerState::Valid => write!(f, "{}: valid", self.signer),
+            SignerState::Invalid(reason) => write!(f, "{}: invalid ({})", self.signer, reason),
+            SignerState::Missing => write!(f, "{}: missing", self.signer),
+        }
+    }
+}
+
+/// Multi-signature verification result
+#[derive(Debug, Clone)]
+pub struct MultiSignatureResult {
+    /// Whether the policy threshold was met
+    pub valid: bool,
+    /// Computed manifest hash
+    pub manifest_hash: String,
+    /// Computed payload hash (if any signature covers the payload)
+    pub payload_hash: Option<String>,
+    /// Number of valid signatures required
+    pub threshold: usize,
+    /// Status of each policy signer, in policy order
+    pub signers: Vec<SignerStatus>,
+    /// Error message listing every signer's status (if invalid)
+    pub error: Option<String>,
+}
+
+/// Status of one `[[provenance]]` record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProvenanceState {
+    /// The record links to its predecessor and its signature verified.
+    Signed,
+    /// The record links to its predecessor but carries no signature.
+    Unsigned,
+    /// The record breaks the chain or its signature is invalid.
+    Broken(String),
+}
+
+/// Status of one provenance record, in log order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProvenanceStep {
+    /// Who made the update.
+    pub actor: String,
+    /// RFC3339 timestamp of the update.
+    pub timestamp: String,
+    /// Whether the record checked out.
+    pub state: ProvenanceState,
+}
+
+/// Provenance chain verification result
+#[derive(Debug, Clone)]
+pub struct ProvenanceResult {
+    /// Whether every record links up and every signature verified
+    pub valid: bool,
+    /// Hash of the payload the archive was created with, per the checkpoint
+    /// or the first record
+    pub origin_payload_hash: String,
+    /// Computed hash of the current payload
+    pub payload_hash: String,
+    /// Whether a valid `[signature]` by `meta.created_by` covers the origin
+    /// payload, tying the chain to the archive's signed creation
+    pub anchored: bool,
+    /// Status of the `[provenance_checkpoint]` standing in for dropped
+    /// records, if the log was compacted; its actor is the signer vouching
+    /// for them
+    pub checkpoint: Option<ProvenanceStep>,
+    /// Status of each record, oldest first
+    pub steps: Vec<ProvenanceStep>,
+    /// Description of the first break in the chain (if invalid)
+    pub error: Option<String>,
+}
+
+impl ProvenanceResult {
+    /// Check if the chain is valid and every record is signed.
+    pub fn is_fully_signed(&self) -> bool {
+        self.valid
+            && self
+                .steps
+                .iter()
+                .all(|step| step.state == ProvenanceState::Signed)
+    }
+}
+
 /// Errors that can occur during verification
 #[derive(Debug, thiserror::Error)]
 pub enum VerificationError {
@@ -67,6 +269,8 @@ pub enum VerificationError {
     HashMismatch { expected: String, actual: String },
     #[error("Unsupported algorithm: {0}")]
     UnsupportedAlgorithm(String),
+    #[error("Invalid signature policy: {0}")]
+    InvalidPolicy(String),
 }
 
 /// Verify a `.sync` file against a signature
@@ -93,6 +297,7 @@ pub enum VerificationError {
 ///     println!("Signature verified! Author: {}", signature.public_key);
 /// }
 /// ```
+#[cfg(feature = "fs")]
 pub fn verify_sync_file(
     path: &Path,
     signature: &SyncSignature,
@@ -206,24 +411,22 @@ pub fn verify_sync_file(
 }
 
 /// Verify a `.sync` file using the manifest `[signature]` section
+///
+/// Co-signatures are not checked; see [`verify_manifest_signatures`].
+#[cfg(feature = "fs")]
 pub fn verify_manifest_signature(
     path: &Path,
 ) -> Result<ManifestSignatureResult, VerificationError> {
-    let file = std::fs::File::open(path)?;
-    let mut archive = zip::ZipArchive::new(file)
-        .map_err(|e| VerificationError::InvalidFormat(format!("Invalid sync archive: {e}")))?;
-
-    let mut manifest_text = String::new();
-    archive
-        .by_name("manifest.toml")
-        .map_err(|e| VerificationError::InvalidFormat(format!("Missing manifest.toml: {e}")))?
-        .read_to_string(&mut manifest_text)
-        .map_err(|e| {
-            VerificationError::InvalidFormat(format!("Failed to read manifest.toml: {e}"))
-        })?;
+    verify_manifest_signature_from_reader(std::fs::File::open(path)?)
+}
 
-    let mut manifest: SyncManifest = SyncManifest::from_toml(manifest_text.as_bytes())
-        .map_err(|e| VerificationError::InvalidFormat(format!("Invalid manifest.toml: {e}")))?;
+/// Verify the manifest `[signature]` section of an archive read from `reader`.
+///
+/// See [`verify_manifest_signature`].
+pub fn verify_manifest_signature_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<ManifestSignatureResult, VerificationError> {
+    let (mut archive, mut manifest) = read_manifest(reader)?;
 
     let signature = match manifest.signature.clone() {
         Some(sig) => sig,
@@ -243,17 +446,7 @@ pub fn verify_manifest_signature(
 
     // Compute manifest hash (exclude signature)
     manifest.signature = None;
-    let manifest_json = serde_json::to_value(&manifest).map_err(|e| {
-        VerificationError::InvalidFormat(format!("Manifest JSON encode failed: {e}"))
-    })?;
-    let canonical_manifest = canonicalize_json(&manifest_json);
-    let manifest_bytes = serde_json::to_vec(&canonical_manifest).map_err(|e| {
-        VerificationError::InvalidFormat(format!("Manifest JSON serialize failed: {e}"))
-    })?;
-    let manifest_hash = format!(
-        "blake3:{}",
-        hex::encode(blake3::hash(&manifest_bytes).as_bytes())
-    );
+    let manifest_hash = compute_manifest_hash(&manifest)?;
 
     if manifest_hash != signature.manifest_hash {
         return Ok(ManifestSignatureResult {
@@ -267,18 +460,7 @@ pub fn verify_manifest_signature(
     // Compute payload hash if provided
     let mut payload_hash = None;
     if signature.payload_hash.is_some() {
-        let mut payload_bytes = Vec::new();
-        archive
-            .by_name("payload")
-            .map_err(|e| VerificationError::InvalidFormat(format!("Missing payload: {e}")))?
-            .read_to_end(&mut payload_bytes)
-            .map_err(|e| {
-                VerificationError::InvalidFormat(format!("Failed to read payload: {e}"))
-            })?;
-        let computed = format!(
-            "blake3:{}",
-            hex::encode(blake3::hash(&payload_bytes).as_bytes())
-        );
+        let computed = hash_archive_payload(&mut archive)?;
         payload_hash = Some(computed.clone());
         if Some(computed) != signature.payload_hash {
             return Ok(ManifestSignatureResult {
@@ -290,18 +472,19 @@ pub fn verify_manifest_signature(
         }
     }
 
+    if signature.module_hash.is_some()
+        && hash_archive_module(&mut archive)? != signature.module_hash
+    {
+        return Ok(ManifestSignatureResult {
+            valid: false,
+            manifest_hash,
+            payload_hash,
+            error: Some("module hash mismatch".to_string()),
+        });
+    }
+
     // Build signing payload
-    let signer = manifest.meta.created_by.clone();
-    let signing_payload = serde_json::json!({
-        "manifest_hash": signature.manifest_hash,
-        "payload_hash": signature.payload_hash,
-        "timestamp": signature.timestamp,
-        "signer": signer,
-    });
-    let canonical_payload = canonicalize_json(&signing_payload);
-    let payload_bytes = serde_json::to_vec(&canonical_payload).map_err(|e| {
-        VerificationError::InvalidFormat(format!("Signing payload serialize failed: {e}"))
-    })?;
+    let payload_bytes = build_signing_payload(&signature, &manifest.meta.created_by)?;
 
     let public_key_bytes = extract_public_key(&manifest.meta.created_by)
         .map_err(|e| VerificationError::InvalidPublicKey(format!("{e}")))?;
@@ -343,11 +526,520 @@ pub fn verify_manifest_signature(
     }
 }
 
+/// Verify the embedded `sync.sig` signature of a `.sync` file.
+///
+/// Returns [`ArchiveSignatureStatus::Unsigned`] if the archive has no
+/// `sync.sig` entry. Returns `Err` only if the file is not a readable archive.
+#[cfg(feature = "fs")]
+pub fn verify_archive_signature(path: &Path) -> Result<ArchiveSignatureStatus, VerificationError> {
+    verify_archive_signature_from_reader(std::fs::File::open(path)?)
+}
+
+/// Verify the embedded `sync.sig` signature of an archive read from `reader`.
+///
+/// See [`verify_archive_signature`].
+pub fn verify_archive_signature_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<ArchiveSignatureStatus, VerificationError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| VerificationError::InvalidFormat(format!("Invalid sync archive: {e}")))?;
+    check_archive_signature(&mut archive)
+}
+
+/// Check the `sync.sig` entry of an open zip archive.
+pub(crate) fn check_archive_signature<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<ArchiveSignatureStatus, VerificationError> {
+    let mut signature_json = Vec::new();
+    match archive.by_name(ARCHIVE_SIGNATURE_ENTRY) {
+        Ok(mut entry) => entry.read_to_end(&mut signature_json)?,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(ArchiveSignatureStatus::Unsigned),
+        Err(e) => {
+            return Err(VerificationError::InvalidFormat(format!(
+                "Failed to read {ARCHIVE_SIGNATURE_ENTRY}: {e}"
+            )))
+        }
+    };
+
+    let signature: ArchiveSignature = match serde_json::from_slice(&signature_json) {
+        Ok(signature) => signature,
+        Err(e) => {
+            return Ok(ArchiveSignatureStatus::Invalid(format!(
+                "Invalid {ARCHIVE_SIGNATURE_ENTRY}: {e}"
+            )))
+        }
+    };
+    if signature.algorithm != "Ed25519" {
+        return Ok(ArchiveSignatureStatus::Invalid(format!(
+            "Unsupported algorithm: {}",
+            signature.algorithm
+        )));
+    }
+
+    let archive_hash = compute_archive_hash(archive)?;
+    if archive_hash != signature.archive_hash {
+        return Ok(ArchiveSignatureStatus::Invalid(format!(
+            "Archive hash mismatch: expected {}, got {}",
+            signature.archive_hash, archive_hash
+        )));
+    }
+
+    let signing_payload = build_archive_signing_payload(&signature)?;
+    if let Err(e) = verify_ed25519(
+        &signature.public_key,
+        &signing_payload,
+        &signature.signature,
+    ) {
+        return Ok(ArchiveSignatureStatus::Invalid(e.to_string()));
+    }
+
+    Ok(ArchiveSignatureStatus::Valid {
+        public_key: signature.public_key,
+    })
+}
+
+/// Compute the hash covered by the embedded `sync.sig` signature.
+///
+/// Every file entry except `sync.sig` itself is hashed in byte order of its
+/// name. For each entry the hash takes the name length and uncompressed size
+/// as little-endian u64s, followed by the name and the contents:
+///
+/// ```text
+/// len(name) || name || size || contents
+/// ```
+///
+/// The result covers entry contents rather than file layout, so it survives
+/// an in-place payload append that is later compacted.
+pub fn compute_archive_hash<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<String, VerificationError> {
+    let mut names: Vec<String> = archive
+        .file_names()
+        .filter(|name| *name != ARCHIVE_SIGNATURE_ENTRY && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    names.sort();
+
+    let mut hasher = blake3::Hasher::new();
+    for name in names {
+        let mut entry = archive
+            .by_name(&name)
+            .map_err(|e| VerificationError::InvalidFormat(format!("Missing {name}: {e}")))?;
+        hasher.update(&(name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+        hasher.update(&entry.size().to_le_bytes());
+        std::io::copy(&mut entry, &mut hasher)
+            .map_err(|e| VerificationError::InvalidFormat(format!("Failed to read {name}: {e}")))?;
+    }
+    Ok(format!(
+        "blake3:{}",
+        hex::encode(hasher.finalize().as_bytes())
+    ))
+}
+
+/// Build the canonical bytes that are signed for an embedded signature.
+pub(crate) fn build_archive_signing_payload(
+    signature: &ArchiveSignature,
+) -> Result<Vec<u8>, VerificationError> {
+    let signing_payload = serde_json::json!({
+        "algorithm": signature.algorithm,
+        "archive_hash": signature.archive_hash,
+        "public_key": signature.public_key,
+        "signed_at": signature.signed_at,
+    });
+    let canonical_payload = canonicalize_json(&signing_payload);
+    serde_json::to_vec(&canonical_payload).map_err(|e| {
+        VerificationError::InvalidFormat(format!("Signing payload serialize failed: {e}"))
+    })
+}
+
+/// Verify a base64 Ed25519 signature over `message` by a did:key signer.
+fn verify_ed25519(did: &str, message: &[u8], signature: &str) -> Result<(), VerificationError> {
+    let public_key_bytes = extract_public_key(did)?;
+    let verifying_key = VerifyingKey::from_bytes(&public_key_bytes)
+        .map_err(|e| VerificationError::InvalidPublicKey(format!("{e}")))?;
+    let signature_bytes =
+        base64::Engine::decode(&base64::engine::general_purpose::STANDARD, signature).map_err(
+            |e| VerificationError::InvalidSignature(format!("Invalid base64 signature: {e}")),
+        )?;
+    let sig_array: [u8; 64] = signature_bytes
+        .try_into()
+        .map_err(|_| VerificationError::InvalidSignature("Invalid signature length".to_string()))?;
+
+    verifying_key
+        .verify(message, &Signature::from_bytes(&sig_array))
+        .map_err(|e| {
+            VerificationError::InvalidSignature(format!("Signature verification failed: {e}"))
+        })
+}
+
+/// Verify the `[signature]` section and co-signatures against a policy.
+///
+/// The signer of `[signature]` is `meta.created_by`; each co-signature names
+/// its own signer. Every signature must cover the current manifest hash and,
+/// if it carries one, the current payload hash. Signatures by keys outside
+/// the policy are ignored.
+///
+/// Returns `Err` if the archive cannot be read or the policy is unsatisfiable.
+/// A failed policy is reported with `valid: false` and the status of every
+/// policy signer.
+#[cfg(feature = "fs")]
+pub fn verify_manifest_signatures(
+    path: &Path,
+    policy: &SignaturePolicy,
+) -> Result<MultiSignatureResult, VerificationError> {
+    verify_manifest_signatures_from_reader(std::fs::File::open(path)?, policy)
+}
+
+/// Verify manifest signatures against a policy for an archive read from
+/// `reader`.
+///
+/// See [`verify_manifest_signatures`].
+pub fn verify_manifest_signatures_from_reader<R: Read + Seek>(
+    reader: R,
+    policy: &SignaturePolicy,
+) -> Result<MultiSignatureResult, VerificationError> {
+    // The fields are public, so a policy may list a signer twice; count
+    // each one once so a single key cannot meet a threshold on its own.
+    let mut policy_signers: Vec<&String> = Vec::new();
+    for signer in &policy.signers {
+        if !policy_signers.contains(&signer) {
+            policy_signers.push(signer);
+        }
+    }
+    if policy.threshold == 0 || policy.threshold > policy_signers.len() {
+        return Err(VerificationError::InvalidPolicy(format!(
+            "threshold {} is not between 1 and {} signers",
+            policy.threshold,
+            policy_signers.len()
+        )));
+    }
+
+    let (mut archive, manifest) = read_manifest(reader)?;
+    let manifest_hash = compute_manifest_hash(&manifest)?;
+
+    let mut signatures = Vec::new();
+    if let Some(signature) = &manifest.signature {
+        signatures.push((manifest.meta.created_by.as_str(), signature));
+    }
+    for signature in &manifest.cosignatures {
+        if let Some(signer) = &signature.signer {
+            signatures.push((signer.as_str(), signature));
+        }
+    }
+
+    let payload_hash = if signatures.iter().any(|(_, sig)| sig.payload_hash.is_some()) {
+        Some(hash_archive_payload(&mut archive)?)
+    } else {
+        None
+    };
+    let module_hash = hash_archive_module(&mut archive)?;
+
+    let signers: Vec<SignerStatus> = policy_signers
+        .into_iter()
+        .map(|signer| {
+            let status = match signatures.iter().find(|(did, _)| did == signer) {
+                None => SignerState::Missing,
+                Some((_, signature)) => match check_manifest_signature(
+                    signature,
+                    signer,
+                    &manifest_hash,
+                    payload_hash.as_deref(),
+                    module_hash.as_deref(),
+                ) {
+                    Ok(()) => SignerState::Valid,
+                    Err(e) => SignerState::Invalid(e),
+                },
+            };
+            SignerStatus {
+                signer: signer.clone(),
+                status,
+            }
+        })
+        .collect();
+
+    let valid_count = signers
+        .iter()
+        .filter(|signer| signer.status == SignerState::Valid)
+        .count();
+    let valid = valid_count >= policy.threshold;
+    let error = (!valid).then(|| {
+        let statuses: Vec<String> = signers.iter().map(ToString::to_string).collect();
+        format!(
+            "{} of {} required signatures valid: {}",
+            valid_count,
+            policy.threshold,
+            statuses.join("; ")
+        )
+    });
+
+    Ok(MultiSignatureResult {
+        valid,
+        manifest_hash,
+        payload_hash,
+        threshold: policy.threshold,
+        signers,
+        error,
+    })
+}
+
+/// Walk the `[[provenance]]` log of a `.sync` file.
+///
+/// Each record must name the hash of the record before it, start from the
+/// payload hash its predecessor ended with, and not predate it. Signed
+/// records must carry a valid signature by their did:key actor. The last
+/// record must end with the current payload. An archive without provenance
+/// is valid, with its current payload as the origin.
+///
+/// A compacted log starts from its `[provenance_checkpoint]` instead, which
+/// must carry a valid signature by its signer and supplies the origin
+/// payload. Trust in the dropped records rests on that signer.
+///
+/// Returns `Err` only if the archive cannot be read; a broken chain is
+/// reported with `valid: false`.
+#[cfg(feature = "fs")]
+pub fn verify_provenance(path: &Path) -> Result<ProvenanceResult, VerificationError> {
+    verify_provenance_from_reader(std::fs::File::open(path)?)
+}
+
+/// Walk the `[[provenance]]` log of an archive read from `reader`.
+///
+/// See [`verify_provenance`].
+pub fn verify_provenance_from_reader<R: Read + Seek>(
+    reader: R,
+) -> Result<ProvenanceResult, VerificationError> {
+    let (mut archive, manifest) = read_manifest(reader)?;
+    let payload_hash = hash_archive_payload(&mut archive)?;
+
+    let checkpoint = manifest.provenance_checkpoint.as_ref();
+    let origin_payload_hash = match (checkpoint, manifest.provenance.first()) {
+        (Some(checkpoint), _) => checkpoint.origin_payload_hash.clone(),
+        (None, Some(first)) => first.previous_payload_hash.clone(),
+        (None, None) => payload_hash.clone(),
+    };
+
+    let checkpoint_step = checkpoint.map(|checkpoint| ProvenanceStep {
+        actor: checkpoint.signer.clone(),
+        timestamp: checkpoint.timestamp.clone(),
+        state: match check_provenance_checkpoint(checkpoint) {
+            Ok(()) => ProvenanceState::Signed,
+            Err(reason) => ProvenanceState::Broken(reason),
+        },
+    });
+
+    let mut steps = Vec::with_capacity(manifest.provenance.len());
+    let mut previous = checkpoint.map(|checkpoint| ProvenanceLink {
+        record_hash: checkpoint.last_record.clone(),
+        payload_hash: &checkpoint.payload_hash,
+        timestamp: &checkpoint.timestamp,
+    });
+    for record in &manifest.provenance {
+        let state = match check_provenance_record(record, previous.as_ref()) {
+            Err(reason) => ProvenanceState::Broken(reason),
+            Ok(()) if record.signature.is_some() => ProvenanceState::Signed,
+            Ok(()) => ProvenanceState::Unsigned,
+        };
+        steps.push(ProvenanceStep {
+            actor: record.actor.clone(),
+            timestamp: record.timestamp.clone(),
+            state,
+        });
+        previous = Some(ProvenanceLink {
+            record_hash: record.record_hash(),
+            payload_hash: &record.payload_hash,
+            timestamp: &record.timestamp,
+        });
+    }
+
+    let mut error = match &checkpoint_step {
+        Some(ProvenanceStep {
+            state: ProvenanceState::Broken(reason),
+            ..
+        }) => Some(format!("checkpoint: {reason}")),
+        _ => None,
+    };
+    if error.is_none() {
+        error = steps
+            .iter()
+            .enumerate()
+            .find_map(|(i, step)| match &step.state {
+                ProvenanceState::Broken(reason) => Some(format!("record {}: {}", i + 1, reason)),
+                _ => None,
+            });
+    }
+    if error.is_none() {
+        if let Some(last) = previous {
+            if last.payload_hash != payload_hash {
+                error = Some(format!(
+                    "current payload {} does not match the last record ({})",
+                    payload_hash, last.payload_hash
+                ));
+            }
+        }
+    }
+
+    let anchored = match &manifest.signature {
+        Some(signature) if signature.payload_hash.is_some() => check_manifest_signature(
+            signature,
+            &manifest.meta.created_by,
+            &compute_manifest_hash(&manifest)?,
+            Some(&origin_payload_hash),
+            hash_archive_module(&mut archive)?.as_deref(),
+        )
+        .is_ok(),
+        _ => false,
+    };
+
+    Ok(ProvenanceResult {
+        valid: error.is_none(),
+        origin_payload_hash,
+        payload_hash,
+        anchored,
+        checkpoint: checkpoint_step,
+        steps,
+        error,
+    })
+}
+
+/// The end of the provenance chain so far: a record or the checkpoint.
+struct ProvenanceLink<'a> {
+    record_hash: String,
+    payload_hash: &'a str,
+    timestamp: &'a str,
+}
+
+/// Check the timestamp and signature of a provenance checkpoint.
+fn check_provenance_checkpoint(checkpoint: &crate::ProvenanceCheckpoint) -> Result<(), String> {
+    chrono::DateTime::parse_from_rfc3339(&checkpoint.timestamp)
+        .map_err(|e| format!("invalid timestamp: {e}"))?;
+    verify_ed25519(
+        &checkpoint.signer,
+        &checkpoint.signing_payload(),
+        &checkpoint.signature,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Check one provenance record against its predecessor.
+fn check_provenance_record(
+    record: &crate::ProvenanceRecord,
+    previous: Option<&ProvenanceLink<'_>>,
+) -> Result<(), String> {
+    let timestamp = chrono::DateTime::parse_from_rfc3339(&record.timestamp)
+        .map_err(|e| format!("invalid timestamp: {e}"))?;
+
+    if let Some(previous) = previous {
+        if record.previous_record.as_deref() != Some(previous.record_hash.as_str()) {
+            return Err("does not link to the previous record".to_string());
+        }
+        if record.previous_payload_hash != previous.payload_hash {
+            return Err("previous payload hash does not match the previous record".to_string());
+        }
+        if chrono::DateTime::parse_from_rfc3339(previous.timestamp)
+            .is_ok_and(|previous| timestamp < previous)
+        {
+            return Err("timestamp predates the previous record".to_string());
+        }
+    } else if record.previous_record.is_some() {
+        return Err("first record links to a missing predecessor".to_string());
+    }
+
+    if let Some(signature) = &record.signature {
+        verify_ed25519(&record.actor, &record.signing_payload(), signature)
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Check one manifest signature against the current hashes.
+fn check_manifest_signature(
+    signature: &ManifestSignature,
+    signer: &str,
+    manifest_hash: &str,
+    payload_hash: Option<&str>,
+    module_hash: Option<&str>,
+) -> Result<(), String> {
+    if signature.algo != "Ed25519" {
+        return Err(format!("unsupported algorithm: {}", signature.algo));
+    }
+    if signature.manifest_hash != manifest_hash {
+        return Err("manifest hash mismatch".to_string());
+    }
+    if signature.payload_hash.is_some() && signature.payload_hash.as_deref() != payload_hash {
+        return Err("payload hash mismatch".to_string());
+    }
+    if signature.module_hash.is_some() && signature.module_hash.as_deref() != module_hash {
+        return Err("module hash mismatch".to_string());
+    }
+
+    let signing_payload = build_signing_payload(signature, signer).map_err(|e| e.to_string())?;
+    verify_ed25519(signer, &signing_payload, &signature.value).map_err(|e| e.to_string())
+}
+
+/// Read a raw archive from `reader` and parse its manifest.
+pub(crate) fn read_manifest<R: Read + Seek>(
+    reader: R,
+) -> Result<(zip::ZipArchive<R>, SyncManifest), VerificationError> {
+    let mut archive = zip::ZipArchive::new(reader)
+        .map_err(|e| VerificationError::InvalidFormat(format!("Invalid sync archive: {e}")))?;
+
+    let mut manifest_text = String::new();
+    archive
+        .by_name("manifest.toml")
+        .map_err(|e| VerificationError::InvalidFormat(format!("Missing manifest.toml: {e}")))?
+        .read_to_string(&mut manifest_text)
+        .map_err(|e| {
+            VerificationError::InvalidFormat(format!("Failed to read manifest.toml: {e}"))
+        })?;
+
+    let manifest = SyncManifest::from_toml(manifest_text.as_bytes())
+        .map_err(|e| VerificationError::InvalidFormat(format!("Invalid manifest.toml: {e}")))?;
+    Ok((archive, manifest))
+}
+
+/// Hash the `sync.wasm` entry of a raw archive, if it has one.
+pub(crate) fn hash_archive_module<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<Option<String>, VerificationError> {
+    let mut module = match archive.by_name("sync.wasm") {
+        Ok(module) => module,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => {
+            return Err(VerificationError::InvalidFormat(format!(
+                "Failed to read sync.wasm: {e}"
+            )))
+        }
+    };
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut module, &mut hasher)?;
+    Ok(Some(format!(
+        "blake3:{}",
+        hex::encode(hasher.finalize().as_bytes())
+    )))
+}
+
+/// Hash the payload entry or payload directory of a raw archive.
+fn hash_archive_payload<R: Read + Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<String, VerificationError> {
+    if archive.index_for_name("payload").is_none() {
+        return hash_payload_dir(archive);
+    }
+
+    let mut payload_bytes = Vec::new();
+    archive
+        .by_name("payload")
+        .map_err(|e| VerificationError::InvalidFormat(format!("Missing payload: {e}")))?
+        .read_to_end(&mut payload_bytes)
+        .map_err(|e| VerificationError::InvalidFormat(format!("Failed to read payload: {e}")))?;
+    Ok(compute_payload_hash(&payload_bytes))
+}
+
 /// Extract public key bytes from did:key format
 ///
 /// Format: did:key:z6Mk<multibase-base58btc-ed25519-public-key>
 /// Multicodec prefix: 0xed01 (Ed25519 public key)
-fn extract_public_key(did: &str) -> Result<[u8; 32], VerificationError> {
+pub(crate) fn extract_public_key(did: &str) -> Result<[u8; 32], VerificationError> {
     // Check prefix
     if !did.starts_with("did:key:z") {
         return Err(VerificationError::InvalidDid(
@@ -393,6 +1085,103 @@ fn extract_public_key(did: &str) -> Result<[u8; 32], VerificationError> {
     Ok(result)
 }
 
+/// Compute the canonical manifest hash used by the `[signature]` section.
+///
+/// The manifest is serialized to JSON with sorted keys and hashed with BLAKE3.
+/// The `[signature]` section, co-signatures, the payload `[[history]]` with
+/// its `last_version` counter and the `[[provenance]]` log with its
+/// checkpoint are not part of the hash, so payload updates
+/// leave a manifest signature intact apart from its payload hash.
+pub(crate) fn compute_manifest_hash(manifest: &SyncManifest) -> Result<String, VerificationError> {
+    let mut manifest_json = serde_json::to_value(manifest).map_err(|e| {
+        VerificationError::InvalidFormat(format!("Manifest JSON encode failed: {e}"))
+    })?;
+    if let Some(fields) = manifest_json.as_object_mut() {
+        fields.remove("signature");
+        fields.remove("cosignatures");
+        fields.remove("last_version");
+        fields.remove("history");
+        fields.remove("provenance");
+        fields.remove("provenance_checkpoint");
+    }
+    let canonical_manifest = canonicalize_json(&manifest_json);
+    let manifest_bytes = serde_json::to_vec(&canonical_manifest).map_err(|e| {
+        VerificationError::InvalidFormat(format!("Manifest JSON serialize failed: {e}"))
+    })?;
+    Ok(format!(
+        "blake3:{}",
+        hex::encode(blake3::hash(&manifest_bytes).as_bytes())
+    ))
+}
+
+/// Compute the payload hash in "blake3:<hex>" format.
+pub(crate) fn compute_payload_hash(payload: &[u8]) -> String {
+    format!("blake3:{}", hex::encode(blake3::hash(payload).as_bytes()))
+}
+
+/// Compute the payload hash of a `payload/` directory tree.
+///
+/// `files` maps each path below `payload/` to its own "blake3:<hex>" hash.
+/// The map is serialized as canonical JSON and hashed with BLAKE3, so the
+/// result covers file names as well as contents.
+pub(crate) fn compute_payload_tree_hash(files: &BTreeMap<String, String>) -> String {
+    let canonical = serde_json::to_vec(files).unwrap_or_default();
+    compute_payload_hash(&canonical)
+}
+
+/// Hash every file under `payload/` in a raw zip archive.
+fn hash_payload_dir<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<String, VerificationError> {
+    let names: Vec<String> = archive
+        .file_names()
+        .filter(|name| name.starts_with(crate::PAYLOAD_DIR) && !name.ends_with('/'))
+        .map(str::to_string)
+        .collect();
+    if names.is_empty() {
+        return Err(VerificationError::InvalidFormat(
+            "Missing payload".to_string(),
+        ));
+    }
+
+    let mut files = BTreeMap::new();
+    for name in names {
+        let mut entry = archive
+            .by_name(&name)
+            .map_err(|e| VerificationError::InvalidFormat(format!("Missing {name}: {e}")))?;
+        let mut hasher = blake3::Hasher::new();
+        std::io::copy(&mut entry, &mut hasher)
+            .map_err(|e| VerificationError::InvalidFormat(format!("Failed to read {name}: {e}")))?;
+        files.insert(
+            name[crate::PAYLOAD_DIR.len()..].to_string(),
+            format!("blake3:{}", hex::encode(hasher.finalize().as_bytes())),
+        );
+    }
+    Ok(compute_payload_tree_hash(&files))
+}
+
+/// Build the canonical bytes that are signed for a manifest signature.
+pub(crate) fn build_signing_payload(
+    signature: &ManifestSignature,
+    signer: &str,
+) -> Result<Vec<u8>, VerificationError> {
+    let mut signing_payload = serde_json::json!({
+        "manifest_hash": signature.manifest_hash,
+        "payload_hash": signature.payload_hash,
+        "timestamp": signature.timestamp,
+        "signer": signer,
+    });
+    // Only present when set, so signatures made before the field existed
+    // still verify.
+    if let Some(module_hash) = &signature.module_hash {
+        signing_payload["module_hash"] = module_hash.clone().into();
+    }
+    let canonical_payload = canonicalize_json(&signing_payload);
+    serde_json::to_vec(&canonical_payload).map_err(|e| {
+        VerificationError::InvalidFormat(format!("Signing payload serialize failed: {e}"))
+    })
+}
+
 fn canonicalize_json(value: &serde_json::Value) -> serde_json::Value {
     match value {
         serde_json::Value::Object(map) => {
@@ -416,6 +1205,7 @@ fn canonicalize_json(value: &serde_json::Value) -> serde_json::Value {
 /// Compute BLAKE3 hash of a `.sync` file
 ///
 /// Returns the hash in format "blake3:<64-char-hex>"
+#[cfg(feature = "fs")]
 pub fn compute_content_hash(path: &Path) -> Result<String, std::io::Error> {
     let bytes = std::fs::read(path)?;
     let hash = blake3::hash(&bytes);
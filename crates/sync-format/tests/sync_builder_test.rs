@@ -1,3 +1,5 @@
+#![cfg(feature = "fs")]
+
 use std::fs::File;
 use std::io::Write;
 use sync_format::{
@@ -11,29 +13,12 @@ fn sync_builder_creates_valid_archive() {
     let temp = TempDir::new().unwrap();
     let path = temp.path().join("builder-test.sync");
 
-    let manifest = SyncManifest {
-        sync: SyncSection {
-            version: "1.2".to_string(),
-            content_type: "text/plain".to_string(),
-            display_ext: "txt".to_string(),
-            variant: Default::default(),
-        },
-        meta: ManifestMetadata {
-            created_by: "builder".to_string(),
-            created_at: "2099-01-23T12:00:00Z".to_string(),
-            hash_algo: "blake3".to_string(),
-        },
-        policy: ManifestPolicy {
-            ttl: 3600,
-            timeout: 30,
-        },
-        permissions: Default::default(),
-        ownership: Default::default(),
-        verification: Default::default(),
-        capabilities: Default::default(),
-        signature: None,
-        encryption: Default::default(),
-    };
+    let mut manifest = SyncManifest::new(
+        SyncSection::new("text/plain", "txt"),
+        ManifestMetadata::new("builder", "2099-01-23T12:00:00Z"),
+    );
+    manifest.sync.version = "1.2".to_string();
+    manifest.policy = ManifestPolicy::new(3600, 30);
 
     SyncBuilder::new()
         .with_manifest(manifest)
@@ -111,6 +96,49 @@ fn sync_archive_missing_manifest_is_rejected() {
     assert!(matches!(err, Error::MissingEntry(entry) if entry == "manifest.toml"));
 }
 
+#[test]
+fn sync_archive_hostile_payload_name_is_rejected() {
+    for hostile in [
+        "payload//etc/passwd",
+        "payload/../../x",
+        "payload/a\\b",
+        "payload/./x",
+    ] {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("hostile.sync");
+
+        let file = File::create(&path).unwrap();
+        let mut zip = ZipWriter::new(file);
+
+        let options: FileOptions<()> =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+        zip.start_file("manifest.toml", options).unwrap();
+        zip.write_all(
+            b"[sync]\nversion = \"1.2\"\ncontent_type = \"text/plain\"\ndisplay_ext = \"txt\"\n\n\
+              [meta]\ncreated_by = \"test\"\ncreated_at = \"2024-01-01T00:00:00Z\"\n",
+        )
+        .unwrap();
+
+        zip.start_file("sync.wasm", options).unwrap();
+        zip.write_all(b"\0asm\x01\0\0\0").unwrap();
+
+        zip.start_file("payload", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+
+        zip.start_file(hostile, options).unwrap();
+        zip.write_all(b"owned").unwrap();
+
+        zip.finish().unwrap();
+
+        let err = SyncArchive::open(&path).expect_err("hostile payload name must fail");
+        assert!(
+            matches!(&err, Error::UnsafeEntryName(name) if name == hostile),
+            "{hostile}: {err:?}"
+        );
+    }
+}
+
 #[test]
 fn sync_archive_missing_wasm_is_rejected() {
     let temp = TempDir::new().unwrap();
@@ -121,6 +149,7 @@ fn sync_archive_missing_wasm_is_rejected() {
 version = "1.2"
 content_type = "text/plain"
 display_ext = "txt"
+variant = "app"
 
 [meta]
 created_by = "builder"
@@ -155,29 +184,12 @@ fn sync_archive_detects_optional_entries() {
     let temp = TempDir::new().unwrap();
     let path = temp.path().join("optional-entries.sync");
 
-    let manifest = SyncManifest {
-        sync: SyncSection {
-            version: "1.2".to_string(),
-            content_type: "text/plain".to_string(),
-            display_ext: "txt".to_string(),
-            variant: Default::default(),
-        },
-        meta: ManifestMetadata {
-            created_by: "builder".to_string(),
-            created_at: "2099-01-23T12:00:00Z".to_string(),
-            hash_algo: "blake3".to_string(),
-        },
-        policy: ManifestPolicy {
-            ttl: 3600,
-            timeout: 30,
-        },
-        permissions: Default::default(),
-        ownership: Default::default(),
-        verification: Default::default(),
-        capabilities: Default::default(),
-        signature: None,
-        encryption: Default::default(),
-    };
+    let mut manifest = SyncManifest::new(
+        SyncSection::new("text/plain", "txt"),
+        ManifestMetadata::new("builder", "2099-01-23T12:00:00Z"),
+    );
+    manifest.sync.version = "1.2".to_string();
+    manifest.policy = ManifestPolicy::new(3600, 30);
 
     SyncBuilder::new()
         .with_manifest(manifest)
@@ -210,29 +222,12 @@ fn sync_archive_update_payload_refreshes_offsets() {
     let temp = TempDir::new().unwrap();
     let path = temp.path().join("update-payload.sync");
 
-    let manifest = SyncManifest {
-        sync: SyncSection {
-            version: "1.2".to_string(),
-            content_type: "text/plain".to_string(),
-            display_ext: "txt".to_string(),
-            variant: Default::default(),
-        },
-        meta: ManifestMetadata {
-            created_by: "builder".to_string(),
-            created_at: "2099-01-23T12:00:00Z".to_string(),
-            hash_algo: "blake3".to_string(),
-        },
-        policy: ManifestPolicy {
-            ttl: 3600,
-            timeout: 30,
-        },
-        permissions: Default::default(),
-        ownership: Default::default(),
-        verification: Default::default(),
-        capabilities: Default::default(),
-        signature: None,
-        encryption: Default::default(),
-    };
+    let mut manifest = SyncManifest::new(
+        SyncSection::new("text/plain", "txt"),
+        ManifestMetadata::new("builder", "2099-01-23T12:00:00Z"),
+    );
+    manifest.sync.version = "1.2".to_string();
+    manifest.policy = ManifestPolicy::new(3600, 30);
 
     SyncBuilder::new()
         .with_manifest(manifest)
@@ -254,3 +249,213 @@ fn sync_archive_update_payload_refreshes_offsets() {
     assert_ne!(updated_offset, original_offset);
     assert!(archive.payload_entry().is_some());
 }
+
+#[test]
+fn sync_builder_streams_file_and_reader_sources() {
+    let temp = TempDir::new().unwrap();
+    let payload_path = temp.path().join("payload.csv");
+    std::fs::write(&payload_path, b"id,value\n1,streamed\n").unwrap();
+
+    let manifest_toml = r#"
+[sync]
+version = "1.2"
+content_type = "text/csv"
+display_ext = "csv"
+
+[meta]
+created_by = "builder"
+created_at = "2099-01-23T12:00:00Z"
+hash_algo = "blake3"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#;
+    let manifest = SyncManifest::from_toml(manifest_toml.as_bytes()).unwrap();
+
+    let path = temp.path().join("streamed.sync");
+    let builder = SyncBuilder::new()
+        .with_manifest(manifest)
+        .with_payload_file(&payload_path)
+        .with_wasm_reader(std::io::Cursor::new(b"\0asm\x01\0\0\0".to_vec()))
+        .with_context_reader(&br#"{"source":"reader"}"#[..]);
+    builder.write_to(&path).unwrap();
+
+    let archive = SyncArchive::open(&path).unwrap();
+    assert_eq!(archive.read_payload().unwrap(), b"id,value\n1,streamed\n");
+    assert!(archive.has_context());
+
+    // Readers are consumed by the first write.
+    let err = builder
+        .write_to(temp.path().join("second.sync"))
+        .expect_err("reader sources can only be written once");
+    assert!(matches!(err, Error::IoError(_)));
+    assert!(!temp.path().join("second.sync").exists());
+}
+
+#[test]
+fn sync_builder_writes_payload_directory() {
+    let temp = TempDir::new().unwrap();
+    let users_path = temp.path().join("users.csv");
+    std::fs::write(&users_path, b"id,name\n1,alice\n").unwrap();
+
+    let manifest_toml = r#"
+[sync]
+version = "1.2"
+content_type = "text/csv"
+display_ext = "csv"
+
+[meta]
+created_by = "builder"
+created_at = "2099-01-23T12:00:00Z"
+hash_algo = "blake3"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#;
+    let manifest = SyncManifest::from_toml(manifest_toml.as_bytes()).unwrap();
+
+    let path = temp.path().join("dataset.sync");
+    SyncBuilder::new()
+        .with_manifest(manifest.clone())
+        .add_payload_file("tables/users.csv", &users_path)
+        .add_payload_bytes("README.md", b"# dataset\n".to_vec())
+        .add_payload_reader("tables/orders.csv", &b"id,total\n7,42\n"[..])
+        .with_wasm_bytes(b"\0asm\x01\0\0\0")
+        .write_to(&path)
+        .unwrap();
+
+    let archive = SyncArchive::open(&path).unwrap();
+    assert!(archive.payload_entry().is_none());
+    assert!(archive.has_payload_dir());
+
+    let paths: Vec<&str> = archive
+        .payload_entries()
+        .map(|entry| entry.payload_path().unwrap())
+        .collect();
+    assert_eq!(
+        paths,
+        ["tables/users.csv", "README.md", "tables/orders.csv"]
+    );
+    assert!(archive
+        .payload_entries()
+        .all(|entry| entry.compression == zip::CompressionMethod::Stored));
+
+    let users = archive
+        .payload_file_reader("tables/users.csv")
+        .unwrap()
+        .read_all()
+        .unwrap();
+    assert_eq!(users, b"id,name\n1,alice\n");
+
+    let mut archive = archive;
+    assert!(matches!(
+        archive.update_payload(b"single"),
+        Err(Error::InvalidFormat(_))
+    ));
+
+    // Invalid trees are rejected before anything is written.
+    let invalid = [
+        vec!["../escape.csv"],
+        vec!["/absolute.csv"],
+        vec!["a//b.csv"],
+        vec!["dup.csv", "dup.csv"],
+        vec!["data", "data/inner.csv"],
+    ];
+    for paths in invalid {
+        let mut builder = SyncBuilder::new()
+            .with_manifest(manifest.clone())
+            .with_wasm_bytes(b"\0asm\x01\0\0\0");
+        for path in &paths {
+            builder = builder.add_payload_bytes(*path, b"x".to_vec());
+        }
+        let err = builder
+            .write_to(temp.path().join("invalid.sync"))
+            .expect_err("invalid payload tree");
+        assert!(matches!(err, Error::InvalidFormat(_)), "{paths:?}: {err}");
+    }
+
+    let err = SyncBuilder::new()
+        .with_manifest(manifest)
+        .with_payload_bytes(b"single")
+        .add_payload_bytes("extra.csv", b"x".to_vec())
+        .with_wasm_bytes(b"\0asm\x01\0\0\0")
+        .write_to(temp.path().join("mixed.sync"))
+        .expect_err("single payload and payload files are exclusive");
+    assert!(matches!(err, Error::InvalidFormat(_)));
+}
+
+#[cfg(feature = "signatures")]
+#[test]
+fn sync_builder_reproducible_output() {
+    use sync_format::{compute_content_hash, verify_manifest_signature, FixedClock, SyncSigner};
+
+    let temp = TempDir::new().unwrap();
+    let manifest_toml = r#"
+[sync]
+version = "1.3"
+content_type = "text/csv"
+display_ext = "csv"
+
+[meta]
+created_by = "builder"
+created_at = "2099-01-23T12:00:00Z"
+hash_algo = "blake3"
+
+[policy]
+ttl = 3600
+timeout = 30
+
+[permissions]
+allow_hosts = ["b.example", "a.example"]
+"#;
+    let manifest = SyncManifest::from_toml(manifest_toml.as_bytes()).unwrap();
+    let clock = FixedClock::from_unix_timestamp(1_700_000_000).unwrap();
+    let signer = SyncSigner::from_bytes(&[7u8; 32]).with_clock(clock);
+
+    let build = |name: &str, files: &[(&str, &[u8])], hosts: &[&str]| {
+        let mut manifest = manifest.clone();
+        manifest.permissions.allow_hosts = hosts.iter().map(|host| host.to_string()).collect();
+        let mut builder = SyncBuilder::new()
+            .with_manifest(manifest)
+            .with_signer(signer.clone())
+            .reproducible(true);
+        for (path, bytes) in files {
+            builder = builder.add_payload_bytes(*path, bytes.to_vec());
+        }
+        builder.write_to(temp.path().join(name)).unwrap()
+    };
+
+    let users: (&str, &[u8]) = ("users.csv", b"id,name\n1,alice\n");
+    let orders: (&str, &[u8]) = ("orders.csv", b"id,total\n7,42\n");
+    let first = build("first.sync", &[users, orders], &["b.example", "a.example"]);
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let second = build("second.sync", &[orders, users], &["a.example", "b.example"]);
+
+    assert_eq!(
+        std::fs::read(&first).unwrap(),
+        std::fs::read(&second).unwrap()
+    );
+    assert_eq!(
+        compute_content_hash(&first).unwrap(),
+        compute_content_hash(&second).unwrap()
+    );
+    assert!(verify_manifest_signature(&first).unwrap().valid);
+
+    let archive = SyncArchive::open(&first).unwrap();
+    let signature = archive.manifest().signature.as_ref().unwrap();
+    assert_eq!(signature.timestamp, "2023-11-14T22:13:20Z");
+    let paths: Vec<&str> = archive
+        .payload_entries()
+        .map(|entry| entry.payload_path().unwrap())
+        .collect();
+    assert_eq!(paths, ["orders.csv", "users.csv"]);
+
+    let mut zip = zip::ZipArchive::new(File::open(&first).unwrap()).unwrap();
+    for index in 0..zip.len() {
+        let entry = zip.by_index(index).unwrap();
+        assert_eq!(entry.last_modified(), Some(zip::DateTime::default()));
+        assert_eq!(entry.unix_mode().map(|mode| mode & 0o777), Some(0o644));
+    }
+}
@@ -1,9 +1,10 @@
 //! FUSE adapter implementation for `.sync` archives.
 //!
 //! This module implements the `fuser::Filesystem` trait for `SyncFuseFS`,
-//! providing zero-copy read access to archive contents.
+//! providing zero-copy read access to archive contents. Multi-file payloads
+//! appear as nested directories.
 
-use crate::vfs::{VfsEntry, VfsMount};
+use crate::vfs::{VfsEntry, VfsMount, VfsNode};
 use fuser::{
     FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyData, ReplyDirectory, ReplyEntry,
     ReplyOpen, Request, FUSE_ROOT_ID,
@@ -35,8 +36,12 @@ pub struct SyncFuseFS {
     archive_file: File,
     /// Inode to VfsEntry index mapping.
     inode_map: HashMap<u64, usize>,
-    /// Name to inode reverse lookup for fast lookups.
-    name_to_inode: HashMap<String, u64>,
+    /// Directory inode to parent directory inode (the root is its own parent).
+    directories: HashMap<u64, u64>,
+    /// Directory inode to its children as (inode, kind, name).
+    children: HashMap<u64, Vec<(u64, FileType, String)>>,
+    /// (parent inode, name) to inode reverse lookup for fast lookups.
+    name_to_inode: HashMap<(u64, String), u64>,
     /// User ID for file ownership (defaults to current user).
     uid: u32,
     /// Group ID for file ownership (defaults to current group).
@@ -60,19 +65,49 @@ impl SyncFuseFS {
         let file = File::open(archive_path.as_ref())?;
 
         let mut inode_map = HashMap::new();
-        let mut name_to_inode = HashMap::new();
+        let mut file_inodes = HashMap::new();
 
         // Assign inodes starting from 2 (1 is reserved for root)
         for (i, entry) in mount.entries().iter().enumerate() {
             let inode = (i + 2) as u64;
             inode_map.insert(inode, i);
-            name_to_inode.insert(entry.display_name.clone(), inode);
+            file_inodes.insert(entry.display_name.as_str(), inode);
             debug!(
                 "Mapped entry '{}' to inode {} (offset={}, size={})",
                 entry.display_name, inode, entry.offset, entry.size
             );
         }
 
+        // Directories get inodes after the files
+        let mut next_dir_inode = mount.entries().len() as u64 + 2;
+        let mut directories = HashMap::from([(FUSE_ROOT_ID, FUSE_ROOT_ID)]);
+        let mut children = HashMap::new();
+        let mut name_to_inode = HashMap::new();
+        let mut pending = vec![(FUSE_ROOT_ID, String::new())];
+
+        while let Some((dir_inode, dir_path)) = pending.pop() {
+            let mut listing = Vec::new();
+            for (name, node) in mount.read_dir(&dir_path).unwrap_or_default() {
+                let (inode, kind) = match node {
+                    VfsNode::File(entry) => (
+                        file_inodes[entry.display_name.as_str()],
+                        FileType::RegularFile,
+                    ),
+                    VfsNode::Directory(path) => {
+                        let inode = next_dir_inode;
+                        next_dir_inode += 1;
+                        directories.insert(inode, dir_inode);
+                        pending.push((inode, path.to_string()));
+                        debug!("Mapped directory '{}' to inode {}", path, inode);
+                        (inode, FileType::Directory)
+                    }
+                };
+                name_to_inode.insert((dir_inode, name.to_string()), inode);
+                listing.push((inode, kind, name.to_string()));
+            }
+            children.insert(dir_inode, listing);
+        }
+
         // Get current user/group IDs
         let uid = unsafe { libc::getuid() };
         let gid = unsafe { libc::getgid() };
@@ -81,6 +116,8 @@ impl SyncFuseFS {
             mount,
             archive_file: file,
             inode_map,
+            directories,
+            children,
             name_to_inode,
             uid,
             gid,
@@ -93,10 +130,10 @@ impl SyncFuseFS {
         self.mount.entries().len()
     }
 
-    /// Build file attributes for the root directory.
-    fn root_attr(&self) -> FileAttr {
+    /// Build file attributes for a directory.
+    fn dir_attr(&self, inode: u64) -> FileAttr {
         FileAttr {
-            ino: FUSE_ROOT_ID,
+            ino: inode,
             size: 0,
             blocks: 0,
             atime: self.mount_time,
@@ -137,8 +174,8 @@ impl SyncFuseFS {
 
     /// Get file attributes by inode.
     fn get_attr(&self, inode: u64) -> Option<FileAttr> {
-        if inode == FUSE_ROOT_ID {
-            return Some(self.root_attr());
+        if self.directories.contains_key(&inode) {
+            return Some(self.dir_attr(inode));
         }
 
         let index = self.inode_map.get(&inode)?;
@@ -166,14 +203,13 @@ impl Filesystem for SyncFuseFS {
         let name_str = name.to_string_lossy();
         trace!("lookup(parent={}, name='{}')", parent, name_str);
 
-        // Only root directory contains entries
-        if parent != FUSE_ROOT_ID {
-            debug!("lookup: parent {} is not root directory", parent);
+        if !self.directories.contains_key(&parent) {
+            debug!("lookup: parent {} is not a directory", parent);
             reply.error(ENOENT);
             return;
         }
 
-        match self.name_to_inode.get(name_str.as_ref()) {
+        match self.name_to_inode.get(&(parent, name_str.to_string())) {
             Some(&inode) => {
                 let attr = self.get_attr(inode).unwrap();
                 reply.entry(&TTL, &attr, 0);
@@ -196,20 +232,20 @@ impl Filesystem for SyncFuseFS {
     ) {
         trace!("readdir(ino={}, offset={})", ino, offset);
 
-        if ino != FUSE_ROOT_ID {
+        let Some(&parent) = self.directories.get(&ino) else {
             warn!("readdir: inode {} is not a directory", ino);
             reply.error(ENOTDIR);
             return;
-        }
+        };
 
         // Build entry list: ".", "..", and actual entries
         let mut entries: Vec<(u64, FileType, &str)> = vec![
-            (FUSE_ROOT_ID, FileType::Directory, "."),
-            (FUSE_ROOT_ID, FileType::Directory, ".."),
+            (ino, FileType::Directory, "."),
+            (parent, FileType::Directory, ".."),
         ];
 
-        for (i, entry) in self.mount.entries().iter().enumerate() {
-            entries.push(((i + 2) as u64, FileType::RegularFile, &entry.display_name));
+        for (child, kind, name) in self.children.get(&ino).into_iter().flatten() {
+            entries.push((*child, *kind, name));
         }
 
         // Skip to offset and add entries until buffer is full
@@ -228,7 +264,7 @@ impl Filesystem for SyncFuseFS {
     fn open(&mut self, _req: &Request, ino: u64, _flags: i32, reply: ReplyOpen) {
         trace!("open(ino={})", ino);
 
-        if ino == FUSE_ROOT_ID {
+        if self.directories.contains_key(&ino) {
             reply.error(ENOTDIR);
             return;
         }
@@ -311,7 +347,7 @@ impl Filesystem for SyncFuseFS {
         // Calculate total size of all entries
         let total_size: u64 = self.mount.entries().iter().map(|e| e.size).sum();
         let blocks = (total_size + (BLOCK_SIZE as u64) - 1) / (BLOCK_SIZE as u64);
-        let files = self.mount.entries().len() as u64 + 1; // +1 for root
+        let files = (self.mount.entries().len() + self.directories.len()) as u64;
 
         reply.statfs(
             blocks, // total blocks
@@ -8,6 +8,7 @@
 //! - Display name and path generation
 //! - **WebDAV server support** (recommended, with `webdav` feature)
 //! - FUSE filesystem support (optional, with `fuse` feature)
+//! - Decrypted vault payloads over WebDAV (with `webdav` and `vault` features)
 //!
 //! ## Example
 //!
@@ -54,6 +55,10 @@
 //! - Cross-platform (macOS, Windows, Linux)
 //! - Standard HTTP/WebDAV protocol
 //!
+//! With the `vault` feature, build the filesystem with
+//! `SyncDavFs::with_credential` and start it with `serve_fs_background` to
+//! serve a vault's plaintext. Range reads only decrypt the chunks they touch.
+//!
 //! ## FUSE Support (Optional)
 //!
 //! For power users on Linux, enable the `fuse` feature:
@@ -77,7 +82,7 @@ pub mod webdav;
 #[cfg(feature = "fuse")]
 pub mod fuse;
 
-pub use vfs::{VfsEntry, VfsMount, VfsMountConfig};
+pub use vfs::{VfsEntry, VfsMount, VfsMountConfig, VfsNode};
 
 // Re-export sync-format types for convenience
 pub use sync_format::{SyncArchive, SyncEntry};
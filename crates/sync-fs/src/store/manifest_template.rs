@@ -1,18 +1,17 @@
-use chrono::{SecondsFormat, Utc};
+use chrono::SecondsFormat;
+use std::sync::Arc;
 use sync_format::{
-    Manifest, ManifestMetadata, ManifestOwnership, ManifestPermissions, ManifestPolicy,
-    ManifestVerification, SyncSection,
+    Clock, Manifest, ManifestMetadata, ManifestPolicy, SyncSection, SyncVariant, SystemClock,
 };
 
-const DEFAULT_SYNC_VERSION: &str = "1.2";
-const DEFAULT_HASH_ALGO: &str = "blake3";
-
 #[derive(Debug, Clone)]
 pub struct ManifestTemplate {
     pub created_by: String,
     pub default_ttl: u64,
     pub default_timeout: u64,
     pub allow_hosts: Vec<String>,
+    pub variant: SyncVariant,
+    pub clock: Arc<dyn Clock>,
 }
 
 impl Default for ManifestTemplate {
@@ -22,41 +21,26 @@ impl Default for ManifestTemplate {
             default_ttl: 3600,
             default_timeout: 30,
             allow_hosts: Vec::new(),
+            variant: SyncVariant::default(),
+            clock: Arc::new(SystemClock),
         }
     }
 }
 
 impl ManifestTemplate {
     pub fn to_manifest(&self, content_type: &str, display_ext: &str) -> Manifest {
-        let created_at = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let created_at = self.clock.now().to_rfc3339_opts(SecondsFormat::Secs, true);
         let display_ext = normalize_display_ext(display_ext);
 
-        Manifest {
-            sync: SyncSection {
-                version: DEFAULT_SYNC_VERSION.to_string(),
-                content_type: content_type.to_string(),
-                display_ext,
-                variant: Default::default(),
-            },
-            meta: ManifestMetadata {
-                created_by: self.created_by.clone(),
-                created_at,
-                hash_algo: DEFAULT_HASH_ALGO.to_string(),
-            },
-            policy: ManifestPolicy {
-                ttl: self.default_ttl,
-                timeout: self.default_timeout,
-            },
-            permissions: ManifestPermissions {
-                allow_hosts: self.allow_hosts.clone(),
-                ..Default::default()
-            },
-            ownership: ManifestOwnership::default(),
-            verification: ManifestVerification::default(),
-            capabilities: Default::default(),
-            signature: None,
-            encryption: Default::default(),
-        }
+        let mut sync = SyncSection::new(content_type, display_ext);
+        sync.variant = self.variant.clone();
+        let meta = ManifestMetadata::new(self.created_by.clone(), created_at);
+
+        let mut manifest = Manifest::new(sync, meta);
+        manifest.policy = ManifestPolicy::new(self.default_ttl, self.default_timeout);
+        manifest.permissions.allow_hosts = self.allow_hosts.clone();
+        manifest.canonicalize();
+        manifest
     }
 }
 
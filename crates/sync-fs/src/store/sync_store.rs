@@ -2,10 +2,14 @@ use super::ManifestTemplate;
 use std::fs::{self, File};
 use std::io::{self, Seek, SeekFrom, Write};
 use std::path::{Component, Path, PathBuf};
+use sync_format::PAYLOAD_ALIGNMENT;
 use tempfile::NamedTempFile;
 use zip::write::FileOptions;
 use zip::{CompressionMethod, ZipArchive, ZipWriter};
 
+/// Unix permissions of every entry the store writes.
+const ENTRY_PERMISSIONS: u32 = 0o644;
+
 #[derive(Debug, thiserror::Error)]
 pub enum SyncStoreError {
     #[error("invalid sync name: {0}")]
@@ -18,6 +22,8 @@ pub enum SyncStoreError {
     Zip(#[from] zip::result::ZipError),
     #[error(transparent)]
     Toml(#[from] toml::ser::Error),
+    #[error(transparent)]
+    Format(#[from] sync_format::Error),
 }
 
 pub type SyncStoreResult<T> = Result<T, SyncStoreError>;
@@ -26,7 +32,7 @@ pub type SyncStoreResult<T> = Result<T, SyncStoreError>;
 pub struct SyncStore {
     base_dir: PathBuf,
     manifest_template: ManifestTemplate,
-    minimal_wasm: Vec<u8>,
+    wasm: Option<Vec<u8>>,
 }
 
 impl SyncStore {
@@ -41,12 +47,12 @@ impl SyncStore {
         Self {
             base_dir: base_dir.into(),
             manifest_template,
-            minimal_wasm: b"\0asm\x01\0\0\0".to_vec(),
+            wasm: None,
         }
     }
 
-    pub fn with_minimal_wasm(mut self, minimal_wasm: Vec<u8>) -> Self {
-        self.minimal_wasm = minimal_wasm;
+    pub fn with_wasm(mut self, wasm: Vec<u8>) -> Self {
+        self.wasm = Some(wasm);
         self
     }
 
@@ -74,6 +80,13 @@ impl SyncStore {
         let manifest = self
             .manifest_template
             .to_manifest(content_type, &display_ext);
+        let entries = ["manifest.toml", "payload"];
+        let wasm_entry = self.wasm.as_ref().map(|_| "sync.wasm");
+        manifest
+            .sync
+            .variant
+            .profile()
+            .check(entries.into_iter().chain(wasm_entry))?;
         let manifest_text = toml::to_string_pretty(&manifest)?;
 
         let mut payload_file = File::open(payload_path.as_ref())?;
@@ -88,7 +101,7 @@ impl SyncStore {
             temp.as_file_mut(),
             &manifest_text,
             &mut payload_file,
-            &self.minimal_wasm,
+            self.wasm.as_deref(),
         )?;
 
         persist_tempfile(temp, &sync_path)?;
@@ -163,20 +176,21 @@ fn write_new_archive<W: Write + Seek>(
     writer: &mut W,
     manifest_text: &str,
     payload: &mut File,
-    minimal_wasm: &[u8],
+    wasm: Option<&[u8]>,
 ) -> SyncStoreResult<()> {
     let mut zip = ZipWriter::new(writer);
-    let options: FileOptions<()> =
-        FileOptions::default().compression_method(CompressionMethod::Stored);
+    let options = entry_options(CompressionMethod::Stored);
 
     zip.start_file("manifest.toml", options)?;
     zip.write_all(manifest_text.as_bytes())?;
 
-    zip.start_file("payload", options)?;
+    zip.start_file("payload", options.with_alignment(PAYLOAD_ALIGNMENT))?;
     io::copy(payload, &mut zip)?;
 
-    zip.start_file("sync.wasm", options)?;
-    zip.write_all(minimal_wasm)?;
+    if let Some(wasm) = wasm {
+        zip.start_file("sync.wasm", options)?;
+        zip.write_all(wasm)?;
+    }
 
     zip.finish()?;
     Ok(())
@@ -196,14 +210,12 @@ fn write_updated_archive<W: Write + Seek>(
             continue;
         }
 
-        let options: FileOptions<()> =
-            FileOptions::default().compression_method(file.compression());
-        zip.start_file(&name, options)?;
+        zip.start_file(&name, entry_options(file.compression()))?;
         io::copy(&mut file, &mut zip)?;
     }
 
-    let payload_options: FileOptions<()> =
-        FileOptions::default().compression_method(CompressionMethod::Stored);
+    let payload_options =
+        entry_options(CompressionMethod::Stored).with_alignment(PAYLOAD_ALIGNMENT);
     zip.start_file("payload", payload_options)?;
     io::copy(payload, &mut zip)?;
 
@@ -211,6 +223,15 @@ fn write_updated_archive<W: Write + Seek>(
     Ok(())
 }
 
+/// Entry options with a fixed timestamp and permissions, so the same
+/// inputs always give the same archive bytes.
+fn entry_options(compression: CompressionMethod) -> FileOptions<'static, ()> {
+    FileOptions::default()
+        .compression_method(compression)
+        .last_modified_time(zip::DateTime::default())
+        .unix_permissions(ENTRY_PERMISSIONS)
+}
+
 fn persist_tempfile(temp: NamedTempFile, final_path: &Path) -> SyncStoreResult<()> {
     temp.persist(final_path)
         .map(|_| ())
@@ -1,12 +1,18 @@
 use std::path::PathBuf;
 use sync_format::SyncArchive;
 
+/// Content type used for files of a multi-file payload.
+const PAYLOAD_FILE_CONTENT_TYPE: &str = "application/octet-stream";
+
 /// Represents a virtual file entry mapped from a `.sync` archive.
 #[derive(Debug, Clone)]
 pub struct VfsEntry {
-    /// Internal name of the entry (e.g., "payload").
+    /// Internal name of the entry (e.g., "payload" or "payload/data/a.csv").
     pub name: String,
     /// User-visible display name with extension.
+    ///
+    /// For files of a multi-file payload this is the `/`-separated path
+    /// relative to the mount root (e.g., "data/a.csv").
     pub display_name: String,
     /// MIME type of the content.
     pub content_type: String,
@@ -16,10 +22,12 @@ pub struct VfsEntry {
     pub vfs_path: PathBuf,
     /// Byte offset within the archive.
     pub offset: u64,
-    /// Size in bytes.
+    /// Size in bytes, as stored (ciphertext size for encrypted entries).
     pub size: u64,
     /// Whether the entry is read-only.
     pub read_only: bool,
+    /// Whether the entry is stored encrypted (vault payloads).
+    pub encrypted: bool,
 }
 
 /// Configuration for VFS mounting.
@@ -43,6 +51,16 @@ impl Default for VfsMountConfig {
     }
 }
 
+/// A node in the directory tree of a [`VfsMount`].
+#[derive(Debug, Clone, Copy)]
+pub enum VfsNode<'a> {
+    /// A directory, given as its path relative to the mount root ("" for
+    /// the root itself).
+    Directory(&'a str),
+    /// A file backed by an archive entry.
+    File(&'a VfsEntry),
+}
+
 /// A virtual filesystem mount from a `.sync` archive.
 #[derive(Debug)]
 pub struct VfsMount {
@@ -60,12 +78,18 @@ impl VfsMount {
     }
 
     /// Create a VFS mount from an archive, automatically adding the payload.
+    ///
+    /// A multi-file payload is exposed as a directory tree at the mount root.
     pub fn from_archive(
         archive: &SyncArchive,
         config: VfsMountConfig,
     ) -> sync_format::Result<Self> {
         let mut mount = Self::new(config);
-        mount.add_payload_from_archive(archive)?;
+        if archive.payload_entry().is_none() && archive.has_payload_dir() {
+            mount.add_payload_dir_from_archive(archive)?;
+        } else {
+            mount.add_payload_from_archive(archive)?;
+        }
         Ok(mount)
     }
 
@@ -95,12 +119,49 @@ impl VfsMount {
             offset: payload_entry.offset,
             size: payload_entry.size,
             read_only: self.config.expose_as_read_only,
+            encrypted: archive.is_entry_encrypted("payload"),
         };
 
         self.entries.push(entry);
         Ok(())
     }
 
+    /// Add every file of a multi-file payload to this mount.
+    ///
+    /// Files keep their paths below `payload/`, relative to the mount path.
+    pub fn add_payload_dir_from_archive(
+        &mut self,
+        archive: &SyncArchive,
+    ) -> sync_format::Result<()> {
+        let file_path = PathBuf::from(archive.archive_path());
+        let mut added = false;
+
+        for payload_entry in archive.payload_entries() {
+            let Some(path) = payload_entry.payload_path() else {
+                continue;
+            };
+
+            self.entries.push(VfsEntry {
+                name: payload_entry.name.clone(),
+                display_name: path.to_string(),
+                content_type: PAYLOAD_FILE_CONTENT_TYPE.to_string(),
+                file_path: file_path.clone(),
+                vfs_path: self.config.mount_path.join(path),
+                offset: payload_entry.offset,
+                size: payload_entry.size,
+                read_only: self.config.expose_as_read_only,
+                encrypted: archive.is_entry_encrypted(&payload_entry.name),
+            });
+            added = true;
+        }
+
+        if added {
+            Ok(())
+        } else {
+            Err(sync_format::Error::PayloadNotFound)
+        }
+    }
+
     /// Get all virtual file entries.
     pub fn entries(&self) -> &[VfsEntry] {
         &self.entries
@@ -115,6 +176,64 @@ impl VfsMount {
     pub fn get_payload_entry(&self) -> Option<&VfsEntry> {
         self.entries.iter().find(|e| e.name == "payload")
     }
+
+    /// Resolve a path relative to the mount root.
+    ///
+    /// Leading and trailing slashes are ignored; the empty path is the root
+    /// directory. Directories exist implicitly as prefixes of file paths.
+    pub fn lookup(&self, path: &str) -> Option<VfsNode<'_>> {
+        let path = path.trim_matches('/');
+        if path.is_empty() {
+            return Some(VfsNode::Directory(""));
+        }
+
+        let mut directory = None;
+        for entry in &self.entries {
+            if entry.display_name == path {
+                return Some(VfsNode::File(entry));
+            }
+            if directory.is_none() && is_descendant(&entry.display_name, path) {
+                directory = Some(VfsNode::Directory(&entry.display_name[..path.len()]));
+            }
+        }
+        directory
+    }
+
+    /// List the children of a directory as `(name, node)` pairs.
+    ///
+    /// Returns `None` if `path` is not a directory.
+    pub fn read_dir(&self, path: &str) -> Option<Vec<(&str, VfsNode<'_>)>> {
+        let path = match self.lookup(path)? {
+            VfsNode::Directory(path) => path,
+            VfsNode::File(_) => return None,
+        };
+        let prefix_len = if path.is_empty() { 0 } else { path.len() + 1 };
+
+        let mut children: Vec<(&str, VfsNode<'_>)> = Vec::new();
+        for entry in &self.entries {
+            if !path.is_empty() && !is_descendant(&entry.display_name, path) {
+                continue;
+            }
+
+            let rest = &entry.display_name[prefix_len..];
+            match rest.find('/') {
+                None => children.push((rest, VfsNode::File(entry))),
+                Some(end) => {
+                    let name = &rest[..end];
+                    if !children.iter().any(|(existing, _)| *existing == name) {
+                        let dir = &entry.display_name[..prefix_len + end];
+                        children.push((name, VfsNode::Directory(dir)));
+                    }
+                }
+            }
+        }
+        Some(children)
+    }
+}
+
+/// Check whether `path` lies below the directory `dir`.
+fn is_descendant(path: &str, dir: &str) -> bool {
+    path.len() > dir.len() + 1 && path.starts_with(dir) && path.as_bytes()[dir.len()] == b'/'
 }
 
 fn build_display_name(base_name: &str, display_ext: &str, show_original_extension: bool) -> String {
@@ -1,9 +1,13 @@
 //! WebDAV filesystem implementation for `.sync` archives.
 //!
 //! This module implements the `dav_server::fs::DavFileSystem` trait,
-//! mapping VFS entries to WebDAV resources.
+//! mapping VFS entries to WebDAV resources. Multi-file payloads appear as
+//! nested collections.
+//!
+//! With the `vault` feature and a credential, encrypted vault payloads are
+//! served as plaintext. Range reads decrypt only the chunks they touch.
 
-use crate::vfs::{VfsEntry, VfsMount};
+use crate::vfs::{VfsEntry, VfsMount, VfsNode};
 use dav_server::davpath::DavPath;
 use dav_server::fs::{
     DavDirEntry, DavFile, DavFileSystem, DavMetaData, FsError, FsFuture, FsStream, OpenOptions,
@@ -17,6 +21,12 @@ use std::os::unix::fs::FileExt;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::SystemTime;
+#[cfg(feature = "vault")]
+use std::{collections::HashMap, io::Read, io::Seek};
+#[cfg(feature = "vault")]
+use sync_format::vault::{Credential, VaultReader};
+#[cfg(feature = "vault")]
+use sync_format::EntryReader;
 
 /// WebDAV filesystem adapter for `.sync` archives.
 ///
@@ -35,6 +45,17 @@ struct SyncDavFsInner {
     archive_path: PathBuf,
     /// Creation time for metadata.
     created: SystemTime,
+    /// Credential and plaintext sizes for encrypted entries.
+    #[cfg(feature = "vault")]
+    vault: Option<VaultAccess>,
+}
+
+/// Access to the encrypted entries of a vault.
+#[cfg(feature = "vault")]
+struct VaultAccess {
+    credential: Credential,
+    /// Plaintext size of each encrypted entry, by entry name.
+    plain_sizes: HashMap<String, u64>,
 }
 
 impl SyncDavFs {
@@ -50,30 +71,79 @@ impl SyncDavFs {
                 mount,
                 archive_path: archive_path.into(),
                 created: SystemTime::now(),
+                #[cfg(feature = "vault")]
+                vault: None,
             }),
         }
     }
 
-    /// Find an entry by path.
-    fn find_entry(&self, path: &DavPath) -> Option<&VfsEntry> {
-        let path_str = path.as_rel_ospath().to_string_lossy();
-        let name = path_str.trim_start_matches('/');
+    /// Create a WebDAV filesystem that decrypts vault entries.
+    ///
+    /// Encrypted entries are served as plaintext with their plaintext size.
+    /// Fails if `credential` cannot open them. Without a credential (see
+    /// [`new`](Self::new)) they are served as stored.
+    ///
+    /// Requires the `vault` feature.
+    #[cfg(feature = "vault")]
+    pub fn with_credential<P: Into<PathBuf>>(
+        mount: VfsMount,
+        archive_path: P,
+        credential: Credential,
+    ) -> sync_format::Result<Self> {
+        let archive_path = archive_path.into();
+        let mut plain_sizes = HashMap::new();
+        for entry in mount.entries().iter().filter(|entry| entry.encrypted) {
+            let mut reader = open_vault_reader(&archive_path, entry, &credential)?;
+            plain_sizes.insert(entry.name.clone(), reader.seek(SeekFrom::End(0))?);
+        }
 
-        if name.is_empty() {
-            return None; // Root directory
+        Ok(Self {
+            inner: Arc::new(SyncDavFsInner {
+                mount,
+                archive_path,
+                created: SystemTime::now(),
+                vault: Some(VaultAccess {
+                    credential,
+                    plain_sizes,
+                }),
+            }),
+        })
+    }
+
+    /// Size of a file as served.
+    fn file_len(&self, entry: &VfsEntry) -> u64 {
+        #[cfg(feature = "vault")]
+        if let Some(size) = self
+            .inner
+            .vault
+            .as_ref()
+            .and_then(|vault| vault.plain_sizes.get(&entry.name))
+        {
+            return *size;
         }
+        entry.size
+    }
 
-        self.inner
-            .mount
-            .entries()
-            .iter()
-            .find(|e| e.display_name == name)
+    /// Metadata for a VFS node.
+    fn node_meta(&self, node: VfsNode<'_>) -> SyncDavMetaData {
+        match node {
+            VfsNode::Directory(_) => SyncDavMetaData::directory(self.inner.created),
+            VfsNode::File(entry) => SyncDavMetaData::file(self.file_len(entry), self.inner.created),
+        }
     }
 
-    /// Check if path is root.
-    fn is_root(&self, path: &DavPath) -> bool {
+    /// Resolve a WebDAV path to a VFS node.
+    fn lookup(&self, path: &DavPath) -> Option<VfsNode<'_>> {
         let path_str = path.as_rel_ospath().to_string_lossy();
-        path_str == "/" || path_str.is_empty()
+        self.inner.mount.lookup(&path_str)
+    }
+
+    /// Find a file entry by path.
+    fn find_entry(&self, path: &DavPath) -> Option<&VfsEntry> {
+        match self.lookup(path)? {
+            VfsNode::File(entry) => Some(entry),
+            VfsNode::Directory(_) => None,
+        }
     }
 }
 
@@ -94,6 +164,18 @@ impl DavFileSystem for SyncDavFs {
             // Find the entry
             let entry = self.find_entry(path).ok_or(FsError::NotFound)?;
 
+            #[cfg(feature = "vault")]
+            if let Some(vault) = self.inner.vault.as_ref().filter(|_| entry.encrypted) {
+                let reader = open_vault_reader(&self.inner.archive_path, entry, &vault.credential)
+                    .map_err(|_| FsError::GeneralFailure)?;
+                let dav_file = SyncDavVaultFile {
+                    len: self.file_len(entry),
+                    reader,
+                    position: 0,
+                };
+                return Ok(Box::new(dav_file) as Box<dyn DavFile>);
+            }
+
             // Open the archive file
             let file = File::open(&self.inner.archive_path).map_err(|_| FsError::GeneralFailure)?;
 
@@ -114,19 +196,18 @@ impl DavFileSystem for SyncDavFs {
         trace!("read_dir({:?}, {:?})", path, meta);
 
         let result = (|| {
-            // Only root directory contains entries
-            if !self.is_root(path) {
-                return Err(FsError::NotFound);
-            }
-
-            // Build directory entries
-            let entries: Vec<Box<dyn DavDirEntry>> = self
+            let path_str = path.as_rel_ospath().to_string_lossy();
+            let children = self
                 .inner
                 .mount
-                .entries()
-                .iter()
-                .map(|entry| {
-                    Box::new(SyncDavDirEntry::new(entry.clone(), self.inner.created))
+                .read_dir(&path_str)
+                .ok_or(FsError::NotFound)?;
+
+            // Build directory entries
+            let entries: Vec<Box<dyn DavDirEntry>> = children
+                .into_iter()
+                .map(|(name, node)| {
+                    Box::new(SyncDavDirEntry::new(name, self.node_meta(node)))
                         as Box<dyn DavDirEntry>
                 })
                 .collect();
@@ -144,16 +225,8 @@ impl DavFileSystem for SyncDavFs {
         trace!("metadata({:?})", path);
 
         let result = (|| {
-            if self.is_root(path) {
-                // Root directory metadata
-                Ok(Box::new(SyncDavMetaData::directory(self.inner.created))
-                    as Box<dyn DavMetaData>)
-            } else {
-                // File metadata
-                let entry = self.find_entry(path).ok_or(FsError::NotFound)?;
-                Ok(Box::new(SyncDavMetaData::file(entry, self.inner.created))
-                    as Box<dyn DavMetaData>)
-            }
+            let node = self.lookup(path).ok_or(FsError::NotFound)?;
+            Ok(Box::new(self.node_meta(node)) as Box<dyn DavMetaData>)
         })();
 
         Box::pin(async move { result })
@@ -183,7 +256,7 @@ impl SyncDavFile {
 
 impl DavFile for SyncDavFile {
     fn metadata(&mut self) -> FsFuture<'_, Box<dyn DavMetaData>> {
-        let meta = SyncDavMetaData::file(&self.entry, SystemTime::now());
+        let meta = SyncDavMetaData::file(self.entry.size, SystemTime::now());
         Box::pin(async move { Ok(Box::new(meta) as Box<dyn DavMetaData>) })
     }
 
@@ -246,25 +319,117 @@ impl DavFile for SyncDavFile {
     }
 }
 
+/// Open a decrypting reader over an encrypted entry.
+#[cfg(feature = "vault")]
+fn open_vault_reader(
+    archive_path: &std::path::Path,
+    entry: &VfsEntry,
+    credential: &Credential,
+) -> sync_format::Result<VaultReader<EntryReader>> {
+    let reader = EntryReader::open(archive_path, entry.offset, entry.size)?;
+    credential.decrypt_reader(reader)
+}
+
+/// WebDAV file that decrypts a vault entry on read.
+#[cfg(feature = "vault")]
+struct SyncDavVaultFile {
+    /// Plaintext size.
+    len: u64,
+    reader: VaultReader<EntryReader>,
+    /// Current read position within the plaintext.
+    position: u64,
+}
+
+#[cfg(feature = "vault")]
+impl std::fmt::Debug for SyncDavVaultFile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SyncDavVaultFile")
+            .field("len", &self.len)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "vault")]
+impl DavFile for SyncDavVaultFile {
+    fn metadata(&mut self) -> FsFuture<'_, Box<dyn DavMetaData>> {
+        let meta = SyncDavMetaData::file(self.len, SystemTime::now());
+        Box::pin(async move { Ok(Box::new(meta) as Box<dyn DavMetaData>) })
+    }
+
+    fn read_bytes(&mut self, count: usize) -> FsFuture<'_, bytes::Bytes> {
+        let to_read = std::cmp::min(count as u64, self.len.saturating_sub(self.position)) as usize;
+
+        let result = (|| {
+            let mut buffer = vec![0u8; to_read];
+            if to_read == 0 {
+                return Ok(buffer);
+            }
+            self.reader.seek(SeekFrom::Start(self.position))?;
+            self.reader.read_exact(&mut buffer)?;
+            self.position += to_read as u64;
+            Ok::<_, std::io::Error>(buffer)
+        })();
+
+        Box::pin(async move {
+            let buffer = result.map_err(|_| FsError::GeneralFailure)?;
+            Ok(bytes::Bytes::from(buffer))
+        })
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> FsFuture<'_, u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(n) => n as i64,
+            SeekFrom::End(n) => self.len as i64 + n,
+            SeekFrom::Current(n) => self.position as i64 + n,
+        };
+
+        if new_pos < 0 {
+            return Box::pin(async { Err(FsError::GeneralFailure) });
+        }
+
+        self.position = new_pos as u64;
+        let pos = self.position;
+        Box::pin(async move { Ok(pos) })
+    }
+
+    fn write_buf(&mut self, _buf: Box<dyn bytes::Buf + Send>) -> FsFuture<'_, ()> {
+        // Read-only filesystem
+        Box::pin(async { Err(FsError::Forbidden) })
+    }
+
+    fn write_bytes(&mut self, _buf: bytes::Bytes) -> FsFuture<'_, ()> {
+        // Read-only filesystem
+        Box::pin(async { Err(FsError::Forbidden) })
+    }
+
+    fn flush(&mut self) -> FsFuture<'_, ()> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
 /// WebDAV directory entry.
 struct SyncDavDirEntry {
-    entry: VfsEntry,
-    created: SystemTime,
+    name: String,
+    meta: SyncDavMetaData,
 }
 
 impl SyncDavDirEntry {
-    fn new(entry: VfsEntry, created: SystemTime) -> Self {
-        Self { entry, created }
+    fn new(name: &str, meta: SyncDavMetaData) -> Self {
+        Self {
+            name: name.to_string(),
+            meta,
+        }
     }
 }
 
 impl DavDirEntry for SyncDavDirEntry {
     fn name(&self) -> Vec<u8> {
-        self.entry.display_name.as_bytes().to_vec()
+        self.name.as_bytes().to_vec()
     }
 
     fn metadata(&self) -> FsFuture<'_, Box<dyn DavMetaData>> {
-        let meta = SyncDavMetaData::file(&self.entry, self.created);
+        let meta = self.meta.clone();
         Box::pin(async move { Ok(Box::new(meta) as Box<dyn DavMetaData>) })
     }
 }
@@ -288,10 +453,10 @@ impl SyncDavMetaData {
         }
     }
 
-    fn file(entry: &VfsEntry, time: SystemTime) -> Self {
+    fn file(len: u64, time: SystemTime) -> Self {
         Self {
             is_dir: false,
-            len: entry.size,
+            len,
             modified: time,
             created: time,
         }
@@ -315,3 +480,71 @@ impl DavMetaData for SyncDavMetaData {
         Ok(self.created)
     }
 }
+
+#[cfg(all(test, feature = "vault"))]
+mod tests {
+    use super::*;
+    use crate::VfsMountConfig;
+    use futures::executor::block_on;
+    use sync_format::vault::Protection;
+    use sync_format::SecretString;
+    use sync_format::{SyncArchive, SyncBuilder, SyncManifest};
+
+    const MANIFEST: &str = r#"
+[sync]
+version = "1.3"
+content_type = "application/octet-stream"
+display_ext = "bin"
+
+[meta]
+created_by = "vault-test"
+created_at = "2099-01-01T00:00:00Z"
+hash_algo = "blake3"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#;
+
+    #[test]
+    fn test_vault_payload_range_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vault.bin.sync");
+        let payload: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        let passphrase = SecretString::from("hunter2".to_string());
+
+        SyncBuilder::new()
+            .with_manifest(SyncManifest::from_toml(MANIFEST.as_bytes()).unwrap())
+            .with_payload_bytes(payload.clone())
+            .with_wasm_bytes(vec![0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00])
+            .with_vault(Protection::Passphrase(passphrase.clone()))
+            .write_to(&path)
+            .unwrap();
+
+        let archive = SyncArchive::open(&path).unwrap();
+        let mount = VfsMount::from_archive(&archive, VfsMountConfig::default()).unwrap();
+        assert!(mount.entries()[0].encrypted);
+
+        let fs =
+            SyncDavFs::with_credential(mount, &path, Credential::Passphrase(passphrase)).unwrap();
+        let dav_path = DavPath::new("/vault.bin").unwrap();
+        let meta = block_on(fs.metadata(&dav_path)).unwrap();
+        assert_eq!(meta.len(), payload.len() as u64);
+
+        let mut file = block_on(fs.open(
+            &dav_path,
+            OpenOptions {
+                read: true,
+                ..Default::default()
+            },
+        ))
+        .unwrap();
+        block_on(file.seek(SeekFrom::Start(150_000))).unwrap();
+        let range = block_on(file.read_bytes(1000)).unwrap();
+        assert_eq!(&range[..], &payload[150_000..151_000]);
+
+        block_on(file.seek(SeekFrom::End(-10))).unwrap();
+        let tail = block_on(file.read_bytes(100)).unwrap();
+        assert_eq!(&tail[..], &payload[payload.len() - 10..]);
+    }
+}
@@ -34,7 +34,7 @@ mod writable;
 pub use filesystem::SyncDavFs;
 pub use remote::{CacheConfig, RemoteMount, RemoteMountConfig};
 pub use server::{
-    serve, serve_background, serve_dual_background, serve_writable, serve_writable_background,
-    SyncWebDavServer,
+    serve, serve_background, serve_dual_background, serve_fs_background, serve_writable,
+    serve_writable_background, SyncWebDavServer,
 };
 pub use writable::WritableSyncFs;
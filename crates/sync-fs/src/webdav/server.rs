@@ -218,8 +218,21 @@ pub async fn serve_background<P: AsRef<Path>>(
     vfs: VfsMount,
     port: u16,
 ) -> io::Result<SyncWebDavServer> {
-    let addr: SocketAddr = ([127, 0, 0, 1], port).into();
     let fs = SyncDavFs::new(vfs, archive_path.as_ref().to_path_buf());
+    serve_fs_background(fs, port).await
+}
+
+/// Start a WebDAV server for a prepared [`SyncDavFs`] in the background.
+///
+/// Use this to serve a filesystem built with options `serve_background`
+/// does not expose, such as [`SyncDavFs::with_credential`] for vaults.
+///
+/// # Arguments
+///
+/// * `fs` - WebDAV filesystem to serve
+/// * `port` - Port to listen on (0 for auto-assign)
+pub async fn serve_fs_background(fs: SyncDavFs, port: u16) -> io::Result<SyncWebDavServer> {
+    let addr: SocketAddr = ([127, 0, 0, 1], port).into();
 
     // Build WebDAV handler
     let dav_server = DavHandler::builder()
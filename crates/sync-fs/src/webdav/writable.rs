@@ -820,7 +820,10 @@ fn map_store_error(err: SyncStoreError) -> FsError {
     match err {
         SyncStoreError::InvalidName(_) | SyncStoreError::InvalidPath(_) => FsError::Forbidden,
         SyncStoreError::Io(err) => map_io_error(err),
-        SyncStoreError::Zip(_) | SyncStoreError::Toml(_) => FsError::GeneralFailure,
+        SyncStoreError::Format(SyncError::IoError(err)) => map_io_error(err),
+        SyncStoreError::Zip(_) | SyncStoreError::Toml(_) | SyncStoreError::Format(_) => {
+            FsError::GeneralFailure
+        }
     }
 }
 
@@ -0,0 +1,71 @@
+use std::fs;
+use std::sync::Arc;
+use sync_format::{Error, FixedClock, SyncArchive, SyncVariant};
+use sync_fs::store::{ManifestTemplate, SyncStore, SyncStoreError};
+use tempfile::TempDir;
+
+#[test]
+fn store_creates_plain_archives_without_wasm() {
+    let temp = TempDir::new().unwrap();
+    let payload_path = temp.path().join("notes.txt");
+    fs::write(&payload_path, "hello").unwrap();
+
+    let store = SyncStore::new(temp.path().join("store"));
+    let sync_path = store
+        .create_sync_from_path("notes.txt", &payload_path, "text/plain")
+        .unwrap();
+
+    let archive = SyncArchive::open(&sync_path).unwrap();
+    assert!(!archive.has_wasm());
+    assert_eq!(archive.read_payload().unwrap(), b"hello");
+}
+
+#[test]
+fn store_requires_wasm_for_app_archives() {
+    let temp = TempDir::new().unwrap();
+    let payload_path = temp.path().join("index.html");
+    fs::write(&payload_path, "<html></html>").unwrap();
+
+    let template = ManifestTemplate {
+        variant: SyncVariant::App,
+        ..Default::default()
+    };
+    let store = SyncStore::with_template(temp.path().join("store"), template);
+    let err = store
+        .create_sync_from_path("app.html", &payload_path, "text/html")
+        .unwrap_err();
+    assert!(
+        matches!(err, SyncStoreError::Format(Error::MissingEntry(ref entry)) if entry == "sync.wasm")
+    );
+
+    let sync_path = store
+        .with_wasm(b"\0asm\x01\0\0\0".to_vec())
+        .create_sync_from_path("app.html", &payload_path, "text/html")
+        .unwrap();
+    assert!(SyncArchive::open(&sync_path).unwrap().has_wasm());
+}
+
+#[test]
+fn store_rebuilds_identical_archives_with_a_fixed_clock() {
+    let temp = TempDir::new().unwrap();
+    let payload_path = temp.path().join("notes.txt");
+    fs::write(&payload_path, "hello").unwrap();
+
+    let template = ManifestTemplate {
+        clock: Arc::new(FixedClock::from_unix_timestamp(1_700_000_000).unwrap()),
+        ..Default::default()
+    };
+    let build = |dir: &str| {
+        SyncStore::with_template(temp.path().join(dir), template.clone())
+            .create_sync_from_path("notes.txt", &payload_path, "text/plain")
+            .unwrap()
+    };
+
+    let first = build("first");
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    let second = build("second");
+    assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+
+    let archive = SyncArchive::open(&first).unwrap();
+    assert_eq!(archive.manifest().meta.created_at, "2023-11-14T22:13:20Z");
+}
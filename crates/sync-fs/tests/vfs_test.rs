@@ -2,7 +2,7 @@ use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use sync_format::SyncArchive;
-use sync_fs::{VfsMount, VfsMountConfig};
+use sync_fs::{VfsMount, VfsMountConfig, VfsNode};
 use tempfile::TempDir;
 use zip::{write::FileOptions, ZipWriter};
 
@@ -63,3 +63,82 @@ fn vfs_display_name_and_path_are_computed() {
     assert_eq!(entry.display_name, "report.csv");
     assert_eq!(entry.vfs_path, PathBuf::from("/mnt/report.csv"));
 }
+
+#[test]
+fn vfs_exposes_payload_directory_as_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let sync_path = temp_dir.path().join("dataset.sync");
+
+    let file = File::create(&sync_path).unwrap();
+    let mut zip = ZipWriter::new(file);
+    let options: FileOptions<()> =
+        FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+    zip.start_file("manifest.toml", options).unwrap();
+    zip.write_all(
+        br#"
+[sync]
+version = "1.2"
+content_type = "text/csv"
+display_ext = "csv"
+
+[meta]
+created_by = "Capsule Sync Test"
+created_at = "2099-01-23T12:00:00Z"
+hash_algo = "blake3"
+
+[policy]
+ttl = 3600
+timeout = 30
+"#,
+    )
+    .unwrap();
+    for (name, data) in [
+        ("payload/README.md", "readme"),
+        ("payload/tables/users.csv", "id\n1\n"),
+        ("payload/tables/2024/orders.csv", "id\n7\n"),
+    ] {
+        zip.start_file(name, options).unwrap();
+        zip.write_all(data.as_bytes()).unwrap();
+    }
+    zip.start_file("sync.wasm", options).unwrap();
+    zip.write_all(b"\0asm\x01\0\0\0").unwrap();
+    zip.finish().unwrap();
+
+    let archive = SyncArchive::open(&sync_path).unwrap();
+    let config = VfsMountConfig {
+        mount_path: PathBuf::from("/mnt"),
+        ..VfsMountConfig::default()
+    };
+    let mount = VfsMount::from_archive(&archive, config).unwrap();
+
+    assert!(mount.get_payload_entry().is_none());
+    assert_eq!(mount.entries().len(), 3);
+
+    let root: Vec<&str> = mount
+        .read_dir("")
+        .unwrap()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(root, ["README.md", "tables"]);
+
+    let tables = mount.read_dir("/tables/").unwrap();
+    assert_eq!(tables.len(), 2);
+    assert!(matches!(tables[0], ("users.csv", VfsNode::File(_))));
+    assert!(matches!(
+        tables[1],
+        ("2024", VfsNode::Directory("tables/2024"))
+    ));
+
+    match mount.lookup("tables/2024/orders.csv") {
+        Some(VfsNode::File(entry)) => {
+            assert_eq!(entry.name, "payload/tables/2024/orders.csv");
+            assert_eq!(entry.vfs_path, PathBuf::from("/mnt/tables/2024/orders.csv"));
+            assert_eq!(entry.size, 5);
+        }
+        other => panic!("unexpected lookup result: {other:?}"),
+    }
+    assert!(mount.lookup("tab").is_none());
+    assert!(mount.read_dir("README.md").is_none());
+}
@@ -6,6 +6,7 @@
 //! - Guest session lifecycle management
 //! - WASM module execution with sandboxing
 //! - Permission enforcement and policy application
+//! - Optional signer trust checks before every request
 //!
 //! ## Example
 //!
@@ -31,4 +32,7 @@ pub use guest::{
 pub use session::{GuestSession, WidgetBounds};
 
 // Re-export sync-format types for convenience
-pub use sync_format::{ManifestPermissions, SyncArchive, SyncBuilder, SyncEntry, SyncManifest};
+pub use sync_format::{
+    ManifestPermissions, SyncArchive, SyncBuilder, SyncEntry, SyncManifest, TrustStore,
+    TrustVerdict,
+};
@@ -9,7 +9,7 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
-use sync_format::{ManifestPermissions, SyncArchive};
+use sync_format::{verify_with_trust, ManifestPermissions, SyncArchive, TrustStore};
 
 static REQUEST_COUNTER: AtomicU64 = AtomicU64::new(1);
 
@@ -34,6 +34,8 @@ pub struct GuestSession {
     pub memory_limit_mb: Option<u64>,
     /// Widget bounds for UI mode.
     pub widget_bounds: Option<WidgetBounds>,
+    /// Trust store the archive signer must satisfy, if any.
+    pub trust_store: Option<TrustStore>,
 }
 
 /// Bounds for widget rendering.
@@ -77,6 +79,7 @@ impl GuestSession {
             cpu_limit_ms: None,
             memory_limit_mb: None,
             widget_bounds: None,
+            trust_store: None,
         })
     }
 
@@ -201,6 +204,32 @@ impl GuestSession {
         Ok(())
     }
 
+    /// Require the archive to be signed by a signer trusted by `store`.
+    ///
+    /// The archive is checked now and again before every request, so a
+    /// session refuses an archive that is re-signed or tampered with later.
+    pub fn require_trust(&mut self, store: TrustStore) -> Result<(), GuestError> {
+        self.trust_store = Some(store);
+        self.verify_trust()
+    }
+
+    /// Verify the archive signer against the session trust store, if set.
+    pub fn verify_trust(&self) -> Result<(), GuestError> {
+        let Some(store) = &self.trust_store else {
+            return Ok(());
+        };
+
+        let verdict = verify_with_trust(&self.sync_path, store)
+            .map_err(|e| GuestError::new(GuestErrorCode::IoError, e.to_string()))?;
+        if !verdict.is_trusted() {
+            return Err(GuestError::new(
+                GuestErrorCode::PermissionDenied,
+                format!("untrusted archive: {}", verdict),
+            ));
+        }
+        Ok(())
+    }
+
     /// Execute a read payload action.
     pub fn execute_read_payload(&self) -> Result<GuestResponse, GuestError> {
         self.execute_request(GuestAction::ReadPayload, Value::Null)
@@ -279,6 +308,7 @@ impl GuestSession {
         input: Value,
     ) -> Result<GuestResponse, GuestError> {
         self.verify_permissions(&action)?;
+        self.verify_trust()?;
 
         let command_path = self.host_app.as_ref().ok_or_else(|| {
             GuestError::new(GuestErrorCode::HostUnavailable, "Host app not configured")
@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use sync_runtime::{GuestAction, GuestErrorCode, GuestSession, WidgetBounds};
+use sync_runtime::{GuestAction, GuestErrorCode, GuestSession, TrustStore, WidgetBounds};
 use tempfile::TempDir;
 use zip::{write::FileOptions, ZipWriter};
 
@@ -117,3 +117,18 @@ fn widget_bounds_requires_positive_dimensions() {
 
     assert!(matches!(err.code, GuestErrorCode::InvalidRequest));
 }
+
+#[test]
+fn untrusted_archive_is_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let sync_path = create_test_sync_file(temp_dir.path());
+
+    let mut session = GuestSession::new(sync_path).unwrap();
+    let err = session
+        .require_trust(TrustStore::new())
+        .expect_err("unsigned archive should not be trusted");
+
+    assert!(matches!(err.code, GuestErrorCode::PermissionDenied));
+    assert!(err.message.starts_with("untrusted archive"));
+    assert!(session.execute_read_payload().is_err());
+}
@@ -35,6 +35,10 @@ pub enum Error {
     #[error("Execution timeout")]
     Timeout,
 
+    /// The archive signer is not trusted.
+    #[error("Untrusted archive: {0}")]
+    Untrusted(String),
+
     /// Invalid input parameter.
     #[error("Invalid input: {0}")]
     InvalidInput(String),
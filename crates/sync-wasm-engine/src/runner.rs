@@ -1,8 +1,8 @@
 use crate::host::{HostFunctions, HostState};
 use crate::{Error, ExecutionResult, Result};
 use secrecy::SecretString;
-use std::io::{Read, Seek, SeekFrom};
-use sync_format::{SyncArchive, SyncVariant};
+use sync_format::vault::{Credential, Identity};
+use sync_format::{verify_with_trust_from_reader, SyncArchive, SyncVariant, TrustStore};
 use wasmtime::{Engine, Linker, Module, Store};
 
 /// Configuration for WASM execution.
@@ -30,7 +30,8 @@ impl Default for ExecutionConfig {
 pub struct WasmRunner {
     engine: Engine,
     config: ExecutionConfig,
-    password: Option<SecretString>,
+    credential: Option<Credential>,
+    trust_store: Option<TrustStore>,
     decrypted_payload: Option<Vec<u8>>,
 }
 
@@ -41,7 +42,8 @@ impl WasmRunner {
         Ok(Self {
             engine,
             config: ExecutionConfig::default(),
-            password: None,
+            credential: None,
+            trust_store: None,
             decrypted_payload: None,
         })
     }
@@ -54,7 +56,21 @@ impl WasmRunner {
 
     /// Set the password for vault (encrypted) archives.
     pub fn with_password(mut self, password: SecretString) -> Self {
-        self.password = Some(password);
+        self.credential = Some(Credential::Passphrase(password));
+        self
+    }
+
+    /// Set recipient identities for vault (encrypted) archives.
+    ///
+    /// Any identity that the vault was encrypted to can open it.
+    pub fn with_identities(mut self, identities: Vec<Identity>) -> Self {
+        self.credential = Some(Credential::Identities(identities));
+        self
+    }
+
+    /// Only execute archives whose signer is trusted by `store`.
+    pub fn with_trust_store(mut self, store: TrustStore) -> Self {
+        self.trust_store = Some(store);
         self
     }
 
@@ -65,15 +81,23 @@ impl WasmRunner {
         action: &str,
         _input: Option<serde_json::Value>,
     ) -> Result<ExecutionResult> {
-        let payload_data = self.read_payload_data(archive)?;
+        if let Some(store) = &self.trust_store {
+            // Verify the bytes this handle reads, not whatever is at the path now.
+            let verdict = verify_with_trust_from_reader(archive.archive_reader()?, store)?;
+            if !verdict.is_trusted() {
+                return Err(Error::Untrusted(verdict.to_string()));
+            }
+        }
 
         let is_vault = archive.manifest().sync.variant == SyncVariant::Vault;
-        if is_vault && self.password.is_none() {
+        if is_vault && self.credential.is_none() {
             return Err(Error::InvalidInput(
-                "Vault archive requires password".to_string(),
+                "Vault archive requires a password or identities".to_string(),
             ));
         }
 
+        let payload_data = self.read_payload_data(archive)?;
+
         if is_vault {
             self.decrypted_payload = Some(payload_data.clone());
         }
@@ -112,10 +136,13 @@ impl WasmRunner {
             };
 
             let payload_updated = if let Some(new_payload) = updated {
-                if is_vault {
-                    self.decrypted_payload = Some(new_payload.clone());
+                match &self.credential {
+                    Some(credential) if is_vault => {
+                        archive.write_payload_with_credential(&new_payload, credential)?;
+                        self.decrypted_payload = Some(new_payload);
+                    }
+                    _ => archive.update_payload(&new_payload)?,
                 }
-                archive.update_payload(&new_payload)?;
                 true
             } else {
                 false
@@ -144,34 +171,34 @@ impl WasmRunner {
         Ok(result)
     }
 
-    /// Read payload data from archive.
+    /// Read payload data from archive, decrypting it for vaults.
     fn read_payload_data(&self, archive: &SyncArchive) -> Result<Vec<u8>> {
-        let entry = archive
-            .payload_entry()
-            .ok_or_else(|| Error::InvalidInput("No payload found".to_string()))?;
-
-        let mut file = std::fs::File::open(archive.archive_path())?;
-        file.seek(SeekFrom::Start(entry.offset))?;
-
-        let mut data = vec![0u8; entry.size as usize];
-        file.read_exact(&mut data)?;
+        if archive.payload_entry().is_none() {
+            return Err(Error::InvalidInput("No payload found".to_string()));
+        }
 
-        Ok(data)
+        self.read_entry(archive, "payload")
     }
 
-    /// Read WASM data from archive.
+    /// Read WASM data from archive, decrypting it if the vault encrypts it.
     fn read_wasm_data(&self, archive: &SyncArchive) -> Result<Vec<u8>> {
-        let entry = archive
-            .entry("sync.wasm")
-            .ok_or_else(|| Error::InvalidInput("No sync.wasm found".to_string()))?;
-
-        let mut file = std::fs::File::open(archive.archive_path())?;
-        file.seek(SeekFrom::Start(entry.offset))?;
+        if !archive.has_wasm() {
+            return Err(Error::InvalidInput("No sync.wasm found".to_string()));
+        }
 
-        let mut data = vec![0u8; entry.size as usize];
-        file.read_exact(&mut data)?;
+        self.read_entry(archive, "sync.wasm")
+    }
 
-        Ok(data)
+    /// Read an entry, decrypting it with the configured credential.
+    fn read_entry(&self, archive: &SyncArchive, name: &str) -> Result<Vec<u8>> {
+        match &self.credential {
+            Some(credential) => Ok(archive.read_entry_with_credential(name, credential)?),
+            None if archive.is_entry_encrypted(name) => Err(Error::InvalidInput(format!(
+                "{} is encrypted and requires a credential",
+                name
+            ))),
+            None => Ok(archive.entry_reader(name)?.read_all()?),
+        }
     }
 
     /// Get the decrypted payload (if available).